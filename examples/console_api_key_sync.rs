@@ -5,7 +5,7 @@
 //!
 //! Run with: cargo run --example 02_console_api_key_sync
 
-use anthropic_auth::{OAuthClient, OAuthConfig, OAuthMode};
+use anthropic_auth::{ExchangeRequest, OAuthClient, OAuthConfig, OAuthMode};
 use std::io::{self, Write};
 
 fn main() -> anyhow::Result<()> {
@@ -28,7 +28,7 @@ fn main() -> anyhow::Result<()> {
     let response = response.trim();
 
     println!("\n🔄 Exchanging code for tokens...");
-    let tokens = client.exchange_code(response, &flow.state, &flow.verifier)?;
+    let tokens = client.exchange(&ExchangeRequest::new(response, &flow))?;
 
     println!("✅ Got OAuth tokens!");
 
@@ -5,7 +5,7 @@
 //!
 //! Run with: cargo run --example 01_max_subscription_sync
 
-use anthropic_auth::{open_browser, OAuthClient, OAuthConfig, OAuthMode};
+use anthropic_auth::{open_browser, ExchangeRequest, OAuthClient, OAuthConfig, OAuthMode};
 use std::io::{self, Write};
 
 fn main() -> anyhow::Result<()> {
@@ -36,7 +36,7 @@ fn main() -> anyhow::Result<()> {
     let response = response.trim();
 
     println!("\n🔄 Exchanging code for tokens...");
-    let tokens = client.exchange_code(response, &flow.state, &flow.verifier)?;
+    let tokens = client.exchange(&ExchangeRequest::new(response, &flow))?;
 
     println!("\n✅ Success!");
     println!(
@@ -0,0 +1,8 @@
+#![no_main]
+
+use anthropic_auth::fuzzing::parse_callback_query;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|query: &str| {
+    let _ = parse_callback_query(query);
+});
@@ -0,0 +1,13 @@
+#![no_main]
+
+use anthropic_auth::fuzzing::parse_code_and_state;
+use libfuzzer_sys::fuzz_target;
+
+const EXPECTED_STATE: &str = "FgE6g_6khGKFFhXAw3tULPM00CPaqgE3Cq6id79Surg";
+
+fuzz_target!(|input: &str| {
+    if let Ok(callback) = parse_code_and_state(input, EXPECTED_STATE) {
+        // A parsed response must never carry a state other than the expected one
+        assert_eq!(callback.state, EXPECTED_STATE);
+    }
+});
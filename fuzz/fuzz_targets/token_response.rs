@@ -0,0 +1,10 @@
+#![no_main]
+
+use anthropic_auth::fuzzing::parse_token_response;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|body: &[u8]| {
+    if let Ok(tokens) = parse_token_response(body) {
+        assert!(tokens.validate().is_ok());
+    }
+});
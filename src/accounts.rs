@@ -0,0 +1,156 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+use crate::{AnthropicAuthError, Result, TokenManager, TokenSet};
+
+/// Outcome of refreshing one account in [`AccountManager::refresh_all`]
+#[derive(Debug)]
+pub struct AccountRefresh {
+    /// Name the account was added under
+    pub account: String,
+    /// The refreshed tokens, or why refreshing failed
+    pub result: Result<TokenSet>,
+}
+
+impl AccountRefresh {
+    /// Check whether the account's user must log in again
+    ///
+    /// True if the account holds no tokens or the server rejected its refresh token.
+    pub fn needs_reauth(&self) -> bool {
+        match &self.result {
+            Ok(_) => false,
+            Err(AnthropicAuthError::TokenExpired) => true,
+            Err(e) => e.requires_reauth(),
+        }
+    }
+}
+
+/// [`TokenManager`]s for many named accounts
+///
+/// Meant for orchestrators holding many Claude accounts at once (CI farms, team
+/// proxies). Each account keeps its own manager, and thus its own client and
+/// config, so accounts may come from different [`Profiles`](crate::Profiles).
+/// Persist refreshed tokens with
+/// [`on_tokens_updated`](crate::OAuthConfigBuilder::on_tokens_updated) on each
+/// account's config.
+///
+/// **Note:** Only available when the `token-manager` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{AccountManager, AsyncOAuthClient, OAuthConfig, TokenManager, TokenSet};
+///
+/// # async fn example(accounts: Vec<(String, TokenSet)>) -> Result<(), Box<dyn std::error::Error>> {
+/// let client = AsyncOAuthClient::new(OAuthConfig::default())?;
+/// let mut manager = AccountManager::new().concurrency(8);
+/// for (name, tokens) in accounts {
+///     manager.insert(name, TokenManager::with_tokens(client.clone(), tokens));
+/// }
+///
+/// for report in manager.refresh_all().await {
+///     match report.result {
+///         Ok(tokens) => println!("{}: expires in {:?}", report.account, tokens.expires_in()),
+///         Err(e) => eprintln!("{}: refresh failed: {}", report.account, e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct AccountManager {
+    accounts: BTreeMap<String, TokenManager>,
+    concurrency: usize,
+}
+
+impl Default for AccountManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AccountManager {
+    /// Create a manager without accounts that refreshes up to 4 accounts at once
+    pub fn new() -> Self {
+        Self {
+            accounts: BTreeMap::new(),
+            concurrency: 4,
+        }
+    }
+
+    /// Set the maximum number of accounts refreshed concurrently (at least 1)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Add an account, e.g. keyed by its profile name
+    pub fn with_account(mut self, name: impl Into<String>, manager: TokenManager) -> Self {
+        self.insert(name, manager);
+        self
+    }
+
+    /// Add or replace an account, returning the replaced manager
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        manager: TokenManager,
+    ) -> Option<TokenManager> {
+        self.accounts.insert(name.into(), manager)
+    }
+
+    /// Remove an account
+    pub fn remove(&mut self, name: &str) -> Option<TokenManager> {
+        self.accounts.remove(name)
+    }
+
+    /// Get the manager of an account
+    pub fn get(&self, name: &str) -> Option<&TokenManager> {
+        self.accounts.get(name)
+    }
+
+    /// Get the account names, sorted
+    pub fn names(&self) -> Vec<String> {
+        self.accounts.keys().cloned().collect()
+    }
+
+    /// Refresh every account now and report each outcome, sorted by account name
+    ///
+    /// Runs at most [`concurrency`](Self::concurrency) refreshes at a time. A
+    /// failing account doesn't stop the others; accounts without tokens report
+    /// [`AnthropicAuthError::TokenExpired`](crate::AnthropicAuthError::TokenExpired).
+    ///
+    /// Must be called from within a tokio runtime.
+    pub async fn refresh_all(&self) -> Vec<AccountRefresh> {
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+
+        let handles = self
+            .accounts
+            .iter()
+            .map(|(name, manager)| {
+                let account = name.clone();
+                let manager = manager.clone();
+                let permits = permits.clone();
+                let handle = tokio::spawn(async move {
+                    let _permit = permits.acquire_owned().await;
+                    manager.refresh().await
+                });
+                (account, handle)
+            })
+            .collect::<Vec<_>>();
+
+        let mut reports = Vec::with_capacity(handles.len());
+        for (account, handle) in handles {
+            // A panicking refresh (e.g. in a user-supplied store) only fails its own account
+            let result = handle.await.unwrap_or_else(|e| {
+                Err(AnthropicAuthError::TokenRefresh(format!(
+                    "Refresh task failed: {}",
+                    e
+                )))
+            });
+            reports.push(AccountRefresh { account, result });
+        }
+        reports
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::{AnthropicAuthError, OAuthClient, OAuthMode, Result, TokenSet, UrlDelivery};
+
+type ActionFn = Arc<dyn Fn(&TokenSet) -> Result<()> + Send + Sync>;
+
+/// A step run after a successful login, e.g. as part of an onboarding sequence
+///
+/// Compose steps with [`PostLoginActions`].
+#[derive(Clone)]
+pub enum PostLoginAction {
+    /// Print a summary of the login (organization, scopes, expiry) to stderr
+    PrintSummary,
+    /// Write the access token to an env file as `variable=token`
+    ///
+    /// An existing line for the variable is replaced; other lines are kept. The
+    /// file is created with owner-only permissions on Unix.
+    WriteEnvFile {
+        /// Path of the env file, e.g. `.env`
+        path: PathBuf,
+        /// Name of the variable holding the access token
+        variable: String,
+    },
+    /// POST a JSON summary of the login to a URL, without any tokens
+    ///
+    /// The body is `{"event": "login", "expires_at": .., "scope": .., "organization": ..}`.
+    Webhook {
+        /// URL to notify
+        url: String,
+    },
+    /// Call your own function with the tokens
+    Custom(ActionFn),
+}
+
+impl fmt::Debug for PostLoginAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrintSummary => f.write_str("PrintSummary"),
+            Self::WriteEnvFile { path, variable } => f
+                .debug_struct("WriteEnvFile")
+                .field("path", path)
+                .field("variable", variable)
+                .finish(),
+            Self::Webhook { url } => f.debug_struct("Webhook").field("url", url).finish(),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl fmt::Display for PostLoginAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PrintSummary => f.write_str("print summary"),
+            Self::WriteEnvFile { path, .. } => write!(f, "write env file {}", path.display()),
+            Self::Webhook { url } => write!(f, "webhook {}", url),
+            Self::Custom(_) => f.write_str("custom action"),
+        }
+    }
+}
+
+impl PostLoginAction {
+    fn run(&self, client: &OAuthClient, tokens: &TokenSet) -> Result<()> {
+        match self {
+            Self::PrintSummary => {
+                eprintln!("{}", summary_text(tokens));
+                Ok(())
+            }
+            Self::WriteEnvFile { path, variable } => {
+                write_env_file(path, variable, &tokens.access_token)
+            }
+            Self::Webhook { url } => {
+                let body = json!({
+                    "event": "login",
+                    "expires_at": tokens.expires_at,
+                    "scope": tokens.scope,
+                    "organization": tokens.organization,
+                });
+                client.post_notification(url, &body)
+            }
+            Self::Custom(action) => action(tokens),
+        }
+    }
+}
+
+/// Ordered list of [`PostLoginAction`]s run after a successful login
+///
+/// Every action runs, even if an earlier one failed. Failures are collected into
+/// a single [`AnthropicAuthError::PostLoginFailed`], which still carries the tokens
+/// since the login itself succeeded.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{OAuthClient, OAuthConfig, OAuthMode, PostLoginActions, UrlDelivery};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = OAuthClient::new(OAuthConfig::default())?;
+/// let actions = PostLoginActions::new()
+///     .write_env_file(".env", "ANTHROPIC_OAUTH_TOKEN")
+///     .webhook("https://onboarding.internal/logins")
+///     .print_summary();
+///
+/// let tokens = client.login_and_then(OAuthMode::Max, &UrlDelivery::default(), &actions)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PostLoginActions {
+    actions: Vec<PostLoginAction>,
+}
+
+impl PostLoginActions {
+    /// Create an empty action list
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append an action
+    pub fn then(mut self, action: PostLoginAction) -> Self {
+        self.actions.push(action);
+        self
+    }
+
+    /// Append [`PostLoginAction::PrintSummary`]
+    pub fn print_summary(self) -> Self {
+        self.then(PostLoginAction::PrintSummary)
+    }
+
+    /// Append [`PostLoginAction::WriteEnvFile`]
+    pub fn write_env_file(self, path: impl Into<PathBuf>, variable: impl Into<String>) -> Self {
+        self.then(PostLoginAction::WriteEnvFile {
+            path: path.into(),
+            variable: variable.into(),
+        })
+    }
+
+    /// Append [`PostLoginAction::Webhook`]
+    pub fn webhook(self, url: impl Into<String>) -> Self {
+        self.then(PostLoginAction::Webhook { url: url.into() })
+    }
+
+    /// Append a [`PostLoginAction::Custom`] from a closure
+    pub fn custom<F>(self, action: F) -> Self
+    where
+        F: Fn(&TokenSet) -> Result<()> + Send + Sync + 'static,
+    {
+        self.then(PostLoginAction::Custom(Arc::new(action)))
+    }
+
+    /// Get the actions in the order they run
+    pub fn actions(&self) -> &[PostLoginAction] {
+        &self.actions
+    }
+
+    /// Run every action in order with the tokens of a successful login
+    ///
+    /// Use this after flows other than [`OAuthClient::login_and_then`], such as
+    /// [`OAuthClient::resume_login`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::PostLoginFailed`] listing every failed action
+    pub fn run(&self, client: &OAuthClient, tokens: &TokenSet) -> Result<()> {
+        let failures: Vec<String> = self
+            .actions
+            .iter()
+            .filter_map(|action| {
+                action
+                    .run(client, tokens)
+                    .err()
+                    .map(|error| format!("{}: {}", action, error))
+            })
+            .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(AnthropicAuthError::PostLoginFailed {
+                tokens: Box::new(tokens.clone()),
+                failures,
+            })
+        }
+    }
+}
+
+impl OAuthClient {
+    /// Run the whole authorization flow, then the given actions (blocking)
+    ///
+    /// Same as [`login`](Self::login), followed by [`PostLoginActions::run`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the login error, or [`AnthropicAuthError::PostLoginFailed`] (carrying
+    /// the tokens) if any action failed
+    pub fn login_and_then(
+        &self,
+        mode: OAuthMode,
+        delivery: &UrlDelivery,
+        actions: &PostLoginActions,
+    ) -> Result<TokenSet> {
+        let tokens = self.login(mode, delivery)?;
+        actions.run(self, &tokens)?;
+        Ok(tokens)
+    }
+}
+
+fn summary_text(tokens: &TokenSet) -> String {
+    let mut summary = String::from("Logged in");
+    if let Some(organization) = &tokens.organization {
+        let name = organization.name.as_deref().unwrap_or(&organization.uuid);
+        summary.push_str(&format!(" to {}", name));
+    }
+    summary.push_str(&format!(
+        ". The access token expires in {} minutes.",
+        tokens.expires_in().as_secs() / 60
+    ));
+    if let Some(scope) = &tokens.scope {
+        summary.push_str(&format!("\nScopes: {}", scope));
+    }
+    summary
+}
+
+/// Set `variable=value` in an env file, replacing an existing line for the variable
+fn write_env_file(path: &Path, variable: &str, value: &str) -> Result<()> {
+    let storage_error =
+        |e: std::io::Error| AnthropicAuthError::Storage(format!("{}: {}", path.display(), e));
+
+    let existing = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(storage_error(e)),
+    };
+
+    let assignment = format!("{}={}", variable, value);
+    let mut replaced = false;
+    let mut lines: Vec<String> = existing
+        .lines()
+        .map(|line| {
+            let name = line.trim_start().trim_start_matches("export ");
+            if name
+                .strip_prefix(variable)
+                .is_some_and(|rest| rest.trim_start().starts_with('='))
+            {
+                replaced = true;
+                assignment.clone()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(assignment);
+    }
+
+    let mut options = OpenOptions::new();
+    options.create(true).truncate(true).write(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path).map_err(storage_error)?;
+    file.write_all(format!("{}\n", lines.join("\n")).as_bytes())
+        .map_err(storage_error)
+}
@@ -0,0 +1,256 @@
+use serde::Serialize;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::TokenFingerprint;
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+use crate::{AnthropicAuthError, TokenSet};
+
+/// Authentication event recorded by an [`AuditSink`]
+///
+/// Like [`LifecycleEvent`](crate::LifecycleEvent)s, audit events identify
+/// credentials by fingerprint and never contain tokens or API keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum AuditEvent {
+    /// An authorization code was exchanged for tokens
+    Login {
+        /// Fingerprint of the new tokens
+        fingerprint: TokenFingerprint,
+        /// Unix timestamp (seconds) when the new access token expires
+        expires_at: u64,
+    },
+    /// An access token was refreshed
+    Refresh {
+        /// Fingerprint of the new tokens
+        fingerprint: TokenFingerprint,
+        /// Unix timestamp (seconds) when the new access token expires
+        expires_at: u64,
+    },
+    /// Refreshing an access token failed
+    RefreshFailed {
+        /// The error, as displayed
+        reason: String,
+        /// Whether the user must log in again (see [`AnthropicAuthError::requires_reauth`])
+        reauth_required: bool,
+    },
+    /// An API key was created
+    KeyCreated {
+        /// Fingerprint of the key (see [`ApiKeyRecord::fingerprint_of`](crate::ApiKeyRecord::fingerprint_of))
+        fingerprint: String,
+    },
+    /// A token manager discarded its tokens, e.g. on logout
+    ///
+    /// Anthropic has no revocation endpoint, so this records that the tokens are
+    /// no longer used here; they stay valid on the server until they expire.
+    TokensRevoked {
+        /// Fingerprint of the discarded tokens
+        fingerprint: TokenFingerprint,
+    },
+}
+
+impl AuditEvent {
+    /// Get the event name, as in the `event` field of the JSON form
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Login { .. } => "login",
+            Self::Refresh { .. } => "refresh",
+            Self::RefreshFailed { .. } => "refresh-failed",
+            Self::KeyCreated { .. } => "key-created",
+            Self::TokensRevoked { .. } => "tokens-revoked",
+        }
+    }
+
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+    pub(crate) fn login(tokens: &TokenSet) -> Self {
+        Self::Login {
+            fingerprint: tokens.fingerprint(),
+            expires_at: tokens.expires_at,
+        }
+    }
+
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+    pub(crate) fn refresh(tokens: &TokenSet) -> Self {
+        Self::Refresh {
+            fingerprint: tokens.fingerprint(),
+            expires_at: tokens.expires_at,
+        }
+    }
+
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+    pub(crate) fn refresh_failed(error: &AnthropicAuthError) -> Self {
+        Self::RefreshFailed {
+            reason: error.to_string(),
+            reauth_required: error.requires_reauth(),
+        }
+    }
+}
+
+/// An [`AuditEvent`] with when it happened and which account it concerns
+///
+/// Serializes to a flat JSON object:
+///
+/// ```json
+/// {"timestamp": 1700000000, "event": "refresh", "account": "org-uuid",
+///  "fingerprint": {"access": "3f2a9c1d8e7b6a50", "refresh": "a1b2c3d4e5f60718"},
+///  "expires_at": 1700003600}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AuditRecord {
+    /// Unix timestamp (seconds) of the event, by the configured [`Clock`](crate::Clock)
+    pub timestamp: u64,
+    /// What happened
+    #[serde(flatten)]
+    pub event: AuditEvent,
+    /// UUID of the organization the tokens belong to, if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+}
+
+/// Receives an audit trail of authentication events
+///
+/// Set it with [`OAuthConfigBuilder::audit_sink`](crate::OAuthConfigBuilder::audit_sink);
+/// the clients then record logins, refreshes, refresh failures and created API
+/// keys, and the token managers record when they discard tokens. Recording must
+/// not fail the OAuth operation, so sinks handle their own errors.
+///
+/// Implemented by [`JsonLinesAuditSink`], `TracingAuditSink` (with the
+/// `audit-tracing` feature) and closures taking an [`AuditRecord`].
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{AuditRecord, OAuthConfig};
+///
+/// let config = OAuthConfig::builder()
+///     .audit_sink(|record: &AuditRecord| {
+///         eprintln!("[audit] {} {:?}", record.event.name(), record.account);
+///     })
+///     .build();
+/// ```
+pub trait AuditSink: Send + Sync {
+    /// Record one event
+    fn record(&self, record: &AuditRecord);
+}
+
+impl<F> AuditSink for F
+where
+    F: Fn(&AuditRecord) + Send + Sync,
+{
+    fn record(&self, record: &AuditRecord) {
+        self(record)
+    }
+}
+
+impl fmt::Debug for dyn AuditSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuditSink(..)")
+    }
+}
+
+/// Appends each [`AuditRecord`] as one line of JSON to a file
+///
+/// The file is created with owner-only permissions on Unix and opened in append
+/// mode for every record, so external log rotation is safe. Write errors are
+/// reported on stderr and otherwise ignored.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{JsonLinesAuditSink, OAuthConfig};
+///
+/// let config = OAuthConfig::builder()
+///     .audit_sink(JsonLinesAuditSink::new("/var/log/my-tool/auth-audit.jsonl"))
+///     .build();
+/// ```
+#[derive(Debug)]
+pub struct JsonLinesAuditSink {
+    path: PathBuf,
+    // Keeps concurrent records from interleaving within this process
+    lock: Mutex<()>,
+}
+
+impl JsonLinesAuditSink {
+    /// Append records to the file at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Get the path of the audit file
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    fn append(&self, line: &str) -> std::io::Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        options.open(&self.path)?.write_all(line.as_bytes())
+    }
+}
+
+impl AuditSink for JsonLinesAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        let line = match serde_json::to_string(record) {
+            Ok(json) => format!("{}\n", json),
+            Err(e) => {
+                eprintln!("Failed to serialize audit record: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = self.append(&line) {
+            eprintln!(
+                "Failed to write audit record to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Emits each [`AuditRecord`] as a `tracing` event
+///
+/// Events are logged at `INFO` level (`WARN` for refresh failures) with the
+/// target `anthropic_auth::audit` and the fields `event`, `account`, `timestamp`
+/// and `details` (the JSON form of the event).
+///
+/// **Note:** Only available when the `audit-tracing` feature is enabled.
+#[cfg(feature = "audit-tracing")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TracingAuditSink;
+
+#[cfg(feature = "audit-tracing")]
+impl AuditSink for TracingAuditSink {
+    fn record(&self, record: &AuditRecord) {
+        let details = serde_json::to_string(&record.event).unwrap_or_default();
+        let account = record.account.as_deref().unwrap_or("");
+        if let AuditEvent::RefreshFailed { .. } = record.event {
+            tracing::warn!(
+                target: "anthropic_auth::audit",
+                event = record.event.name(),
+                account,
+                timestamp = record.timestamp,
+                details = %details,
+            );
+        } else {
+            tracing::info!(
+                target: "anthropic_auth::audit",
+                event = record.event.name(),
+                account,
+                timestamp = record.timestamp,
+                details = %details,
+            );
+        }
+    }
+}
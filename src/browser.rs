@@ -1,3 +1,6 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
 use crate::{AnthropicAuthError, Result};
 
 /// Open a URL in the user's default web browser
@@ -32,3 +35,262 @@ pub fn open_browser(url: &str) -> Result<()> {
     webbrowser::open(url)
         .map_err(|e| AnthropicAuthError::BrowserLaunch(format!("Failed to open browser: {}", e)))
 }
+
+/// Something that can open the authorization URL for the user
+///
+/// Implemented by [`OpenOptions`] and by closures taking the URL, so apps can plug
+/// in their own launcher (a kiosk shell, a remote desktop bridge, a test double).
+/// Use it for logins with [`UrlDelivery::OpenWith`](crate::UrlDelivery::OpenWith).
+///
+/// **Note:** Only available when the `browser` feature is enabled.
+pub trait BrowserOpener: Send + Sync {
+    /// Open `url`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL could not be handed to a browser
+    fn open(&self, url: &str) -> Result<()>;
+}
+
+impl<F> BrowserOpener for F
+where
+    F: Fn(&str) -> Result<()> + Send + Sync,
+{
+    fn open(&self, url: &str) -> Result<()> {
+        self(url)
+    }
+}
+
+/// How to open a URL: which browser, in a private window, or with a custom command
+///
+/// The default opens the system's default browser like [`open_browser`], except
+/// that under WSL it first tries `wslview` so the URL opens in the Windows
+/// browser. Set [`command`](Self::command) for full control, or
+/// [`browser`](Self::browser) to pick an installed browser by executable name.
+///
+/// **Note:** Only available when the `browser` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{BrowserOpener, OpenOptions};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// // A fresh Firefox private window, so the login doesn't reuse another account's session
+/// OpenOptions::new()
+///     .browser("firefox")
+///     .private(true)
+///     .open("https://claude.ai/oauth/authorize?...")?;
+///
+/// // Any launcher; `{url}` is replaced with the URL
+/// OpenOptions::new()
+///     .command("open -na 'Google Chrome' --args --incognito {url}")
+///     .open("https://claude.ai/oauth/authorize?...")?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OpenOptions {
+    browser: Option<String>,
+    private: bool,
+    command: Option<String>,
+    wsl: bool,
+}
+
+impl Default for OpenOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OpenOptions {
+    /// Open URLs in the default browser, using `wslview` under WSL
+    pub fn new() -> Self {
+        Self {
+            browser: None,
+            private: false,
+            command: None,
+            wsl: true,
+        }
+    }
+
+    /// Open URLs with this browser executable (name on `PATH` or full path)
+    ///
+    /// For example `firefox`, `google-chrome`, `chromium`, `brave-browser` or
+    /// `msedge`. The URL is passed as the last argument.
+    pub fn browser(mut self, browser: impl Into<String>) -> Self {
+        self.browser = Some(browser.into());
+        self
+    }
+
+    /// Open URLs in a private (incognito) window of the chosen [`browser`](Self::browser)
+    ///
+    /// Firefox, Chrome, Chromium, Brave, Vivaldi, Edge and Opera are recognized by
+    /// their executable name. For other browsers, use a [`command`](Self::command)
+    /// with the right flag instead.
+    pub fn private(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+
+    /// Open URLs by running this command template
+    ///
+    /// The template is split into arguments at whitespace (quote arguments
+    /// containing spaces with `'` or `"`), and every `{url}` is replaced with the
+    /// URL. Without a `{url}`, the URL is appended as the last argument. The
+    /// command is not run through a shell. Takes precedence over
+    /// [`browser`](Self::browser).
+    pub fn command(mut self, template: impl Into<String>) -> Self {
+        self.command = Some(template.into());
+        self
+    }
+
+    /// Set whether to try `wslview` first when running under WSL (default: true)
+    ///
+    /// Only applies when neither a command nor a browser is set.
+    pub fn wsl(mut self, wsl: bool) -> Self {
+        self.wsl = wsl;
+        self
+    }
+
+    /// Work out the program and arguments to run, or `None` for the default browser
+    fn launch_command(&self, url: &str) -> Result<Option<Vec<String>>> {
+        if let Some(template) = &self.command {
+            let mut args = split_command(template)?;
+            if args.is_empty() {
+                return Err(AnthropicAuthError::BrowserLaunch(
+                    "the browser command is empty".to_string(),
+                ));
+            }
+            if args.iter().any(|arg| arg.contains("{url}")) {
+                for arg in &mut args {
+                    *arg = arg.replace("{url}", url);
+                }
+            } else {
+                args.push(url.to_string());
+            }
+            return Ok(Some(args));
+        }
+
+        if let Some(browser) = &self.browser {
+            let mut args = vec![browser.clone()];
+            if self.private {
+                args.push(private_flag(browser)?.to_string());
+            }
+            args.push(url.to_string());
+            return Ok(Some(args));
+        }
+
+        if self.private {
+            return Err(AnthropicAuthError::BrowserLaunch(
+                "a private window needs a specific browser or command".to_string(),
+            ));
+        }
+        if self.wsl && is_wsl() {
+            return Ok(Some(vec!["wslview".to_string(), url.to_string()]));
+        }
+        Ok(None)
+    }
+}
+
+impl BrowserOpener for OpenOptions {
+    fn open(&self, url: &str) -> Result<()> {
+        let Some(args) = self.launch_command(url)? else {
+            return open_browser(url);
+        };
+
+        let spawned = Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        match spawned {
+            Ok(_) => Ok(()),
+            // wslview is missing on minimal WSL installs; the default opener may still work
+            Err(_) if self.command.is_none() && self.browser.is_none() => open_browser(url),
+            Err(e) => Err(AnthropicAuthError::BrowserLaunch(format!(
+                "could not run {}: {}",
+                args[0], e
+            ))),
+        }
+    }
+}
+
+/// Command-line flag opening a private window in `browser`
+fn private_flag(browser: &str) -> Result<&'static str> {
+    let name = Path::new(browser)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(browser)
+        .to_ascii_lowercase();
+
+    if ["firefox", "librewolf", "waterfox"]
+        .iter()
+        .any(|known| name.contains(known))
+    {
+        Ok("--private-window")
+    } else if name.contains("edge") {
+        Ok("--inprivate")
+    } else if ["chrome", "chromium", "brave", "vivaldi"]
+        .iter()
+        .any(|known| name.contains(known))
+    {
+        Ok("--incognito")
+    } else if name.contains("opera") {
+        Ok("--private")
+    } else {
+        Err(AnthropicAuthError::BrowserLaunch(format!(
+            "don't know how to open a private window in {}; use a command instead",
+            browser
+        )))
+    }
+}
+
+/// Whether this is Linux running under the Windows Subsystem for Linux
+fn is_wsl() -> bool {
+    cfg!(target_os = "linux")
+        && (std::env::var_os("WSL_DISTRO_NAME").is_some()
+            || std::fs::read_to_string("/proc/sys/kernel/osrelease")
+                .is_ok_and(|release| release.to_ascii_lowercase().contains("microsoft")))
+}
+
+/// Split a command template into arguments, honoring `'` and `"` quotes
+fn split_command(template: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote = None;
+
+    for c in template.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '\'' || c == '"' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(AnthropicAuthError::BrowserLaunch(format!(
+            "unterminated quote in browser command: {}",
+            template
+        )));
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::sync::{OnceLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use crate::{OAuthConfig, TokenSet};
+
+/// Process-global token storage, keyed by profile name
+static CACHE: OnceLock<RwLock<HashMap<String, TokenSet>>> = OnceLock::new();
+
+fn read() -> RwLockReadGuard<'static, HashMap<String, TokenSet>> {
+    CACHE
+        .get_or_init(Default::default)
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write() -> RwLockWriteGuard<'static, HashMap<String, TokenSet>> {
+    CACHE
+        .get_or_init(Default::default)
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Store tokens in the process-global cache under the given profile
+///
+/// Replaces any tokens previously cached for the profile. Typically called by
+/// the part of the application that performs login or refresh.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{cache_token, current_token, OAuthClient, OAuthConfig};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client = OAuthClient::new(OAuthConfig::default())?;
+/// # let flow = client.start_flow(anthropic_auth::OAuthMode::Max)?;
+/// # let tokens = client.exchange(&anthropic_auth::ExchangeRequest::new("code", &flow))?;
+/// cache_token("default", tokens);
+///
+/// // Anywhere else in the process
+/// if let Some(tokens) = current_token("default") {
+///     println!("Using token expiring in {:?}", tokens.expires_in());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn cache_token(profile: &str, tokens: TokenSet) {
+    write().insert(profile.to_string(), tokens);
+}
+
+/// Get the cached tokens for a profile if they are still valid
+///
+/// Returns `None` if nothing is cached for the profile or the cached tokens are
+/// expired (see [`TokenSet::is_expired`]). Use [`current_token_with`] to honor a
+/// configured clock and expiry buffer, and [`cached_token`] to get expired tokens
+/// too, e.g. to refresh them.
+pub fn current_token(profile: &str) -> Option<TokenSet> {
+    current_token_with(profile, &OAuthConfig::default())
+}
+
+/// Get the cached tokens for a profile if they are still valid by
+/// [`OAuthConfig::is_expired`]
+pub fn current_token_with(profile: &str, config: &OAuthConfig) -> Option<TokenSet> {
+    read()
+        .get(profile)
+        .filter(|tokens| !config.is_expired(tokens))
+        .cloned()
+}
+
+/// Get the cached tokens for a profile regardless of expiry
+pub fn cached_token(profile: &str) -> Option<TokenSet> {
+    read().get(profile).cloned()
+}
+
+/// Remove and return the cached tokens for a profile
+pub fn evict_token(profile: &str) -> Option<TokenSet> {
+    write().remove(profile)
+}
+
+/// List the profiles that currently have cached tokens
+pub fn cached_profiles() -> Vec<String> {
+    read().keys().cloned().collect()
+}
+
+/// Remove all tokens from the process-global cache
+pub fn clear_token_cache() {
+    write().clear();
+}
@@ -0,0 +1,450 @@
+use serde::Deserialize;
+use std::net::{Ipv4Addr, SocketAddr};
+use url::Url;
+
+use crate::{AnthropicAuthError, CallbackData, Result};
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct CallbackQuery {
+    pub(crate) code: Option<String>,
+    pub(crate) state: Option<String>,
+    pub(crate) error: Option<String>,
+}
+
+/// Default path the callback server listens on
+pub const DEFAULT_CALLBACK_PATH: &str = "/callback";
+
+/// Where the callback server listens
+///
+/// Defaults to `127.0.0.1:1455` and `/callback`. Bind to `[::1]` for IPv6-only
+/// loopback, or to `0.0.0.0` / `[::]` when the redirect arrives through a port
+/// forward (devcontainers, WSL2, remote development).
+///
+/// Behind a reverse proxy (nginx, caddy), set the externally visible callback URL
+/// with [`public_url`](Self::public_url). The callback is then also served on the
+/// public URL's path, for proxies that forward the path unchanged.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::CallbackServerConfig;
+/// use std::net::SocketAddr;
+///
+/// let config = CallbackServerConfig::new("[::1]:8080".parse::<SocketAddr>().unwrap())
+///     .path("/oauth/anthropic");
+/// assert_eq!(config.path, "/oauth/anthropic");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackServerConfig {
+    /// Socket address to bind
+    pub addr: SocketAddr,
+    /// Path the callback is served on
+    pub path: String,
+    /// Callback URL as seen by the browser, when it differs from the bind address
+    /// (default: none)
+    pub public_url: Option<Url>,
+    /// Whether to check `Forwarded` / `X-Forwarded-*` headers against
+    /// [`public_url`](Self::public_url) (default: `false`)
+    ///
+    /// Only enable this when every request passes through a proxy that sets these
+    /// headers, since clients can forge them otherwise. Callbacks forwarded for
+    /// another host, scheme or path prefix are then answered with HTTP 421.
+    pub trust_forwarded_headers: bool,
+    /// Whether to reject requests that can't be the browser's redirect (default: `true`)
+    ///
+    /// Requests made by scripts or subresources of other web pages (a
+    /// `Sec-Fetch-Mode` other than `navigate`, or a `Sec-Fetch-Dest` other than
+    /// `document`) are answered with HTTP 403. On a loopback bind address, so are
+    /// requests whose `Host` is neither a loopback name nor the public URL's host,
+    /// which blocks DNS rebinding.
+    pub check_request_headers: bool,
+}
+
+impl Default for CallbackServerConfig {
+    fn default() -> Self {
+        Self::with_port(1455)
+    }
+}
+
+impl CallbackServerConfig {
+    /// Listen on the given socket address with the default path
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            path: DEFAULT_CALLBACK_PATH.to_string(),
+            public_url: None,
+            trust_forwarded_headers: false,
+            check_request_headers: true,
+        }
+    }
+
+    /// Listen on `127.0.0.1` with the given port and the default path
+    pub fn with_port(port: u16) -> Self {
+        Self::new(SocketAddr::from((Ipv4Addr::LOCALHOST, port)))
+    }
+
+    /// Check that the bind address is currently free
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::PortUnavailable`] if the address cannot be bound
+    pub fn check_available(&self) -> Result<()> {
+        crate::check_port_available(self.addr)
+    }
+
+    /// Set the callback path (a leading `/` is added if missing)
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.path = normalize_path(path.into());
+        self
+    }
+
+    /// Serve the callback on a random, unguessable path below the current one
+    ///
+    /// Appends a 128-bit nonce, e.g. `/callback/Xq3...`, so other local programs
+    /// and web pages can't reach the callback route without knowing the redirect
+    /// URI. Create a new config for every flow and send its
+    /// [`redirect_uri`](Self::redirect_uri) in the authorization URL (e.g. with
+    /// [`AuthorizationUrlBuilder::redirect_uri`](crate::AuthorizationUrlBuilder::redirect_uri)).
+    /// The server must accept the resulting redirect URI for the client ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::CallbackServerConfig;
+    ///
+    /// let config = CallbackServerConfig::with_port(1455).secret_path();
+    /// assert!(config.path.starts_with("/callback/"));
+    /// assert!(config.redirect_uri().starts_with("http://localhost:1455/callback/"));
+    /// ```
+    pub fn secret_path(mut self) -> Self {
+        let mut nonce = [0u8; 16];
+        rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut nonce);
+        self.path = format!(
+            "{}/{}",
+            self.path.trim_end_matches('/'),
+            base64::Engine::encode(&base64::engine::general_purpose::URL_SAFE_NO_PAD, nonce)
+        );
+        self
+    }
+
+    /// Set the callback URL the browser is redirected to, e.g. when the redirect
+    /// terminates at a reverse proxy
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::CallbackServerConfig;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // nginx forwards https://auth.example.com/oauth/callback to 127.0.0.1:1455
+    /// let config = CallbackServerConfig::with_port(1455)
+    ///     .public_url("https://auth.example.com/oauth/callback".parse()?)
+    ///     .trust_forwarded_headers(true);
+    /// assert_eq!(config.redirect_uri(), "https://auth.example.com/oauth/callback");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn public_url(mut self, url: Url) -> Self {
+        self.public_url = Some(url);
+        self
+    }
+
+    /// Set whether to check `Forwarded` / `X-Forwarded-*` headers against the public URL
+    pub fn trust_forwarded_headers(mut self, trust: bool) -> Self {
+        self.trust_forwarded_headers = trust;
+        self
+    }
+
+    /// Set whether to reject requests that can't be the browser's redirect
+    ///
+    /// See [`check_request_headers`](Self::check_request_headers).
+    pub fn check_request_headers(mut self, check: bool) -> Self {
+        self.check_request_headers = check;
+        self
+    }
+
+    /// Get the redirect URI matching this server
+    ///
+    /// The public URL if set, otherwise `http://localhost:<port><path>`.
+    pub fn redirect_uri(&self) -> String {
+        match &self.public_url {
+            Some(url) => url.to_string(),
+            None => format!("http://localhost:{}{}", self.addr.port(), self.path),
+        }
+    }
+
+    /// Get the paths the callback is served on: the bind path and the public URL's path
+    pub(crate) fn callback_paths(&self) -> Vec<String> {
+        let mut paths = vec![self.path.clone()];
+        if let Some(url) = &self.public_url {
+            if url.path() != self.path {
+                paths.push(url.path().to_string());
+            }
+        }
+        paths
+    }
+
+    /// Check that a callback request was forwarded for the public URL
+    ///
+    /// Always `true` unless forwarded headers are trusted and a public URL is set.
+    /// Requests without forwarding headers (straight to the bind address) pass.
+    pub(crate) fn accepts_forwarded<'a>(
+        &self,
+        path: &str,
+        header: impl Fn(&str) -> Option<&'a str>,
+    ) -> bool {
+        let Some(public) = self
+            .public_url
+            .as_ref()
+            .filter(|_| self.trust_forwarded_headers)
+        else {
+            return true;
+        };
+
+        let (proto, host) = forwarded_origin(&header);
+        if proto.is_some_and(|proto| !proto.eq_ignore_ascii_case(public.scheme())) {
+            return false;
+        }
+        if let Some(host) = host {
+            let host = match header("x-forwarded-port") {
+                Some(port) if !host.contains(':') => format!("{}:{}", host, first_value(port)),
+                _ => host.to_string(),
+            };
+            let Ok(forwarded) = Url::parse(&format!("{}://{}", public.scheme(), host)) else {
+                return false;
+            };
+            if forwarded.host_str() != public.host_str()
+                || forwarded.port_or_known_default() != public.port_or_known_default()
+            {
+                return false;
+            }
+        }
+        match header("x-forwarded-prefix") {
+            Some(prefix) => {
+                let prefix = first_value(prefix).trim_end_matches('/');
+                format!("{}{}", prefix, path) == public.path()
+            }
+            None => true,
+        }
+    }
+}
+
+impl CallbackServerConfig {
+    /// Check that a callback request looks like the browser following the redirect
+    ///
+    /// Always `true` if [`check_request_headers`](Self::check_request_headers) is
+    /// off. Headers a client doesn't send are not required.
+    pub(crate) fn accepts_request<'a>(&self, header: impl Fn(&str) -> Option<&'a str>) -> bool {
+        if !self.check_request_headers {
+            return true;
+        }
+
+        let fetch_is = |name: &str, expected: &str| {
+            header(name).map_or(true, |value| value.trim().eq_ignore_ascii_case(expected))
+        };
+        if !fetch_is("sec-fetch-mode", "navigate") || !fetch_is("sec-fetch-dest", "document") {
+            return false;
+        }
+
+        match header("host") {
+            Some(host) if self.addr.ip().is_loopback() => self.accepts_host(host),
+            _ => true,
+        }
+    }
+
+    /// Check a `Host` header against the loopback names and the public URL's host
+    fn accepts_host(&self, host: &str) -> bool {
+        let Ok(url) = Url::parse(&format!("http://{}", host.trim())) else {
+            return false;
+        };
+        let public_host = self.public_url.as_ref().and_then(Url::host_str);
+        match url.host() {
+            Some(url::Host::Domain(domain)) => {
+                domain.eq_ignore_ascii_case("localhost")
+                    || public_host.is_some_and(|public| domain.eq_ignore_ascii_case(public))
+            }
+            Some(url::Host::Ipv4(ip)) => ip.is_loopback() || public_host == url.host_str(),
+            Some(url::Host::Ipv6(ip)) => ip.is_loopback() || public_host == url.host_str(),
+            None => false,
+        }
+    }
+}
+
+/// Get the scheme and host the client used, from `Forwarded` or `X-Forwarded-*`
+fn forwarded_origin<'a>(
+    header: &impl Fn(&str) -> Option<&'a str>,
+) -> (Option<&'a str>, Option<&'a str>) {
+    if let Some(forwarded) = header("forwarded") {
+        // Only the first (client-facing) hop counts
+        let hop = forwarded.split(',').next().unwrap_or_default();
+        let mut origin = (None, None);
+        for pair in hop.split(';') {
+            let Some((name, value)) = pair.split_once('=') else {
+                continue;
+            };
+            let value = value.trim().trim_matches('"');
+            match name.trim().to_ascii_lowercase().as_str() {
+                "proto" => origin.0 = Some(value),
+                "host" => origin.1 = Some(value),
+                _ => {}
+            }
+        }
+        return origin;
+    }
+
+    (
+        header("x-forwarded-proto").map(first_value),
+        header("x-forwarded-host").map(first_value),
+    )
+}
+
+/// Get the first of comma-separated header values (the client-facing hop)
+fn first_value(value: &str) -> &str {
+    value.split(',').next().unwrap_or_default().trim()
+}
+
+pub(crate) fn normalize_path(path: String) -> String {
+    if path.starts_with('/') {
+        path
+    } else {
+        format!("/{}", path)
+    }
+}
+
+/// Page shown when a proxy forwarded the callback for another URL than the public one
+pub(crate) const MISDIRECTED_PAGE: &str = r#"
+            <html>
+                <head><title>Misdirected Request</title></head>
+                <body>
+                    <h1>Misdirected Request</h1>
+                    <p>This callback was forwarded for a different address than the configured callback URL.</p>
+                </body>
+            </html>
+            "#;
+
+/// Page shown when a request doesn't look like the browser following the redirect
+pub(crate) const FORBIDDEN_PAGE: &str = r#"
+            <html>
+                <head><title>Forbidden</title></head>
+                <body>
+                    <h1>Forbidden</h1>
+                    <p>This request was not accepted as an authorization callback.</p>
+                </body>
+            </html>
+            "#;
+
+/// Page shown when a callback arrives for a flow that already completed or is unknown
+const EXPIRED_PAGE: &str = r#"
+            <html>
+                <head><title>Login Link Expired</title></head>
+                <body>
+                    <h1>This Login Link Has Expired</h1>
+                    <p>This authorization was already completed or belongs to an earlier login attempt.</p>
+                    <p>Please close this window and restart the login from the application.</p>
+                </body>
+            </html>
+            "#;
+
+/// Page shown when a callback carries no state, so it can't belong to any flow
+const MISSING_STATE_PAGE: &str = r#"
+            <html>
+                <head><title>Authorization Failed</title></head>
+                <body>
+                    <h1>Authorization Failed</h1>
+                    <p>Security validation failed. Please try again.</p>
+                    <p>You can close this window.</p>
+                </body>
+            </html>
+            "#;
+
+/// Get the page for a callback that matched no waiting flow
+///
+/// The flows keep waiting: only a callback carrying a flow's own state may
+/// resolve it, so a stray or forged request can't end a login in progress.
+pub(crate) fn unmatched_page(params: &CallbackQuery) -> &'static str {
+    if params.state.is_some() {
+        EXPIRED_PAGE
+    } else {
+        MISSING_STATE_PAGE
+    }
+}
+
+/// Resolve a callback whose state matched a waiting flow
+///
+/// Returns the result for the flow and the page shown in the browser.
+pub(crate) fn callback_outcome(params: CallbackQuery) -> (Result<CallbackData>, String) {
+    // Check for OAuth errors
+    if let Some(error) = params.error {
+        let page = format!(
+            r#"
+            <html>
+                <head><title>Authorization Failed</title></head>
+                <body>
+                    <h1>Authorization Failed</h1>
+                    <p>Error: {}</p>
+                    <p>You can close this window.</p>
+                </body>
+            </html>
+            "#,
+            html_escape(&error)
+        );
+        return (
+            Err(AnthropicAuthError::OAuth(format!("OAuth error: {}", error))),
+            page,
+        );
+    }
+
+    // Extract code
+    match params.code {
+        Some(code) => (
+            Ok(CallbackData {
+                code,
+                state: params.state.unwrap_or_default(),
+            }),
+            r#"
+                <html>
+                    <head><title>Authorization Successful</title></head>
+                    <body>
+                        <h1>Authorization Successful!</h1>
+                        <p>You have successfully authorized the application.</p>
+                        <p>You can close this window and return to the terminal.</p>
+                    </body>
+                </html>
+                "#
+            .to_string(),
+        ),
+        None => (
+            Err(AnthropicAuthError::InvalidAuthorizationCode),
+            r#"
+                <html>
+                    <head><title>Authorization Failed</title></head>
+                    <body>
+                        <h1>Authorization Failed</h1>
+                        <p>No authorization code received.</p>
+                        <p>You can close this window.</p>
+                    </body>
+                </html>
+                "#
+            .to_string(),
+        ),
+    }
+}
+
+/// Escape text for inclusion in an HTML page
+///
+/// Query parameters are attacker-controlled, so anything echoed into a callback
+/// page goes through this first.
+fn html_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#x27;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,81 @@
+use serde::Serialize;
+
+/// Features compiled into this build of the crate
+///
+/// Returned by [`capabilities`]. Fields are added as the crate grows, so match on
+/// the ones you need instead of constructing the struct.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// The sync [`OAuthClient`](crate::OAuthClient) (`blocking` or `blocking-ureq`)
+    pub blocking: bool,
+    /// The async `AsyncOAuthClient` (`async`)
+    #[serde(rename = "async")]
+    pub async_client: bool,
+    /// TLS for the `reqwest` clients via rustls (`rustls-tls`)
+    pub rustls_tls: bool,
+    /// TLS for the `reqwest` clients via the platform's TLS library (`native-tls`)
+    pub native_tls: bool,
+    /// Opening the authorization URL in the default browser (`browser`)
+    pub browser: bool,
+    /// The tokio/axum callback server and router (`callback-server`)
+    pub callback_server: bool,
+    /// The `std::net` callback server (`callback-server-blocking`)
+    pub callback_server_blocking: bool,
+    /// Serving the callback over HTTPS (`callback-server-tls`)
+    pub callback_server_tls: bool,
+    /// Storing tokens in files (`file-store`)
+    pub file_store: bool,
+    /// Storing tokens in the OS keyring (`keyring-store`)
+    pub keyring: bool,
+    /// Storing tokens in browser `localStorage`/`sessionStorage` (`web-storage`,
+    /// on `wasm32` only)
+    pub web_storage: bool,
+    /// The token managers (`token-manager` or `token-manager-blocking`)
+    pub token_manager: bool,
+    /// The interactive terminal login (`terminal`)
+    pub terminal: bool,
+    /// Built for WebAssembly (`wasm32`)
+    pub wasm: bool,
+}
+
+/// Get the features compiled into this build of the crate
+///
+/// Lets host applications adapt their UX to the actual build, e.g. hide an
+/// "Open browser" button or skip the local callback server.
+///
+/// # Example
+///
+/// ```
+/// let capabilities = anthropic_auth::capabilities();
+/// if !capabilities.browser {
+///     println!("Copy the authorization URL into your browser");
+/// }
+/// ```
+pub const fn capabilities() -> Capabilities {
+    Capabilities {
+        blocking: cfg!(any(feature = "blocking", feature = "blocking-ureq")),
+        async_client: cfg!(feature = "async"),
+        rustls_tls: cfg!(all(feature = "reqwest", feature = "rustls-tls")),
+        native_tls: cfg!(all(feature = "reqwest", feature = "native-tls")),
+        browser: cfg!(feature = "browser"),
+        callback_server: cfg!(feature = "callback-server"),
+        callback_server_blocking: cfg!(feature = "callback-server-blocking"),
+        callback_server_tls: cfg!(feature = "callback-server-tls"),
+        file_store: cfg!(feature = "file-store"),
+        keyring: cfg!(feature = "keyring-store"),
+        web_storage: cfg!(all(feature = "web-storage", target_arch = "wasm32")),
+        token_manager: cfg!(any(
+            feature = "token-manager",
+            all(
+                feature = "token-manager-blocking",
+                any(feature = "blocking", feature = "blocking-ureq")
+            )
+        )),
+        terminal: cfg!(all(
+            feature = "terminal",
+            any(feature = "blocking", feature = "blocking-ureq")
+        )),
+        wasm: cfg!(target_arch = "wasm32"),
+    }
+}
@@ -0,0 +1,114 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{AnthropicAuthError, Result, TokenSet};
+
+/// Claims decoded from a JWT access token
+///
+/// The payload is decoded **without** signature verification. Use it to inspect
+/// what a token says about itself (subject, organization, scopes, expiry), never
+/// as proof that the token is genuine.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenClaims {
+    /// Subject (`sub`), usually the account the token was issued to
+    pub subject: Option<String>,
+    /// Organization ID (`org_id` or `organization_id`)
+    pub org_id: Option<String>,
+    /// Granted scopes (`scope` as a space-separated string, or `scp` as a list)
+    pub scopes: Vec<String>,
+    /// Unix timestamp (seconds) when the token expires (`exp`)
+    pub expires_at: Option<u64>,
+    /// Unix timestamp (seconds) when the token was issued (`iat`)
+    pub issued_at: Option<u64>,
+    /// The full decoded payload, for claims not exposed as fields
+    pub raw: Value,
+}
+
+impl TokenClaims {
+    /// Check whether the token grants the given scope
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+impl TokenSet {
+    /// Decode the claims of the access token
+    ///
+    /// This lets you derive expiry from the token itself (`exp`) instead of the
+    /// locally computed `expires_at`. The signature is **not** verified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the access token is not a JWT or its payload cannot be decoded
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use anthropic_auth::TokenSet;
+    /// # fn example(tokens: TokenSet) -> Result<(), Box<dyn std::error::Error>> {
+    /// let claims = tokens.claims()?;
+    /// println!("Subject: {:?}, expires at: {:?}", claims.subject, claims.expires_at);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn claims(&self) -> Result<TokenClaims> {
+        inspect_token(&self.access_token)
+    }
+}
+
+/// Decode the payload of a JWT without verifying its signature
+///
+/// # Arguments
+///
+/// * `token` - A JWT in compact serialization (`header.payload.signature`)
+///
+/// # Errors
+///
+/// Returns an error if the token is not a JWT or its payload is not a base64url-encoded
+/// JSON object
+pub fn inspect_token(token: &str) -> Result<TokenClaims> {
+    let mut parts = token.split('.');
+    let payload = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(_), Some(payload), Some(_), None) => payload,
+        _ => {
+            return Err(AnthropicAuthError::InvalidTokenFormat(
+                "expected a JWT with three dot-separated segments".to_string(),
+            ))
+        }
+    };
+
+    // Tolerate padded payloads even though JWTs are specified as unpadded base64url
+    let bytes =
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(payload.trim_end_matches('='))?;
+    let raw: Value = serde_json::from_slice(&bytes)?;
+
+    if !raw.is_object() {
+        return Err(AnthropicAuthError::InvalidTokenFormat(
+            "JWT payload is not a JSON object".to_string(),
+        ));
+    }
+
+    let string_claim = |name: &str| raw.get(name).and_then(Value::as_str).map(str::to_string);
+
+    let scopes = match (raw.get("scope"), raw.get("scp")) {
+        (Some(Value::String(scope)), _) | (None, Some(Value::String(scope))) => {
+            scope.split_whitespace().map(str::to_string).collect()
+        }
+        (_, Some(Value::Array(scopes))) => scopes
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    Ok(TokenClaims {
+        subject: string_claim("sub"),
+        org_id: string_claim("org_id").or_else(|| string_claim("organization_id")),
+        scopes,
+        expires_at: raw.get("exp").and_then(Value::as_u64),
+        issued_at: raw.get("iat").and_then(Value::as_u64),
+        raw,
+    })
+}
@@ -0,0 +1,180 @@
+use url::Url;
+
+use super::shared::{authorize_url, generate_pkce_and_state};
+use crate::{
+    AnthropicAuthError, CsrfState, OAuthConfig, OAuthFlow, OAuthMode, PkceVerifier, Result,
+};
+
+/// Query parameters set by the builder itself, which [`AuthorizationUrlBuilder::param`] can't override
+const RESERVED_PARAMS: &[&str] = &[
+    "code",
+    "client_id",
+    "response_type",
+    "redirect_uri",
+    "scope",
+    "code_challenge",
+    "code_challenge_method",
+    "state",
+];
+
+/// Builds the authorization URL of a flow piece by piece
+///
+/// `start_flow` is shorthand for `authorization_url(mode).build()`. Use the
+/// builder directly to pre-fill the login form, force a prompt, add query
+/// parameters, or bring the state and PKCE verifier of a larger SSO journey.
+/// Whatever is customized, [`build`](Self::build) returns an [`OAuthFlow`] whose
+/// verifier, state and redirect URI match the URL, so it can be exchanged and
+/// persisted like any other flow.
+///
+/// Get one from `OAuthClient::authorization_url` or
+/// `AsyncOAuthClient::authorization_url`, or from [`new`](Self::new) with a
+/// config.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{AuthorizationUrlBuilder, OAuthConfig, OAuthMode};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = OAuthConfig::default();
+/// let flow = AuthorizationUrlBuilder::new(&config, OAuthMode::Max)
+///     .login_hint("user@example.com")
+///     .prompt("login")
+///     .param("utm_source", "my-app")
+///     .build()?;
+/// assert_eq!(flow.query_param("login_hint").as_deref(), Some("user@example.com"));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AuthorizationUrlBuilder<'a> {
+    config: &'a OAuthConfig,
+    mode: OAuthMode,
+    scope: Option<String>,
+    redirect_uri: Option<String>,
+    state: Option<CsrfState>,
+    verifier: Option<PkceVerifier>,
+    params: Vec<(String, String)>,
+}
+
+impl<'a> AuthorizationUrlBuilder<'a> {
+    /// Start building the authorization URL of a flow in `mode`
+    pub fn new(config: &'a OAuthConfig, mode: OAuthMode) -> Self {
+        Self {
+            config,
+            mode,
+            scope: None,
+            redirect_uri: None,
+            state: None,
+            verifier: None,
+            params: Vec::new(),
+        }
+    }
+
+    /// Request these scopes instead of the configured ones
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = Some(scope.into());
+        self
+    }
+
+    /// Redirect to this URI instead of the configured one
+    ///
+    /// The URI must be registered for the client ID.
+    pub fn redirect_uri(mut self, redirect_uri: impl Into<String>) -> Self {
+        self.redirect_uri = Some(redirect_uri.into());
+        self
+    }
+
+    /// Use this CSRF state token instead of a random one
+    ///
+    /// For journeys that already carry a state, e.g. one issued by an outer SSO
+    /// flow. It should still be unguessable.
+    pub fn state(mut self, state: CsrfState) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    /// Use this PKCE verifier instead of a random one
+    ///
+    /// The `code_challenge` of the URL is derived from it (S256), so the flow keeps
+    /// the verifier needed for the exchange.
+    pub fn verifier(mut self, verifier: PkceVerifier) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
+    /// Pre-fill the login form with an email address (`login_hint`)
+    pub fn login_hint(self, email: impl Into<String>) -> Self {
+        self.param("login_hint", email)
+    }
+
+    /// Ask the server how to prompt the user (`prompt`), e.g. `login` or `consent`
+    pub fn prompt(self, prompt: impl Into<String>) -> Self {
+        self.param("prompt", prompt)
+    }
+
+    /// Append another query parameter
+    ///
+    /// Parameters are appended after the standard ones, in the order they were added.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((name.into(), value.into()));
+        self
+    }
+
+    /// Build the authorization URL and the flow to complete it
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] if an extra parameter would
+    /// override one the builder sets (such as `state` or `redirect_uri`; use the
+    /// dedicated methods instead), or a URL error if the authorization endpoint
+    /// doesn't parse
+    pub fn build(self) -> Result<OAuthFlow> {
+        if let Some((name, _)) = self
+            .params
+            .iter()
+            .find(|(name, _)| RESERVED_PARAMS.contains(&name.as_str()))
+        {
+            return Err(AnthropicAuthError::InvalidConfig(format!(
+                "authorization URL parameter '{}' is set by the builder",
+                name
+            )));
+        }
+
+        // Generate PKCE challenge and verifier, plus a separate random state for CSRF
+        // protection (more secure than using verifier)
+        let (generated_verifier, generated_state) = generate_pkce_and_state(self.config);
+        let verifier = self.verifier.unwrap_or(generated_verifier);
+        let state = self.state.unwrap_or(generated_state);
+        let redirect_uri = self
+            .redirect_uri
+            .unwrap_or_else(|| self.config.redirect_uri());
+        let scope = self.scope.as_deref().unwrap_or(&self.config.scope);
+
+        let mut url = Url::parse(&authorize_url(self.config, &self.mode))?;
+        let mut query = url.query_pairs_mut();
+        if self.config.protocol.sends_code_param() {
+            query.append_pair("code", "true");
+        }
+        query
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("response_type", "code")
+            .append_pair("redirect_uri", &redirect_uri)
+            .append_pair("scope", scope)
+            .append_pair("code_challenge", &verifier.challenge())
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", &state);
+        for (name, value) in &self.params {
+            query.append_pair(name, value);
+        }
+        drop(query);
+
+        Ok(OAuthFlow {
+            authorization_url: url.to_string(),
+            verifier,
+            state,
+            mode: self.mode,
+            redirect_uri,
+        })
+    }
+}
@@ -1,10 +1,14 @@
-use oauth2::PkceCodeChallenge;
-use rand::Rng;
-use url::Url;
-
+use super::authorize::AuthorizationUrlBuilder;
+use super::dry_run;
+use super::http_log::{HttpExchange, HttpLog};
+use super::interrupt::InterruptHandle;
 use super::shared::*;
+use super::transport::{DefaultTransport, Transport};
 use crate::types::{ApiKeyResponse, TokenResponse};
-use crate::{OAuthConfig, OAuthFlow, OAuthMode, Result, TokenSet};
+use crate::{
+    ApiKeyOptions, AuditEvent, CallbackData, CsrfState, ExchangeOptions, ExchangeRequest,
+    LifecycleEvent, OAuthConfig, OAuthFlow, OAuthMode, PkceVerifier, Result, TokenSet,
+};
 
 /// Synchronous Anthropic OAuth client for authentication
 ///
@@ -14,7 +18,7 @@ use crate::{OAuthConfig, OAuthFlow, OAuthMode, Result, TokenSet};
 /// # Example
 ///
 /// ```no_run
-/// use anthropic_auth::{OAuthClient, OAuthConfig, OAuthMode};
+/// use anthropic_auth::{ExchangeRequest, OAuthClient, OAuthConfig, OAuthMode};
 ///
 /// fn main() -> Result<(), Box<dyn std::error::Error>> {
 ///     let client = OAuthClient::new(OAuthConfig::default())?;
@@ -23,13 +27,17 @@ use crate::{OAuthConfig, OAuthFlow, OAuthMode, Result, TokenSet};
 ///     println!("Visit: {}", flow.authorization_url);
 ///     // User authorizes and you get the code and state...
 ///     
-///     let tokens = client.exchange_code("code_value", "state_value", &flow.verifier)?;
+///     let tokens = client.exchange(&ExchangeRequest::new("code_value", &flow))?;
 ///     println!("Got tokens!");
 ///     Ok(())
 /// }
 /// ```
 pub struct OAuthClient {
     config: OAuthConfig,
+    http: DefaultTransport,
+    exchanged: ExchangedCodes,
+    http_log: HttpLog,
+    interrupt: InterruptHandle,
 }
 
 impl OAuthClient {
@@ -41,9 +49,59 @@ impl OAuthClient {
     ///
     /// # Errors
     ///
-    /// Returns an error if the configuration is invalid
+    /// Returns an error if the configuration is invalid (e.g. a malformed default header)
+    /// or the HTTP client cannot be created
     pub fn new(config: OAuthConfig) -> Result<Self> {
-        Ok(Self { config })
+        let http = DefaultTransport::from_config(&config)?;
+
+        Ok(Self {
+            http_log: HttpLog::new(&config),
+            config,
+            http,
+            exchanged: ExchangedCodes::default(),
+            interrupt: InterruptHandle::new(),
+        })
+    }
+
+    /// Get the configuration the client was created with
+    pub fn config(&self) -> &OAuthConfig {
+        &self.config
+    }
+
+    /// Get the most recent HTTP exchanges of this client, oldest first
+    ///
+    /// Empty unless [`OAuthConfig::debug_http`] is enabled. Secrets are scrubbed
+    /// from the transcripts (see [`HttpExchange`]), so they can be attached to bug
+    /// reports about failing exchanges or refreshes.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{OAuthClient, ExchangeRequest, OAuthConfig, OAuthMode};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = OAuthConfig::builder().debug_http(true).build();
+    /// let client = OAuthClient::new(config)?;
+    /// let flow = client.start_flow(OAuthMode::Max)?;
+    /// if let Err(e) = client.exchange(&ExchangeRequest::new("code#state", &flow)) {
+    ///     eprintln!("Exchange failed: {}", e);
+    ///     for exchange in client.last_exchanges() {
+    ///         eprintln!("{}", serde_json::to_string(&exchange)?);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn last_exchanges(&self) -> Vec<HttpExchange> {
+        self.http_log.exchanges()
+    }
+
+    /// Get a handle that aborts this client's in-flight network calls from another thread
+    ///
+    /// Once a handle was taken, each request runs on a worker thread so the
+    /// calling thread can stop waiting for it. See [`InterruptHandle`].
+    pub fn interrupt_handle(&self) -> InterruptHandle {
+        self.interrupt.arm()
     }
 
     /// Start the OAuth authorization flow
@@ -71,53 +129,48 @@ impl OAuthClient {
     /// # }
     /// ```
     pub fn start_flow(&self, mode: OAuthMode) -> Result<OAuthFlow> {
-        // Generate PKCE challenge and verifier
-        let (pkce_challenge, pkce_verifier) = PkceCodeChallenge::new_random_sha256();
-        let verifier = pkce_verifier.secret().to_string();
-
-        // Generate a separate random state for CSRF protection (more secure than using verifier)
-        let state = generate_random_state();
-
-        // Determine base domain based on mode
-        let base_domain = match mode {
-            OAuthMode::Max => "claude.ai",
-            OAuthMode::Console => "console.anthropic.com",
-        };
+        self.authorization_url(mode).build()
+    }
 
-        // Build authorization URL
-        let auth_url = format!("https://{}/oauth/authorize", base_domain);
-        let mut url = Url::parse(&auth_url)?;
-
-        url.query_pairs_mut()
-            .append_pair("code", "true")
-            .append_pair("client_id", &self.config.client_id)
-            .append_pair("response_type", "code")
-            .append_pair("redirect_uri", REDIRECT_URI)
-            .append_pair("scope", SCOPE)
-            .append_pair("code_challenge", pkce_challenge.as_str())
-            .append_pair("code_challenge_method", "S256")
-            .append_pair("state", &state);
-
-        Ok(OAuthFlow {
-            authorization_url: url.to_string(),
-            verifier,
-            state,
-            mode,
-        })
+    /// Build the authorization URL of a flow piece by piece
+    ///
+    /// Like [`start_flow`](Self::start_flow), but lets the caller add a login
+    /// hint, a prompt or extra query parameters, and bring their own state or
+    /// PKCE verifier. See [`AuthorizationUrlBuilder`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use anthropic_auth::{OAuthClient, OAuthConfig, OAuthMode};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = OAuthClient::new(OAuthConfig::default())?;
+    /// let flow = client
+    ///     .authorization_url(OAuthMode::Max)
+    ///     .login_hint("user@example.com")
+    ///     .build()?;
+    /// println!("Visit: {}", flow.authorization_url);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn authorization_url(&self, mode: OAuthMode) -> AuthorizationUrlBuilder<'_> {
+        AuthorizationUrlBuilder::new(&self.config, mode)
     }
 
-    /// Exchange an authorization code for access and refresh tokens (blocking)
+    /// Exchange an authorization response for access and refresh tokens (blocking)
     ///
     /// After the user authorizes the application, Anthropic returns a combined string
     /// in the format `code#state`. This method parses that format, validates the state
-    /// for CSRF protection, and exchanges the code for tokens.
+    /// for CSRF protection, and exchanges the code for tokens. A request built from
+    /// the flow with [`ExchangeRequest::new`] also uses the token endpoint of the
+    /// flow's mode.
+    ///
+    /// Exchanging a code again with the same verifier and redirect URI, within a few
+    /// minutes, returns the cached tokens instead of posting the used code again,
+    /// unless [`ExchangeOptions::allow_reuse`] is set.
     ///
     /// # Arguments
     ///
-    /// * `code_with_state` - The combined authorization response (format: "code#state")
-    ///   or just the code if already separated
-    /// * `expected_state` - The state token from the original flow (for CSRF validation)
-    /// * `verifier` - The PKCE verifier from the original flow
+    /// * `request` - The authorization response with the state and verifier of its flow
     ///
     /// # Returns
     ///
@@ -126,7 +179,7 @@ impl OAuthClient {
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The code, state, or verifier is invalid or empty
+    /// - The code is invalid or empty
     /// - The state doesn't match the expected state (CSRF protection)
     /// - The token exchange fails (invalid code, network error, etc.)
     /// - The response contains invalid token data
@@ -134,58 +187,169 @@ impl OAuthClient {
     /// # Example
     ///
     /// ```no_run
-    /// # use anthropic_auth::{OAuthClient, OAuthConfig, OAuthMode};
+    /// # use anthropic_auth::{ExchangeRequest, OAuthClient, OAuthConfig, OAuthMode};
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// # let client = OAuthClient::new(OAuthConfig::default())?;
     /// # let flow = client.start_flow(OAuthMode::Max)?;
     /// // User pastes the combined response from Anthropic
     /// let response = "code123#state456";
-    /// let tokens = client.exchange_code(response, &flow.state, &flow.verifier)?;
+    /// let tokens = client.exchange(&ExchangeRequest::new(response, &flow))?;
     /// println!("Access token expires in: {:?}", tokens.expires_in());
     /// # Ok(())
     /// # }
     /// ```
-    pub fn exchange_code(
-        &self,
-        code_with_state: &str,
-        expected_state: &str,
-        verifier: &str,
-    ) -> Result<TokenSet> {
+    pub fn exchange(&self, request: &ExchangeRequest<'_>) -> Result<TokenSet> {
         // Parse code and state from the input
-        let (code, state) = parse_code_and_state(code_with_state, expected_state)?;
-
-        // Validate inputs
+        check_state_entropy(request.expected_state, self.config.min_state_entropy)?;
+        let (code, state) = parse_code_and_state(
+            request.response,
+            request.expected_state,
+            self.config.protocol,
+        )?;
+
+        // State and verifier are validated by their types
         validate_code(&code)?;
-        validate_state(&state)?;
-        validate_verifier(verifier)?;
-
-        let client = reqwest::blocking::Client::new();
-        let request_body = build_token_request(&code, &state, verifier, &self.config.client_id);
-
-        let response = client.post(TOKEN_URL).json(&request_body).send()?;
 
-        if !response.status().is_success() {
-            let status = response.status().as_u16();
-            let body = response.text().unwrap_or_default();
-            return Err(create_http_error(status, &body));
+        let redirect_uri = request
+            .redirect_uri
+            .map_or_else(|| self.config.redirect_uri(), str::to_string);
+
+        // A used code only gets an opaque 400 from the server; reuse the first result
+        if !request.options.allow_reuse {
+            if let Some(tokens) = self.exchanged.get(
+                &code,
+                request.verifier,
+                &redirect_uri,
+                self.config.clock().unix_secs(),
+            ) {
+                return Ok(tokens);
+            }
+        }
+        let request_body = build_token_request(
+            &code,
+            &state,
+            request.verifier,
+            &self.config.client_id,
+            &redirect_uri,
+            self.config.protocol,
+        );
+
+        let response = self.post(token_url(&self.config, request.mode), None, &request_body)?;
+
+        if !response.is_success() {
+            return Err(create_http_error(
+                response.status,
+                &response.body,
+                response.retry_after,
+            ));
         }
 
         let token_response: TokenResponse = response.json()?;
-        let tokens = TokenSet::from(token_response);
+        let tokens = token_response.into_token_set(self.config.clock());
 
         // Validate the token structure
-        tokens.validate().map_err(|e| {
-            crate::AnthropicAuthError::OAuth(format!("Invalid token response: {}", e))
-        })?;
-
+        tokens
+            .validate_with(self.config.clock(), self.config.clock_skew)
+            .map_err(|e| {
+                crate::AnthropicAuthError::OAuth(format!("Invalid token response: {}", e))
+            })?;
+
+        self.exchanged.insert(
+            &code,
+            request.verifier,
+            &redirect_uri,
+            &tokens,
+            self.config.clock().unix_secs(),
+        );
+        tokens_updated(&self.config, &tokens);
+        self.notify(&LifecycleEvent::login(&tokens));
+        self.config
+            .audit(AuditEvent::login(&tokens), tokens.organization.as_ref());
         Ok(tokens)
     }
 
+    /// Exchange an authorization code for access and refresh tokens (blocking)
+    ///
+    /// Same as [`exchange`](Self::exchange) with [`ExchangeRequest::from_parts`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`exchange`](Self::exchange)
+    #[deprecated(note = "use `exchange` with an `ExchangeRequest`")]
+    pub fn exchange_code(
+        &self,
+        code_with_state: &str,
+        expected_state: &CsrfState,
+        verifier: &PkceVerifier,
+    ) -> Result<TokenSet> {
+        self.exchange(&ExchangeRequest::from_parts(
+            code_with_state,
+            expected_state,
+            verifier,
+        ))
+    }
+
+    /// Exchange an authorization code for tokens with explicit options (blocking)
+    ///
+    /// Same as [`exchange`](Self::exchange) with [`ExchangeRequest::from_parts`]
+    /// and [`ExchangeRequest::options`].
+    ///
+    /// # Errors
+    ///
+    /// Same as [`exchange`](Self::exchange)
+    #[deprecated(note = "use `exchange` with an `ExchangeRequest`")]
+    pub fn exchange_code_with(
+        &self,
+        code_with_state: &str,
+        expected_state: &CsrfState,
+        verifier: &PkceVerifier,
+        options: &ExchangeOptions,
+    ) -> Result<TokenSet> {
+        self.exchange(
+            &ExchangeRequest::from_parts(code_with_state, expected_state, verifier)
+                .options(options.clone()),
+        )
+    }
+
+    /// Exchange the authorization code of a callback for tokens (blocking)
+    ///
+    /// Takes the [`CallbackData`] received by a callback server together with the
+    /// [`OAuthFlow`] it belongs to, so code, state, and verifier can't be mixed up
+    /// across concurrent flows. The callback state is checked against `flow.state`
+    /// again before exchanging.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the callback state doesn't match the flow (CSRF
+    /// protection), or if the code exchange fails (see [`exchange`](Self::exchange))
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use anthropic_auth::{CallbackData, OAuthClient, OAuthConfig, OAuthMode};
+    /// # fn example(receive_callback: impl Fn() -> CallbackData) -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = OAuthClient::new(OAuthConfig::default())?;
+    /// let flow = client.start_flow(OAuthMode::Max)?;
+    ///
+    /// let callback = receive_callback();
+    /// let tokens = client.exchange_callback(&callback, &flow)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn exchange_callback(&self, callback: &CallbackData, flow: &OAuthFlow) -> Result<TokenSet> {
+        check_callback_state(callback, flow)?;
+        self.exchange(&ExchangeRequest::new(&callback.code, flow))
+    }
+
     /// Refresh an expired access token (blocking)
     ///
     /// When an access token expires, use the refresh token to obtain a new
     /// access token without requiring the user to re-authorize.
     ///
+    /// If you still have the full previous `TokenSet`, prefer
+    /// [`refresh_token_with_fallback`](Self::refresh_token_with_fallback), which keeps the
+    /// old refresh token when the server doesn't return a rotated one.
+    ///
     /// # Arguments
     ///
     /// * `refresh_token` - The refresh token from a previous token exchange
@@ -201,41 +365,52 @@ impl OAuthClient {
     /// # Example
     ///
     /// ```no_run
-    /// # use anthropic_auth::{OAuthClient, OAuthConfig};
+    /// # use anthropic_auth::{ExchangeRequest, OAuthClient, OAuthConfig};
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// # let client = OAuthClient::new(OAuthConfig::default())?;
-    /// # let old_tokens = client.exchange_code("code", "state", "verifier")?;
+    /// # let flow = client.start_flow(anthropic_auth::OAuthMode::Max)?;
+    /// # let old_tokens = client.exchange(&ExchangeRequest::new("code", &flow))?;
     /// let new_tokens = client.refresh_token(&old_tokens.refresh_token)?;
     /// # Ok(())
     /// # }
     /// ```
     pub fn refresh_token(&self, refresh_token: &str) -> Result<TokenSet> {
-        if refresh_token.is_empty() {
-            return Err(crate::AnthropicAuthError::OAuth(
-                "Refresh token is empty".to_string(),
-            ));
-        }
-
-        let client = reqwest::blocking::Client::new();
-        let request_body = build_refresh_request(refresh_token, &self.config.client_id);
-
-        let response = client.post(TOKEN_URL).json(&request_body).send()?;
-
-        if !response.status().is_success() {
-            let status = response.status().as_u16();
-            let body = response.text().unwrap_or_default();
-            return Err(create_http_error(status, &body));
-        }
-
-        let token_response: TokenResponse = response.json()?;
-        let tokens = TokenSet::from(token_response);
-
-        // Validate the token structure
-        tokens.validate().map_err(|e| {
-            crate::AnthropicAuthError::OAuth(format!("Invalid token response: {}", e))
-        })?;
+        self.refresh(refresh_token, None)
+    }
 
-        Ok(tokens)
+    /// Refresh tokens, carrying forward fields the server omits (blocking)
+    ///
+    /// Anthropic may rotate refresh tokens: when a new refresh token is returned it
+    /// replaces the old one, and when the response omits it the previous refresh token
+    /// is kept. Scope, token type, and organization are carried forward the same way.
+    ///
+    /// # Arguments
+    ///
+    /// * `old_tokens` - The token set being refreshed
+    ///
+    /// # Returns
+    ///
+    /// A new `TokenSet` with a fresh access token and a usable refresh token
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the refresh fails (invalid refresh token, network error, etc.)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use anthropic_auth::{ExchangeRequest, OAuthClient, OAuthConfig};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = OAuthClient::new(OAuthConfig::default())?;
+    /// # let flow = client.start_flow(anthropic_auth::OAuthMode::Max)?;
+    /// # let old_tokens = client.exchange(&ExchangeRequest::new("code", &flow))?;
+    /// let new_tokens = client.refresh_token_with_fallback(&old_tokens)?;
+    /// assert!(!new_tokens.refresh_token.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn refresh_token_with_fallback(&self, old_tokens: &TokenSet) -> Result<TokenSet> {
+        self.refresh(&old_tokens.refresh_token, Some(old_tokens))
     }
 
     /// Create an API key using a Console OAuth access token (blocking)
@@ -258,32 +433,66 @@ impl OAuthClient {
     /// # Example
     ///
     /// ```no_run
-    /// # use anthropic_auth::{OAuthClient, OAuthConfig, OAuthMode};
+    /// # use anthropic_auth::{ExchangeRequest, OAuthClient, OAuthConfig, OAuthMode};
     /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
     /// # let client = OAuthClient::new(OAuthConfig::default())?;
     /// # let flow = client.start_flow(OAuthMode::Console)?;
-    /// # let tokens = client.exchange_code("code", "state", &flow.verifier)?;
+    /// # let tokens = client.exchange(&ExchangeRequest::new("code", &flow))?;
     /// let api_key = client.create_api_key(&tokens.access_token)?;
     /// println!("API Key: {}", api_key);
     /// # Ok(())
     /// # }
     /// ```
     pub fn create_api_key(&self, access_token: &str) -> Result<String> {
+        self.create_api_key_with(access_token, &ApiKeyOptions::default())
+    }
+
+    /// Create an API key with a name and/or workspace (blocking)
+    ///
+    /// Same as [`create_api_key`](Self::create_api_key), but lets you choose the
+    /// key's display name and the workspace it is created in.
+    ///
+    /// # Arguments
+    ///
+    /// * `access_token` - The access token from Console mode OAuth
+    /// * `options` - Key name and workspace
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if API key creation fails
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use anthropic_auth::{ApiKeyOptions, OAuthClient, OAuthConfig};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let client = OAuthClient::new(OAuthConfig::default())?;
+    /// # let access_token = "access_token";
+    /// let options = ApiKeyOptions {
+    ///     name: Some("ci-runner".to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let api_key = client.create_api_key_with(access_token, &options)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_api_key_with(
+        &self,
+        access_token: &str,
+        options: &ApiKeyOptions,
+    ) -> Result<String> {
         validate_access_token(access_token)?;
 
-        let client = reqwest::blocking::Client::new();
-        let request_body = build_api_key_request();
+        let request_body = build_api_key_request(options);
 
-        let response = client
-            .post(API_KEY_URL)
-            .header("authorization", format!("Bearer {}", access_token))
-            .json(&request_body)
-            .send()?;
+        let response = self.post(api_key_url(&self.config), Some(access_token), &request_body)?;
 
-        if !response.status().is_success() {
-            let status = response.status().as_u16();
-            let body = response.text().unwrap_or_default();
-            return Err(create_http_error(status, &body));
+        if !response.is_success() {
+            return Err(create_http_error(
+                response.status,
+                &response.body,
+                response.retry_after,
+            ));
         }
 
         let key_response: ApiKeyResponse = response.json()?;
@@ -295,55 +504,145 @@ impl OAuthClient {
             ));
         }
 
+        let fingerprint = crate::ApiKeyRecord::fingerprint_of(&key_response.raw_key);
+        self.config.audit(
+            AuditEvent::KeyCreated {
+                fingerprint: fingerprint.clone(),
+            },
+            None,
+        );
+        self.notify(&LifecycleEvent::KeyCreated { fingerprint });
         Ok(key_response.raw_key)
     }
-}
 
-/// Generate a cryptographically random state token for CSRF protection
-fn generate_random_state() -> String {
-    let mut rng = rand::thread_rng();
-    let random_bytes: Vec<u8> = (0..32).map(|_| rng.gen()).collect();
-    base64::Engine::encode(
-        &base64::engine::general_purpose::URL_SAFE_NO_PAD,
-        &random_bytes,
-    )
-}
+    /// POST a JSON notification (no bearer token), failing on a non-2xx response
+    pub(crate) fn post_notification(&self, url: &str, body: &serde_json::Value) -> Result<()> {
+        let response = self.post(url, None, body)?;
+        if !response.is_success() {
+            return Err(create_http_error(
+                response.status,
+                &response.body,
+                response.retry_after,
+            ));
+        }
+        Ok(())
+    }
 
-/// Parse code and state from the authorization response
-///
-/// Anthropic returns the authorization response in the format "code#state".
-/// This function parses that format and validates the state against the expected value.
-///
-/// # Arguments
-///
-/// * `code_with_state` - The authorization response (may contain "#state" or just the code)
-/// * `expected_state` - The state token from the original flow for validation
-///
-/// # Returns
-///
-/// A tuple of (code, state) where state has been validated against expected_state
-///
-/// # Errors
-///
-/// Returns an error if the state doesn't match the expected state (CSRF protection)
-fn parse_code_and_state(code_with_state: &str, expected_state: &str) -> Result<(String, String)> {
-    if let Some(hash_pos) = code_with_state.find('#') {
-        // Parse "code#state" format
-        let code = &code_with_state[..hash_pos];
-        let returned_state = &code_with_state[hash_pos + 1..];
-
-        // Validate state for CSRF protection
-        if returned_state != expected_state {
-            return Err(crate::AnthropicAuthError::OAuth(format!(
-                "State mismatch - possible CSRF attack. Expected: {}, Got: {}",
-                expected_state, returned_state
-            )));
+    /// Report an event to the configured webhook, if any
+    ///
+    /// Best effort: the event is sent from a detached thread, so a slow webhook
+    /// never holds up the OAuth operation, and failures are ignored.
+    pub(crate) fn notify(&self, event: &LifecycleEvent) {
+        if self.config.dry_run {
+            return;
+        }
+        if let Some(notifier) = &self.config.notifier {
+            let (body, headers) = notifier.request(event);
+            let url = notifier.url().to_string();
+            std::thread::spawn(move || {
+                if let Ok(http) = DefaultTransport::for_webhook() {
+                    let _ = http.post_raw(&url, &headers, body);
+                }
+            });
+        }
+    }
+
+    /// POST a JSON body, sleeping and retrying while rate limited if configured to
+    ///
+    /// Both the request and the sleep can be aborted through the interrupt handle.
+    fn post(
+        &self,
+        url: &str,
+        bearer: Option<&str>,
+        body: &serde_json::Value,
+    ) -> Result<HttpResponse> {
+        let mut attempt = 0;
+        loop {
+            let response = self.send(url, bearer, body)?;
+            match rate_limit_wait(&self.config, response.status, response.retry_after, attempt) {
+                Some(wait) => self.interrupt.sleep(wait)?,
+                None => return Ok(response),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// POST a JSON body once and read the whole response, recording it if `debug_http` is set
+    fn send(
+        &self,
+        url: &str,
+        bearer: Option<&str>,
+        body: &serde_json::Value,
+    ) -> Result<HttpResponse> {
+        let (http, owned_url, bearer, owned_body) = (
+            self.http.clone(),
+            url.to_string(),
+            bearer.map(str::to_string),
+            body.clone(),
+        );
+
+        let started = self.http_log.start(&self.config);
+        let result = if self.config.dry_run {
+            Ok(dry_run::respond(&self.config, url, body))
+        } else {
+            self.interrupt
+                .run(move || http.post_json(&owned_url, bearer.as_deref(), &owned_body))
+        };
+        self.http_log
+            .record(&self.config, started, url, body, &result);
+        result
+    }
+
+    /// Perform a refresh, optionally carrying forward omitted fields from `previous`
+    fn refresh(&self, refresh_token: &str, previous: Option<&TokenSet>) -> Result<TokenSet> {
+        let result = self.try_refresh(refresh_token, previous);
+        match &result {
+            Ok(tokens) => self
+                .config
+                .audit(AuditEvent::refresh(tokens), tokens.organization.as_ref()),
+            Err(error) => self.config.audit(
+                AuditEvent::refresh_failed(error),
+                previous.and_then(|tokens| tokens.organization.as_ref()),
+            ),
+        }
+        result
+    }
+
+    fn try_refresh(&self, refresh_token: &str, previous: Option<&TokenSet>) -> Result<TokenSet> {
+        if refresh_token.is_empty() {
+            return Err(crate::AnthropicAuthError::OAuth(
+                "Refresh token is empty".to_string(),
+            ));
+        }
+
+        let request_body = build_refresh_request(refresh_token, &self.config.client_id);
+
+        let response = self.post(token_url(&self.config, None), None, &request_body)?;
+
+        if !response.is_success() {
+            return Err(create_http_error(
+                response.status,
+                &response.body,
+                response.retry_after,
+            ));
+        }
+
+        let token_response: TokenResponse = response.json()?;
+        let mut tokens = token_response.into_token_set(self.config.clock());
+        if let Some(previous) = previous {
+            tokens.carry_forward(previous);
         }
 
-        Ok((code.to_string(), returned_state.to_string()))
-    } else {
-        // No "#" found, assume just the code was provided
-        // Use the expected_state directly
-        Ok((code_with_state.to_string(), expected_state.to_string()))
+        // Validate the token structure
+        tokens
+            .validate_with(self.config.clock(), self.config.clock_skew)
+            .map_err(|e| {
+                crate::AnthropicAuthError::OAuth(format!("Invalid token response: {}", e))
+            })?;
+
+        self.exchanged.forget_refresh_token(refresh_token);
+        tokens_updated(&self.config, &tokens);
+        self.notify(&LifecycleEvent::refresh(&tokens));
+        Ok(tokens)
     }
 }
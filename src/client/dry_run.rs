@@ -0,0 +1,42 @@
+use serde_json::{json, Value};
+
+use super::shared::{api_key_url, HttpResponse};
+use crate::OAuthConfig;
+
+/// Lifetime of synthetic access tokens (8 hours, like real Max tokens)
+const DRY_RUN_EXPIRES_IN: u64 = 8 * 60 * 60;
+
+/// Answer a POST the way Anthropic would, without sending it
+///
+/// Used instead of the network when [`OAuthConfig::dry_run`] is set. Requests to
+/// the API key endpoint get a synthetic API key, everything else a synthetic
+/// token response; the client then parses them like real responses.
+pub(super) fn respond(config: &OAuthConfig, url: &str, body: &Value) -> HttpResponse {
+    let response = if url == api_key_url(config) {
+        json!({ "raw_key": synthetic("sk-ant-api03-dry-run") })
+    } else {
+        let scope = match body.get("grant_type").and_then(Value::as_str) {
+            Some("refresh_token") => body.get("scope").and_then(Value::as_str),
+            _ => None,
+        }
+        .unwrap_or(&config.scope);
+        json!({
+            "access_token": synthetic("sk-ant-oat01-dry-run"),
+            "refresh_token": synthetic("sk-ant-ort01-dry-run"),
+            "expires_in": DRY_RUN_EXPIRES_IN,
+            "token_type": "Bearer",
+            "scope": scope,
+        })
+    };
+
+    HttpResponse {
+        status: 200,
+        body: response.to_string(),
+        retry_after: None,
+    }
+}
+
+/// Create a unique token with a recognizable prefix
+fn synthetic(prefix: &str) -> String {
+    format!("{}-{:016x}", prefix, rand::random::<u64>())
+}
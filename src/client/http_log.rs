@@ -0,0 +1,195 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime};
+
+use super::shared::HttpResponse;
+use crate::{OAuthConfig, Result};
+
+/// How many HTTP exchanges a client keeps when `debug_http` is enabled
+const HTTP_LOG_CAPACITY: usize = 32;
+
+/// Longest body kept in a transcript; longer ones are cut off
+const MAX_BODY_LEN: usize = 4096;
+
+/// JSON fields whose values never appear in a transcript
+const SECRET_FIELDS: &[&str] = &[
+    "access_token",
+    "refresh_token",
+    "id_token",
+    "code",
+    "code_verifier",
+    "state",
+    "raw_key",
+    "api_key",
+    "client_secret",
+];
+
+/// Prefix shared by Anthropic access tokens, refresh tokens and API keys
+const SECRET_PREFIX: &str = "sk-ant-";
+
+const REDACTED: &str = "<redacted>";
+
+/// Sanitized transcript of one HTTP request made by a client
+///
+/// Recorded when [`OAuthConfig::debug_http`](crate::OAuthConfig::debug_http) is
+/// enabled and returned by `last_exchanges()`. Authorization codes, verifiers,
+/// state tokens, access and refresh tokens and API keys are replaced with
+/// `<redacted>`, and headers are not recorded, so transcripts can be attached to
+/// bug reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HttpExchange {
+    /// Unix timestamp (seconds) when the request was sent
+    pub timestamp: u64,
+    /// HTTP method, e.g. "POST"
+    pub method: String,
+    /// Request URL
+    pub url: String,
+    /// Scrubbed request body
+    pub request_body: String,
+    /// Response status, or `None` if no response was received
+    pub status: Option<u16>,
+    /// Scrubbed response body, or `None` if no response was received
+    pub response_body: Option<String>,
+    /// Why the request failed without a response (network error, interrupt)
+    pub error: Option<String>,
+    /// How long the request took, in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Ring buffer of the most recent HTTP exchanges; disabled unless `debug_http` is set
+///
+/// Clones share the same buffer.
+#[derive(Debug, Clone, Default)]
+pub(super) struct HttpLog(Option<Arc<Mutex<VecDeque<HttpExchange>>>>);
+
+impl HttpLog {
+    pub(super) fn new(config: &OAuthConfig) -> Self {
+        Self(config.debug_http.then(Default::default))
+    }
+
+    /// Get when a request starts, if exchanges are recorded
+    pub(super) fn start(&self, config: &OAuthConfig) -> Option<SystemTime> {
+        self.0.as_ref().map(|_| config.clock().now())
+    }
+
+    /// Record a POST sent at `started`, forgetting the oldest exchange when full
+    pub(super) fn record(
+        &self,
+        config: &OAuthConfig,
+        started: Option<SystemTime>,
+        url: &str,
+        request_body: &Value,
+        result: &Result<HttpResponse>,
+    ) {
+        let (Some(log), Some(started)) = (&self.0, started) else {
+            return;
+        };
+
+        let (status, response_body, error) = match result {
+            Ok(response) => (
+                Some(response.status),
+                Some(scrub_body(&response.body)),
+                None,
+            ),
+            Err(e) => (None, None, Some(scrub_text(&e.to_string()))),
+        };
+        let exchange = HttpExchange {
+            timestamp: started
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs(),
+            method: "POST".to_string(),
+            url: url.to_string(),
+            request_body: scrub_json(request_body).to_string(),
+            status,
+            response_body,
+            error,
+            duration_ms: config
+                .clock()
+                .now()
+                .duration_since(started)
+                .unwrap_or(Duration::ZERO)
+                .as_millis() as u64,
+        };
+
+        let mut exchanges = lock(log);
+        if exchanges.len() == HTTP_LOG_CAPACITY {
+            exchanges.pop_front();
+        }
+        exchanges.push_back(exchange);
+    }
+
+    /// Get the recorded exchanges, oldest first
+    pub(super) fn exchanges(&self) -> Vec<HttpExchange> {
+        self.0
+            .as_ref()
+            .map(|log| lock(log).iter().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+fn lock(log: &Mutex<VecDeque<HttpExchange>>) -> MutexGuard<'_, VecDeque<HttpExchange>> {
+    log.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Scrub a response body, as JSON if it parses and as text otherwise
+fn scrub_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(value) => scrub_json(&value).to_string(),
+        Err(_) => {
+            let mut text = scrub_text(body);
+            if text.len() > MAX_BODY_LEN {
+                let mut end = MAX_BODY_LEN;
+                while !text.is_char_boundary(end) {
+                    end -= 1;
+                }
+                text.truncate(end);
+                text.push_str("...");
+            }
+            text
+        }
+    }
+}
+
+/// Copy a JSON value with the values of secret fields, and tokens inside strings, redacted
+fn scrub_json(value: &Value) -> Value {
+    match value {
+        Value::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .map(|(name, value)| {
+                    let value = if SECRET_FIELDS.contains(&name.as_str()) {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        scrub_json(value)
+                    };
+                    (name.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(scrub_json).collect()),
+        Value::String(text) => Value::String(scrub_text(text)),
+        other => other.clone(),
+    }
+}
+
+/// Redact anything that looks like an Anthropic token or API key
+fn scrub_text(text: &str) -> String {
+    let mut scrubbed = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(SECRET_PREFIX) {
+        scrubbed.push_str(&rest[..start]);
+        scrubbed.push_str(SECRET_PREFIX);
+        scrubbed.push_str(REDACTED);
+
+        let secret = &rest[start + SECRET_PREFIX.len()..];
+        let end = secret
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .unwrap_or(secret.len());
+        rest = &secret[end..];
+    }
+    scrubbed.push_str(rest);
+    scrubbed
+}
@@ -0,0 +1,138 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::{AnthropicAuthError, Result};
+
+/// Aborts the in-flight network calls of an [`OAuthClient`](crate::OAuthClient) from another thread
+///
+/// Calls interrupted this way fail with [`AnthropicAuthError::Interrupted`] right away,
+/// including calls sleeping through a rate limit. Calls started after
+/// [`interrupt`](Self::interrupt) returns are not affected.
+///
+/// The abandoned request finishes in the background and its connection is dropped
+/// rather than reused; set a request timeout to bound how long that takes.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{AnthropicAuthError, OAuthClient, OAuthConfig};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = OAuthClient::new(OAuthConfig::default())?;
+/// let interrupt = client.interrupt_handle();
+/// // e.g. from a Ctrl-C handler
+/// std::thread::spawn(move || interrupt.interrupt());
+///
+/// match client.refresh_token("refresh_token") {
+///     Err(AnthropicAuthError::Interrupted) => println!("Cancelled"),
+///     result => println!("{:?}", result.map(|tokens| tokens.expires_in())),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct InterruptHandle {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    /// Set once a handle was given out; until then calls run on the caller's thread
+    armed: AtomicBool,
+    next_id: AtomicU64,
+    /// Wakers of the calls in flight, each failing its call with `Interrupted`
+    waiters: Mutex<Vec<(u64, Waker)>>,
+}
+
+type Waker = Box<dyn Fn() + Send>;
+
+impl std::fmt::Debug for InterruptHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InterruptHandle")
+            .field("in_flight", &self.waiters().len())
+            .finish()
+    }
+}
+
+impl InterruptHandle {
+    pub(super) fn new() -> Self {
+        Self {
+            inner: Arc::new(Inner::default()),
+        }
+    }
+
+    /// Abort every call currently in flight on the client
+    pub fn interrupt(&self) {
+        for (_, wake) in self.waiters().drain(..) {
+            wake();
+        }
+    }
+
+    /// Give out a handle, from then on running calls so they can be interrupted
+    pub(super) fn arm(&self) -> Self {
+        self.inner.armed.store(true, Ordering::SeqCst);
+        self.clone()
+    }
+
+    /// Run `call` on a worker thread, returning early if interrupted
+    pub(super) fn run<T: Send + 'static>(
+        &self,
+        call: impl FnOnce() -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        if !self.inner.armed.load(Ordering::SeqCst) {
+            return call();
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let id = self.register(&tx);
+        std::thread::spawn(move || {
+            let _ = tx.send(Some(call()));
+        });
+
+        let outcome = rx
+            .recv()
+            .ok()
+            .flatten()
+            .unwrap_or(Err(AnthropicAuthError::Interrupted));
+        self.unregister(id);
+        outcome
+    }
+
+    /// Sleep for `duration`, returning early if interrupted
+    pub(super) fn sleep(&self, duration: Duration) -> Result<()> {
+        let (tx, rx) = mpsc::channel::<Option<()>>();
+        let id = self.register(&tx);
+        let outcome = match rx.recv_timeout(duration) {
+            Ok(_) => Err(AnthropicAuthError::Interrupted),
+            Err(_) => Ok(()),
+        };
+        self.unregister(id);
+        outcome
+    }
+
+    /// Register a call waiting on `tx`, which receives `None` when interrupted
+    fn register<T: Send + 'static>(&self, tx: &Sender<Option<T>>) -> u64 {
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let tx = tx.clone();
+        self.waiters().push((
+            id,
+            Box::new(move || {
+                let _ = tx.send(None);
+            }),
+        ));
+        id
+    }
+
+    fn unregister(&self, id: u64) {
+        self.waiters().retain(|(waiter, _)| *waiter != id);
+    }
+
+    fn waiters(&self) -> MutexGuard<'_, Vec<(u64, Waker)>> {
+        self.inner
+            .waiters
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -1,9 +1,21 @@
-mod shared;
+mod authorize;
+mod dry_run;
+mod http_log;
+pub(crate) mod shared;
 
-#[cfg(feature = "blocking")]
+pub use authorize::AuthorizationUrlBuilder;
+pub use http_log::HttpExchange;
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
 mod blocking;
-#[cfg(feature = "blocking")]
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+mod interrupt;
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+mod transport;
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
 pub use blocking::OAuthClient;
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+pub use interrupt::InterruptHandle;
 
 #[cfg(feature = "async")]
 mod r#async;
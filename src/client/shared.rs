@@ -1,12 +1,204 @@
-use crate::{AnthropicAuthError, Result};
+pub(super) use crate::csrf::check_state_entropy;
+use crate::csrf::states_match;
+use crate::types::{API_KEY_URL, TOKEN_URL};
+use crate::{
+    AnthropicAuthError, ApiKeyOptions, CallbackData, CsrfState, OAuthConfig, OAuthFlow, OAuthMode,
+    PkceVerifier, ProtocolProfile, Result, TokenSet,
+};
+use rand::RngCore;
+#[cfg(feature = "reqwest")]
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::de::DeserializeOwned;
 use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-// OAuth constants
-pub(super) const SCOPE: &str = "org:create_api_key user:profile user:inference";
-pub(super) const TOKEN_URL: &str = "https://console.anthropic.com/v1/oauth/token";
-pub(super) const API_KEY_URL: &str =
-    "https://api.anthropic.com/api/oauth/claude_cli/create_api_key";
-pub(super) const REDIRECT_URI: &str = "https://console.anthropic.com/oauth/code/callback";
+/// Get the authorization page URL for a mode, honoring endpoint overrides
+///
+/// A [`OAuthMode::Custom`] mode wins over the configured endpoints.
+pub(super) fn authorize_url(config: &OAuthConfig, mode: &OAuthMode) -> String {
+    match (mode, &config.endpoints) {
+        (OAuthMode::Custom(endpoints), _) | (_, Some(endpoints)) => endpoints.authorize_url.clone(),
+        (mode, None) => mode.endpoints().authorize_url,
+    }
+}
+
+/// Get the token endpoint URL for a flow's mode (if known), honoring endpoint overrides
+pub(super) fn token_url<'a>(config: &'a OAuthConfig, mode: Option<&'a OAuthMode>) -> &'a str {
+    match (mode, &config.endpoints) {
+        (Some(OAuthMode::Custom(endpoints)), _) | (_, Some(endpoints)) => &endpoints.token_url,
+        _ => TOKEN_URL,
+    }
+}
+
+/// Get the API key creation endpoint URL, honoring endpoint overrides
+pub(super) fn api_key_url(config: &OAuthConfig) -> &str {
+    config
+        .endpoints
+        .as_ref()
+        .map_or(API_KEY_URL, |endpoints| &endpoints.api_key_url)
+}
+
+/// Generate the PKCE verifier and CSRF state token for a new flow
+///
+/// Uses the configured [`FlowRng`](crate::FlowRng), or `thread_rng` by default.
+pub(super) fn generate_pkce_and_state(config: &OAuthConfig) -> (PkceVerifier, CsrfState) {
+    // 32 bytes each: a 43 character verifier and a 256-bit state, both base64url
+    let mut verifier_bytes = [0u8; 32];
+    let mut state_bytes = [0u8; 32];
+    match &config.rng {
+        Some(rng) => {
+            rng.fill_bytes(&mut verifier_bytes);
+            rng.fill_bytes(&mut state_bytes);
+        }
+        None => {
+            let mut rng = rand::thread_rng();
+            rng.fill_bytes(&mut verifier_bytes);
+            rng.fill_bytes(&mut state_bytes);
+        }
+    }
+
+    (
+        PkceVerifier::from_bytes(&verifier_bytes),
+        CsrfState::from_bytes(&state_bytes),
+    )
+}
+
+/// How many exchanged authorization codes a client remembers
+const EXCHANGED_CODES_CAPACITY: usize = 32;
+
+/// How long a repeated exchange can get the cached tokens (seconds)
+const EXCHANGED_CODES_TTL_SECS: u64 = 300;
+
+/// An exchange this client already made: the hashed flow inputs, the tokens they
+/// yielded and when
+#[derive(Debug)]
+struct ExchangedCode {
+    key: String,
+    tokens: TokenSet,
+    exchanged_at: u64,
+}
+
+/// Authorization codes a client already exchanged, with the tokens they yielded
+///
+/// Re-submitting a used code gets an opaque HTTP 400 from the server, so a repeated
+/// exchange by the same flow (e.g. a retried callback handler) returns the cached
+/// tokens instead. Entries are keyed by a SHA-256 hash of the code, PKCE verifier
+/// and redirect URI, so only a caller holding the flow's verifier gets a hit. They
+/// expire after a few minutes and are dropped once their refresh token is used;
+/// clones share the same cache.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ExchangedCodes(Arc<Mutex<VecDeque<ExchangedCode>>>);
+
+impl ExchangedCodes {
+    /// Get the tokens the same flow already exchanged a code for
+    pub(super) fn get(
+        &self,
+        code: &str,
+        verifier: &PkceVerifier,
+        redirect_uri: &str,
+        now: u64,
+    ) -> Option<TokenSet> {
+        let key = exchange_key(code, verifier, redirect_uri);
+        let mut codes = self.lock();
+        codes.retain(|entry| now.saturating_sub(entry.exchanged_at) < EXCHANGED_CODES_TTL_SECS);
+        codes
+            .iter()
+            .find(|entry| entry.key == key)
+            .map(|entry| entry.tokens.clone())
+    }
+
+    /// Remember the tokens a code was exchanged for, forgetting the oldest code when full
+    pub(super) fn insert(
+        &self,
+        code: &str,
+        verifier: &PkceVerifier,
+        redirect_uri: &str,
+        tokens: &TokenSet,
+        now: u64,
+    ) {
+        let mut codes = self.lock();
+        if codes.len() == EXCHANGED_CODES_CAPACITY {
+            codes.pop_front();
+        }
+        codes.push_back(ExchangedCode {
+            key: exchange_key(code, verifier, redirect_uri),
+            tokens: tokens.clone(),
+            exchanged_at: now,
+        });
+    }
+
+    /// Forget exchanges whose refresh token was just used, as it may have rotated
+    pub(super) fn forget_refresh_token(&self, refresh_token: &str) {
+        self.lock()
+            .retain(|entry| entry.tokens.refresh_token != refresh_token);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, VecDeque<ExchangedCode>> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+fn exchange_key(code: &str, verifier: &PkceVerifier, redirect_uri: &str) -> String {
+    let mut hasher = Sha256::new();
+    for part in [code, verifier.as_str(), redirect_uri] {
+        // Length-prefixed so that no two inputs hash the same concatenation
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Status and body of an HTTP response, read in full
+pub(super) struct HttpResponse {
+    pub status: u16,
+    pub body: String,
+    /// How long the server asked to wait before retrying, if it said so
+    pub retry_after: Option<Duration>,
+}
+
+impl HttpResponse {
+    /// Check whether the status is 2xx
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Deserialize the body as JSON
+    pub fn json<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_str(&self.body)?)
+    }
+}
+
+/// Convert the configured default headers into a `HeaderMap`
+#[cfg(feature = "reqwest")]
+pub(super) fn build_default_headers(headers: &[(String, String)]) -> Result<HeaderMap> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let header_name = HeaderName::from_bytes(name.as_bytes()).map_err(|e| {
+            AnthropicAuthError::InvalidConfig(format!("Invalid header name '{}': {}", name, e))
+        })?;
+        let header_value = HeaderValue::from_str(value).map_err(|e| {
+            AnthropicAuthError::InvalidConfig(format!("Invalid value for header '{}': {}", name, e))
+        })?;
+        map.append(header_name, header_value);
+    }
+    Ok(map)
+}
+
+/// Create a proxy for all requests, still honoring `NO_PROXY`
+#[cfg(all(feature = "reqwest", not(target_arch = "wasm32")))]
+pub(super) fn build_proxy(url: &str) -> Result<reqwest::Proxy> {
+    let proxy = reqwest::Proxy::all(url).map_err(|e| {
+        AnthropicAuthError::InvalidConfig(format!("Invalid proxy URL '{}': {}", url, e))
+    })?;
+    Ok(proxy.no_proxy(reqwest::NoProxy::from_env()))
+}
 
 /// Build the token exchange request body
 pub(super) fn build_token_request(
@@ -14,15 +206,20 @@ pub(super) fn build_token_request(
     state: &str,
     verifier: &str,
     client_id: &str,
+    redirect_uri: &str,
+    protocol: ProtocolProfile,
 ) -> serde_json::Value {
-    json!({
+    let mut body = json!({
         "code": code,
-        "state": state,
         "grant_type": "authorization_code",
         "client_id": client_id,
-        "redirect_uri": REDIRECT_URI,
+        "redirect_uri": redirect_uri,
         "code_verifier": verifier,
-    })
+    });
+    if protocol.echoes_state_in_token_request() {
+        body["state"] = json!(state);
+    }
+    body
 }
 
 /// Build the refresh token request body
@@ -35,81 +232,199 @@ pub(super) fn build_refresh_request(refresh_token: &str, client_id: &str) -> ser
 }
 
 /// Build the API key creation request body
-pub(super) fn build_api_key_request() -> serde_json::Value {
-    json!({})
-}
-
-/// Create a detailed error from HTTP response
-pub(super) fn create_http_error(status: u16, body: &str) -> AnthropicAuthError {
-    // Provide helpful hints based on common error scenarios
-    let hint = match status {
-        400 => {
-            if body.contains("code") {
-                Some("The authorization code may be invalid, expired, or already used. Please try the flow again.")
-            } else if body.contains("verifier") || body.contains("code_verifier") {
-                Some("The PKCE verifier doesn't match. Make sure you're using the verifier from the same flow.")
-            } else if body.contains("state") {
-                Some("The state parameter is invalid. This could indicate a security issue.")
-            } else {
-                Some("Bad request - check that all parameters are correct.")
+pub(super) fn build_api_key_request(options: &ApiKeyOptions) -> serde_json::Value {
+    json!(options)
+}
+
+/// How many times a rate-limited request is retried when respecting rate limits
+#[cfg(any(
+    feature = "blocking",
+    feature = "blocking-ureq",
+    all(feature = "tokio", not(target_arch = "wasm32"))
+))]
+const RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Longest wait read from a response, and thus slept through when respecting rate limits
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Timeout of webhook requests, which use their own HTTP client
+#[cfg(not(target_arch = "wasm32"))]
+pub(super) const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Wait assumed when a 429 response doesn't say when to retry
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Reset values at least this large are Unix timestamps rather than delays
+/// (about 3 years in seconds, so well past any real delay)
+const RESET_TIMESTAMP_THRESHOLD: f64 = 100_000_000.0;
+
+/// Read how long to wait before retrying from the headers of a 429 response
+///
+/// Understands `retry-after-ms`, `retry-after` in seconds, and the
+/// `ratelimit-reset`/`x-ratelimit-reset` headers as a delay in seconds or a Unix
+/// timestamp. Other statuses and unparseable values give `None`; waits are
+/// capped at a minute, so a hostile header can't stall or crash the client.
+pub(super) fn parse_retry_after<'a>(
+    status: u16,
+    header: impl Fn(&str) -> Option<&'a str>,
+) -> Option<Duration> {
+    if status != 429 {
+        return None;
+    }
+    let seconds = |name| {
+        header(name)
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .filter(|secs| secs.is_finite() && *secs >= 0.0)
+    };
+    let reset = |name| {
+        seconds(name).map(|secs| {
+            if secs < RESET_TIMESTAMP_THRESHOLD {
+                return secs;
             }
-        }
-        401 => Some("Authentication failed - the access token may be invalid or expired."),
-        403 => Some("Access forbidden - you may not have permission to perform this action."),
-        404 => Some("Endpoint not found - the API URL may have changed."),
-        429 => Some("Rate limit exceeded - please wait before retrying."),
-        500..=599 => {
-            Some("Server error - this is an issue on Anthropic's side. Please try again later.")
-        }
-        _ => None,
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or(Duration::ZERO)
+                .as_secs_f64();
+            (secs - now).max(0.0)
+        })
     };
+    seconds("retry-after-ms")
+        .map(|ms| ms / 1000.0)
+        .or_else(|| seconds("retry-after"))
+        .or_else(|| reset("ratelimit-reset"))
+        .or_else(|| reset("x-ratelimit-reset"))
+        .map(|secs| {
+            Duration::try_from_secs_f64(secs)
+                .map_or(MAX_RATE_LIMIT_WAIT, |wait| wait.min(MAX_RATE_LIMIT_WAIT))
+        })
+}
 
-    let error_message = if let Some(hint) = hint {
-        format!("HTTP {} - {}\nHint: {}", status, body, hint)
-    } else {
-        format!("HTTP {} - {}", status, body)
-    };
+/// Read how long to wait before retrying from reqwest response headers
+#[cfg(feature = "reqwest")]
+pub(super) fn header_retry_after(status: u16, headers: &HeaderMap) -> Option<Duration> {
+    parse_retry_after(status, |name| {
+        headers.get(name).and_then(|value| value.to_str().ok())
+    })
+}
+
+/// Get how long to sleep before retrying a response, if it should be retried
+#[cfg(any(
+    feature = "blocking",
+    feature = "blocking-ureq",
+    all(feature = "tokio", not(target_arch = "wasm32"))
+))]
+pub(super) fn rate_limit_wait(
+    config: &OAuthConfig,
+    status: u16,
+    retry_after: Option<Duration>,
+    attempt: u32,
+) -> Option<Duration> {
+    if !config.respect_rate_limits || status != 429 || attempt >= RATE_LIMIT_RETRIES {
+        return None;
+    }
+    Some(retry_after.unwrap_or(DEFAULT_RETRY_AFTER))
+}
+
+/// Create an error from an HTTP response
+///
+/// A 429 becomes [`AnthropicAuthError::RateLimited`], waiting `retry_after` or a
+/// default of 60 seconds. What to do about other statuses is decided by
+/// [`AnthropicAuthError::recovery`].
+pub(super) fn create_http_error(
+    status: u16,
+    body: &str,
+    retry_after: Option<Duration>,
+) -> AnthropicAuthError {
+    if status == 429 {
+        return AnthropicAuthError::RateLimited {
+            retry_after: retry_after.unwrap_or(DEFAULT_RETRY_AFTER),
+            body: body.to_string(),
+        };
+    }
 
     AnthropicAuthError::Http {
         status,
-        body: error_message,
+        body: body.to_string(),
     }
 }
 
-/// Validate authorization code format
-pub(super) fn validate_code(code: &str) -> Result<()> {
-    if code.is_empty() {
-        return Err(AnthropicAuthError::InvalidAuthorizationCode);
+/// Parse code and state from the authorization response
+///
+/// Anthropic returns the authorization response in the format "code#state".
+/// This function parses that format and validates the state against the expected value.
+///
+/// # Arguments
+///
+/// * `code_with_state` - The authorization response (may contain "#state" or just the code)
+/// * `expected_state` - The state token from the original flow for validation
+/// * `protocol` - Whether the response uses the `code#state` format at all
+///
+/// # Returns
+///
+/// A tuple of (code, state) where state has been validated against expected_state
+///
+/// # Errors
+///
+/// Returns an error if the state doesn't match the expected state (CSRF protection)
+pub(crate) fn parse_code_and_state(
+    code_with_state: &str,
+    expected_state: &CsrfState,
+    protocol: ProtocolProfile,
+) -> Result<(String, String)> {
+    if !protocol.uses_code_with_state() {
+        return Ok((
+            code_with_state.to_string(),
+            expected_state.as_str().to_string(),
+        ));
     }
-    // Authorization codes should be reasonably long
-    if code.len() < 10 {
-        return Err(AnthropicAuthError::InvalidAuthorizationCode);
+
+    match code_with_state.split_once('#') {
+        Some((code, returned_state)) => {
+            // Validate state for CSRF protection, without revealing either value
+            if !states_match(returned_state, expected_state) {
+                return Err(AnthropicAuthError::OAuth(
+                    "State mismatch - possible CSRF attack".to_string(),
+                ));
+            }
+
+            Ok((code.to_string(), returned_state.to_string()))
+        }
+        // No "#" found, assume just the code was provided
+        None => Ok((
+            code_with_state.to_string(),
+            expected_state.as_str().to_string(),
+        )),
+    }
+}
+
+/// Run the configured hook with freshly issued tokens, if any
+pub(super) fn tokens_updated(config: &OAuthConfig, tokens: &TokenSet) {
+    if let Some(hook) = &config.on_tokens_updated {
+        hook.call(tokens);
     }
-    Ok(())
 }
 
-/// Validate state token format
-pub(super) fn validate_state(state: &str) -> Result<()> {
-    if state.is_empty() {
+/// Check that a callback belongs to the given flow
+///
+/// The callback server already validates the state, but the callback may have been
+/// captured by other means or paired with the wrong flow.
+pub(super) fn check_callback_state(callback: &CallbackData, flow: &OAuthFlow) -> Result<()> {
+    if !states_match(&callback.state, &flow.state) {
         return Err(AnthropicAuthError::OAuth(
-            "State token is empty".to_string(),
+            "State mismatch - possible CSRF attack".to_string(),
         ));
     }
     Ok(())
 }
 
-/// Validate verifier format
-pub(super) fn validate_verifier(verifier: &str) -> Result<()> {
-    if verifier.is_empty() {
-        return Err(AnthropicAuthError::OAuth(
-            "PKCE verifier is empty".to_string(),
-        ));
+/// Validate authorization code format
+pub(super) fn validate_code(code: &str) -> Result<()> {
+    if code.is_empty() {
+        return Err(AnthropicAuthError::InvalidAuthorizationCode);
     }
-    // PKCE verifier should be between 43-128 characters (base64url encoded)
-    if verifier.len() < 43 || verifier.len() > 128 {
-        return Err(AnthropicAuthError::OAuth(
-            "PKCE verifier has invalid length (must be 43-128 characters)".to_string(),
-        ));
+    // Authorization codes should be reasonably long
+    if code.len() < 10 {
+        return Err(AnthropicAuthError::InvalidAuthorizationCode);
     }
     Ok(())
 }
@@ -0,0 +1,233 @@
+use serde_json::Value;
+
+use super::shared::HttpResponse;
+use crate::{OAuthConfig, Result};
+
+/// Blocking HTTP backend used by `OAuthClient`
+///
+/// `reqwest::blocking` is used by default; the `blocking-ureq` feature switches to
+/// `ureq`, which doesn't need an async runtime under the hood.
+pub(super) trait Transport: Sized + Clone + Send + 'static {
+    /// Create the backend, applying the configured default headers
+    fn from_config(config: &OAuthConfig) -> Result<Self>;
+
+    /// Create a backend for webhook events
+    ///
+    /// Webhooks go to third-party URLs, so they get neither the configured
+    /// default headers nor the proxy, and a short timeout of their own.
+    fn for_webhook() -> Result<Self>;
+
+    /// POST a JSON body, optionally with a bearer token
+    ///
+    /// Non-2xx responses are returned as `Ok` so the caller can build a detailed error.
+    fn post_json(&self, url: &str, bearer: Option<&str>, body: &Value) -> Result<HttpResponse>;
+
+    /// POST an already serialized JSON body with extra headers
+    ///
+    /// Used where the exact bytes matter, e.g. for signed webhook events.
+    fn post_raw(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: String,
+    ) -> Result<HttpResponse>;
+}
+
+// ureq wins when both backends are enabled, so `--all-features` builds still work; the
+// reqwest one is then compiled but unused (see the `blocking-ureq` feature docs)
+#[cfg(not(feature = "blocking-ureq"))]
+pub(super) type DefaultTransport = reqwest::blocking::Client;
+
+#[cfg(feature = "blocking-ureq")]
+pub(super) type DefaultTransport = UreqTransport;
+
+#[cfg(not(feature = "blocking-ureq"))]
+impl Transport for reqwest::blocking::Client {
+    fn from_config(config: &OAuthConfig) -> Result<Self> {
+        let mut builder = reqwest::blocking::Client::builder().default_headers(
+            super::shared::build_default_headers(&config.default_headers)?,
+        );
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = config.request_timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(super::shared::build_proxy(proxy)?);
+        }
+
+        builder
+            .build()
+            .map_err(|e| crate::AnthropicAuthError::ClientCreation(e.to_string()))
+    }
+
+    fn for_webhook() -> Result<Self> {
+        reqwest::blocking::Client::builder()
+            .no_proxy()
+            .timeout(super::shared::WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| crate::AnthropicAuthError::ClientCreation(e.to_string()))
+    }
+
+    fn post_json(&self, url: &str, bearer: Option<&str>, body: &Value) -> Result<HttpResponse> {
+        let mut request = self.post(url).json(body);
+        if let Some(token) = bearer {
+            request = request.header("authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send()?;
+        let status = response.status().as_u16();
+        let retry_after = super::shared::header_retry_after(status, response.headers());
+        let body = response.text()?;
+
+        Ok(HttpResponse {
+            status,
+            body,
+            retry_after,
+        })
+    }
+
+    fn post_raw(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: String,
+    ) -> Result<HttpResponse> {
+        let mut request = self
+            .post(url)
+            .header("content-type", "application/json")
+            .body(body);
+        for (name, value) in headers {
+            request = request.header(name, value);
+        }
+
+        let response = request.send()?;
+        let status = response.status().as_u16();
+        let retry_after = super::shared::header_retry_after(status, response.headers());
+        let body = response.text()?;
+
+        Ok(HttpResponse {
+            status,
+            body,
+            retry_after,
+        })
+    }
+}
+
+/// `ureq`-based backend that doesn't spin up a tokio runtime
+#[cfg(feature = "blocking-ureq")]
+#[derive(Clone)]
+pub(super) struct UreqTransport {
+    agent: ureq::Agent,
+    default_headers: Vec<(String, String)>,
+}
+
+#[cfg(feature = "blocking-ureq")]
+impl Transport for UreqTransport {
+    fn from_config(config: &OAuthConfig) -> Result<Self> {
+        for (name, value) in &config.default_headers {
+            validate_header(name, value)?;
+        }
+
+        let mut agent = ureq::AgentBuilder::new();
+        if let Some(timeout) = config.connect_timeout {
+            agent = agent.timeout_connect(timeout);
+        }
+        if let Some(timeout) = config.request_timeout {
+            agent = agent.timeout(timeout);
+        }
+        if let Some(proxy) = &config.proxy {
+            let proxy = ureq::Proxy::new(proxy).map_err(|e| {
+                crate::AnthropicAuthError::InvalidConfig(format!(
+                    "Invalid proxy URL '{}': {}",
+                    proxy, e
+                ))
+            })?;
+            agent = agent.proxy(proxy);
+        }
+
+        Ok(Self {
+            agent: agent.build(),
+            default_headers: config.default_headers.clone(),
+        })
+    }
+
+    fn for_webhook() -> Result<Self> {
+        Ok(Self {
+            agent: ureq::AgentBuilder::new()
+                .timeout(super::shared::WEBHOOK_TIMEOUT)
+                .build(),
+            default_headers: Vec::new(),
+        })
+    }
+
+    fn post_json(&self, url: &str, bearer: Option<&str>, body: &Value) -> Result<HttpResponse> {
+        let mut request = self.agent.post(url).set("content-type", "application/json");
+        for (name, value) in &self.default_headers {
+            request = request.set(name, value);
+        }
+        if let Some(token) = bearer {
+            request = request.set("authorization", &format!("Bearer {}", token));
+        }
+
+        send_ureq(request, &body.to_string())
+    }
+
+    fn post_raw(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: String,
+    ) -> Result<HttpResponse> {
+        let mut request = self.agent.post(url).set("content-type", "application/json");
+        for (name, value) in self.default_headers.iter().chain(headers) {
+            request = request.set(name, value);
+        }
+
+        send_ureq(request, &body)
+    }
+}
+
+/// Send a ureq request, returning non-2xx responses as `Ok`
+#[cfg(feature = "blocking-ureq")]
+fn send_ureq(request: ureq::Request, body: &str) -> Result<HttpResponse> {
+    let response = match request.send_string(body) {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(e) => return Err(crate::AnthropicAuthError::Transport(e.to_string())),
+    };
+    let status = response.status();
+    let retry_after = super::shared::parse_retry_after(status, |name| response.header(name));
+    let body = response
+        .into_string()
+        .map_err(|e| crate::AnthropicAuthError::Transport(e.to_string()))?;
+
+    Ok(HttpResponse {
+        status,
+        body,
+        retry_after,
+    })
+}
+
+/// Reject header names and values that ureq would only refuse at request time
+#[cfg(feature = "blocking-ureq")]
+fn validate_header(name: &str, value: &str) -> Result<()> {
+    const SEPARATORS: &str = "()<>@,;:\\\"/[]?={} \t";
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_graphic() && !SEPARATORS.contains(c))
+    {
+        return Err(crate::AnthropicAuthError::InvalidConfig(format!(
+            "Invalid header name '{}'",
+            name
+        )));
+    }
+    if value.chars().any(|c| c.is_control() && c != '\t') {
+        return Err(crate::AnthropicAuthError::InvalidConfig(format!(
+            "Invalid value for header '{}'",
+            name
+        )));
+    }
+    Ok(())
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// How long before expiry a token already counts as expired by default (5 minutes)
+///
+/// Prevents races where a token expires between checking and using it.
+pub const DEFAULT_EXPIRY_BUFFER: Duration = Duration::from_secs(300);
+
+/// Source of the current time for expiry checks
+///
+/// Defaults to [`SystemClock`]. Implement it to plug in NTP-corrected or
+/// otherwise trusted time on machines with unreliable clocks (embedded devices,
+/// suspended VMs), or use [`FixedClock`] for deterministic tests.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{Clock, OAuthConfig};
+/// use std::time::{Duration, SystemTime};
+///
+/// /// Local clock corrected by an offset learned from an NTP server
+/// #[derive(Debug)]
+/// struct CorrectedClock {
+///     offset: Duration,
+/// }
+///
+/// impl Clock for CorrectedClock {
+///     fn now(&self) -> SystemTime {
+///         SystemTime::now() + self.offset
+///     }
+/// }
+///
+/// let config = OAuthConfig::builder()
+///     .clock(CorrectedClock { offset: Duration::from_secs(42) })
+///     .build();
+/// ```
+pub trait Clock: fmt::Debug + Send + Sync {
+    /// Get the current time
+    fn now(&self) -> SystemTime;
+
+    /// Get the current time as a Unix timestamp in seconds
+    fn unix_secs(&self) -> u64 {
+        self.now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or(Duration::ZERO)
+            .as_secs()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Arc<C> {
+    fn now(&self) -> SystemTime {
+        (**self).now()
+    }
+}
+
+/// The operating system's clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Clock that only moves when told to
+///
+/// **Warning:** Only for tests.
+///
+/// Pass an `Arc<FixedClock>` to [`OAuthConfigBuilder::clock`](crate::OAuthConfigBuilder::clock)
+/// to keep a handle for moving the time.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{FixedClock, TokenSet, DEFAULT_EXPIRY_BUFFER};
+/// use std::time::Duration;
+///
+/// let clock = FixedClock::at_unix_secs(1_700_000_000);
+/// let tokens = TokenSet {
+///     access_token: "access".to_string(),
+///     refresh_token: "refresh".to_string(),
+///     expires_at: 1_700_003_600,
+///     scope: None,
+///     token_type: None,
+///     organization: None,
+///     obtained_at: None,
+///     account_id: None,
+/// };
+/// assert!(!tokens.is_expired_with(&clock, Duration::ZERO, DEFAULT_EXPIRY_BUFFER));
+///
+/// clock.advance(Duration::from_secs(3400));
+/// assert!(tokens.is_expired_with(&clock, Duration::ZERO, DEFAULT_EXPIRY_BUFFER));
+/// ```
+#[derive(Debug)]
+pub struct FixedClock(Mutex<SystemTime>);
+
+impl FixedClock {
+    /// Stop the clock at `time`
+    pub fn new(time: SystemTime) -> Self {
+        Self(Mutex::new(time))
+    }
+
+    /// Stop the clock at a Unix timestamp in seconds
+    pub fn at_unix_secs(secs: u64) -> Self {
+        Self::new(UNIX_EPOCH + Duration::from_secs(secs))
+    }
+
+    /// Set the time
+    pub fn set(&self, time: SystemTime) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = time;
+    }
+
+    /// Move the time forward
+    pub fn advance(&self, by: Duration) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
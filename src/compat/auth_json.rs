@@ -0,0 +1,356 @@
+//! `auth.json` files of open-source agent tools
+//!
+//! Agent tools keep their Anthropic login in a JSON file, with the same three
+//! values under different names. [`AuthJsonFormat`] names each layout explicitly:
+//!
+//! | Format | Layout | Expiry |
+//! |--------|--------|--------|
+//! | [`OpenCode`](AuthJsonFormat::OpenCode) | `{"anthropic": {"type": "oauth", "access", "refresh", "expires"}}` | milliseconds |
+//! | [`ClaudeCode`](AuthJsonFormat::ClaudeCode) | `{"claudeAiOauth": {"accessToken", "refreshToken", "expiresAt"}}` | milliseconds |
+//! | [`OAuth`](AuthJsonFormat::OAuth) | `{"access_token", "refresh_token", "expires_at"}` | seconds |
+//!
+//! # Example
+//!
+//! ```no_run
+//! use anthropic_auth::compat::auth_json::{AuthJsonFormat, AuthJsonStore};
+//! use anthropic_auth::TokenStore;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Share the login of OpenCode; other providers in its auth.json are kept
+//! let store = AuthJsonStore::at_default_path(AuthJsonFormat::OpenCode)?;
+//! if let Some(tokens) = store.load()? {
+//!     println!("Signed in with OpenCode, expires in {:?}", tokens.expires_in());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{claude_code, home_dir, storage_error, write_atomic};
+use crate::{AnthropicAuthError, Result, TokenSet, TokenStore};
+
+/// Provider key of the Anthropic entry in OpenCode's `auth.json`
+const OPENCODE_PROVIDER: &str = "anthropic";
+
+/// Top-level keys a token set is read from in the [`AuthJsonFormat::OAuth`]
+/// format, including the accepted aliases
+const OAUTH_TOKEN_KEYS: &[&str] = &[
+    "version",
+    "access_token",
+    "refresh_token",
+    "expires_at",
+    "expires_in",
+    "obtained_at",
+    "created_at",
+    "issued_at",
+    "scope",
+    "scopes",
+    "token_type",
+    "organization",
+    "account_id",
+    "account_uuid",
+];
+
+/// Layout of an `auth.json` credentials file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum AuthJsonFormat {
+    /// OpenCode's `auth.json`: one entry per provider, the OAuth login under
+    /// `anthropic` with `access`, `refresh` and `expires` (milliseconds)
+    OpenCode,
+    /// Claude Code's `.credentials.json` (see [`claude_code`])
+    ClaudeCode,
+    /// Plain OAuth field names at the top level: `access_token`, `refresh_token`
+    /// and `expires_at` (seconds), as written by [`TokenSet::to_json_pretty`]
+    OAuth,
+}
+
+impl AuthJsonFormat {
+    /// Guess the format of a credentials document
+    ///
+    /// Returns `None` if the JSON is malformed or matches no known layout.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::compat::auth_json::AuthJsonFormat;
+    ///
+    /// let json = r#"{"anthropic": {"type": "oauth", "access": "a", "refresh": "r", "expires": 0}}"#;
+    /// assert_eq!(AuthJsonFormat::detect(json), Some(AuthJsonFormat::OpenCode));
+    /// ```
+    pub fn detect(json: &str) -> Option<Self> {
+        let document: Value = serde_json::from_str(json).ok()?;
+        Self::detect_document(&document)
+    }
+
+    fn detect_document(document: &Value) -> Option<Self> {
+        if document.get(claude_code::OAUTH_KEY).is_some() {
+            Some(Self::ClaudeCode)
+        } else if document
+            .get(OPENCODE_PROVIDER)
+            .and_then(|entry| entry.get("type"))
+            .is_some_and(|kind| kind == "oauth")
+        {
+            Some(Self::OpenCode)
+        } else if document.get("access_token").is_some() && document.get("refresh_token").is_some()
+        {
+            Some(Self::OAuth)
+        } else {
+            None
+        }
+    }
+
+    /// Get where the owning tool keeps this file, if it has a fixed location
+    ///
+    /// - `OpenCode`: `$XDG_DATA_HOME/opencode/auth.json`, else `~/.local/share/opencode/auth.json`
+    /// - `ClaudeCode`: [`claude_code::default_path`]
+    /// - `OAuth`: `None`
+    pub fn default_path(self) -> Option<PathBuf> {
+        match self {
+            Self::OpenCode => {
+                let data_dir = match std::env::var_os("XDG_DATA_HOME") {
+                    Some(dir) => PathBuf::from(dir),
+                    None => home_dir()?.join(".local").join("share"),
+                };
+                Some(data_dir.join("opencode").join("auth.json"))
+            }
+            Self::ClaudeCode => claude_code::default_path(),
+            Self::OAuth => None,
+        }
+    }
+
+    /// Read the tokens of a document, or `None` if it holds no login
+    fn read(self, document: &Value) -> Result<Option<TokenSet>> {
+        match self {
+            Self::OpenCode => match document.get(OPENCODE_PROVIDER) {
+                Some(entry) if entry.get("type").is_some_and(|kind| kind == "oauth") => {
+                    let entry: OpenCodeOAuth = serde_json::from_value(entry.clone())?;
+                    Ok(Some(entry.into()))
+                }
+                _ => Ok(None),
+            },
+            Self::ClaudeCode => claude_code::read_entry(document),
+            Self::OAuth if document.get("access_token").is_some() => {
+                let stored = serde_json::from_value(document.clone())?;
+                Ok(Some(TokenSet::from_versioned(stored)?))
+            }
+            Self::OAuth => Ok(None),
+        }
+    }
+
+    /// Store the tokens in a document (an object), keeping unrelated entries
+    fn write(self, document: &mut Value, tokens: &TokenSet) -> Result<()> {
+        match self {
+            Self::OpenCode => {
+                document[OPENCODE_PROVIDER] = serde_json::to_value(OpenCodeOAuth::from(tokens))?;
+            }
+            Self::ClaudeCode => claude_code::write_entry(document, tokens)?,
+            Self::OAuth => {
+                if let (Some(entries), Value::Object(fields)) = (
+                    document.as_object_mut(),
+                    serde_json::to_value(tokens.versioned())?,
+                ) {
+                    // Drop the previous login's fields, including those the new
+                    // tokens leave out
+                    entries.retain(|key, _| !OAUTH_TOKEN_KEYS.contains(&key.as_str()));
+                    entries.extend(fields);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The `anthropic` entry of OpenCode's `auth.json`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename = "oauth")]
+struct OpenCodeOAuth {
+    access: String,
+    refresh: String,
+    /// Unix timestamp in milliseconds
+    expires: u64,
+}
+
+impl From<OpenCodeOAuth> for TokenSet {
+    fn from(entry: OpenCodeOAuth) -> Self {
+        TokenSet {
+            access_token: entry.access,
+            refresh_token: entry.refresh,
+            expires_at: entry.expires / 1000,
+            scope: None,
+            token_type: None,
+            organization: None,
+            obtained_at: None,
+            account_id: None,
+        }
+    }
+}
+
+impl From<&TokenSet> for OpenCodeOAuth {
+    fn from(tokens: &TokenSet) -> Self {
+        OpenCodeOAuth {
+            access: tokens.access_token.clone(),
+            refresh: tokens.refresh_token.clone(),
+            expires: tokens.expires_at.saturating_mul(1000),
+        }
+    }
+}
+
+/// Read a token set from an `auth.json` document in the given format
+///
+/// # Errors
+///
+/// Returns [`AnthropicAuthError::InvalidTokenFormat`] if the document holds no
+/// login in that format, or a serialization error if it is malformed
+pub fn import(json: &str, format: AuthJsonFormat) -> Result<TokenSet> {
+    let document: Value = serde_json::from_str(json)?;
+    format.read(&document)?.ok_or_else(|| {
+        AnthropicAuthError::InvalidTokenFormat(format!("no {:?} login found", format))
+    })
+}
+
+/// Read a token set from an `auth.json` document in any known format
+///
+/// # Errors
+///
+/// Returns [`AnthropicAuthError::InvalidTokenFormat`] if the format isn't
+/// recognized, or a serialization error if the JSON is malformed
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::compat::auth_json::{self, AuthJsonFormat};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let json = r#"{"claudeAiOauth": {"accessToken": "a", "refreshToken": "r", "expiresAt": 1700000000000}}"#;
+/// let (format, tokens) = auth_json::import_any(json)?;
+/// assert_eq!(format, AuthJsonFormat::ClaudeCode);
+/// assert_eq!(tokens.expires_at, 1700000000);
+///
+/// // Hand the same login to OpenCode
+/// let opencode = auth_json::export(&tokens, AuthJsonFormat::OpenCode)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn import_any(json: &str) -> Result<(AuthJsonFormat, TokenSet)> {
+    let document: Value = serde_json::from_str(json)?;
+    let format = AuthJsonFormat::detect_document(&document).ok_or_else(|| {
+        AnthropicAuthError::InvalidTokenFormat("unrecognized auth.json format".to_string())
+    })?;
+    let tokens = format.read(&document)?.ok_or_else(|| {
+        AnthropicAuthError::InvalidTokenFormat(format!("no {:?} login found", format))
+    })?;
+    Ok((format, tokens))
+}
+
+/// Write a token set as an `auth.json` document in the given format
+///
+/// Formats without scopes or an organization (such as OpenCode) drop them.
+///
+/// # Errors
+///
+/// Returns an error if serialization fails
+pub fn export(tokens: &TokenSet, format: AuthJsonFormat) -> Result<String> {
+    let mut document = Value::Object(Map::new());
+    format.write(&mut document, tokens)?;
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// Token store reading and writing an `auth.json` file in a given format
+///
+/// Saving replaces only the Anthropic login; other entries of the file (such as
+/// OpenCode's other providers) are kept. Clearing removes just the login, except
+/// for the [`OAuth`](AuthJsonFormat::OAuth) format, whose file is removed.
+#[derive(Debug, Clone)]
+pub struct AuthJsonStore {
+    path: PathBuf,
+    format: AuthJsonFormat,
+}
+
+impl AuthJsonStore {
+    /// Use the file at `path`, in the given format
+    pub fn new(path: impl Into<PathBuf>, format: AuthJsonFormat) -> Self {
+        Self {
+            path: path.into(),
+            format,
+        }
+    }
+
+    /// Use the file where the owning tool keeps it (see [`AuthJsonFormat::default_path`])
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::Storage`] if the format has no fixed location
+    /// or no home directory is known
+    pub fn at_default_path(format: AuthJsonFormat) -> Result<Self> {
+        let path = format.default_path().ok_or_else(|| {
+            AnthropicAuthError::Storage(format!("No default location for {:?} auth.json", format))
+        })?;
+        Ok(Self::new(path, format))
+    }
+
+    /// Get the path of the file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get the format of the file
+    pub fn format(&self) -> AuthJsonFormat {
+        self.format
+    }
+
+    fn read_document(&self) -> Result<Option<Value>> {
+        match fs::read_to_string(&self.path) {
+            Ok(json) => Ok(Some(serde_json::from_str(&json)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(storage_error(&self.path, e)),
+        }
+    }
+}
+
+impl TokenStore for AuthJsonStore {
+    fn load(&self) -> Result<Option<TokenSet>> {
+        match self.read_document()? {
+            Some(document) => self.format.read(&document),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, tokens: &TokenSet) -> Result<()> {
+        let mut document = self
+            .read_document()?
+            .filter(Value::is_object)
+            .unwrap_or_else(|| Value::Object(Map::new()));
+        self.format.write(&mut document, tokens)?;
+        write_atomic(
+            &self.path,
+            serde_json::to_string_pretty(&document)?.as_bytes(),
+        )
+    }
+
+    fn clear(&self) -> Result<()> {
+        let Some(mut document) = self.read_document()? else {
+            return Ok(());
+        };
+        match self.format {
+            AuthJsonFormat::OpenCode => {
+                if let Some(entries) = document.as_object_mut() {
+                    entries.remove(OPENCODE_PROVIDER);
+                }
+            }
+            AuthJsonFormat::ClaudeCode => claude_code::remove_entry(&mut document),
+            AuthJsonFormat::OAuth => {
+                return fs::remove_file(&self.path).map_err(|e| storage_error(&self.path, e));
+            }
+        }
+        write_atomic(
+            &self.path,
+            serde_json::to_string_pretty(&document)?.as_bytes(),
+        )
+    }
+}
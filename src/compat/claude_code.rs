@@ -0,0 +1,295 @@
+//! Claude Code credentials
+//!
+//! Claude Code keeps its OAuth login as JSON under a `claudeAiOauth` key, either
+//! in `~/.claude/.credentials.json` or, on macOS, in the Keychain entry
+//! `Claude Code-credentials`:
+//!
+//! ```json
+//! {
+//!   "claudeAiOauth": {
+//!     "accessToken": "sk-ant-oat01-...",
+//!     "refreshToken": "sk-ant-ort01-...",
+//!     "expiresAt": 1748658860401,
+//!     "scopes": ["user:inference", "user:profile"],
+//!     "subscriptionType": "max"
+//!   }
+//! }
+//! ```
+//!
+//! # Example
+//!
+//! ```no_run
+//! use anthropic_auth::compat::claude_code::ClaudeCodeStore;
+//! use anthropic_auth::TokenStore;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // Reuse the login of Claude Code instead of authenticating again
+//! if let Some(tokens) = ClaudeCodeStore::detect()?.load()? {
+//!     println!("Signed in with Claude Code, expires in {:?}", tokens.expires_in());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+
+use super::{home_dir, storage_error, write_atomic};
+use crate::{AnthropicAuthError, Result, TokenSet, TokenStore};
+
+/// Keychain service name Claude Code stores its credentials under (macOS)
+pub const KEYCHAIN_SERVICE: &str = "Claude Code-credentials";
+
+/// Environment variable overriding Claude Code's configuration directory
+pub const ENV_CONFIG_DIR: &str = "CLAUDE_CONFIG_DIR";
+
+/// Key of the OAuth credentials in Claude Code's credentials JSON
+pub(super) const OAUTH_KEY: &str = "claudeAiOauth";
+
+/// The OAuth credentials entry of Claude Code
+///
+/// Fields this crate doesn't know are kept, so writing back doesn't drop them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeCodeCredentials {
+    /// The access token
+    pub access_token: String,
+    /// The refresh token
+    pub refresh_token: String,
+    /// Unix timestamp in **milliseconds** when the access token expires
+    pub expires_at: u64,
+    /// Granted scopes
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// Subscription of the account, e.g. `"max"` or `"pro"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subscription_type: Option<String>,
+    /// Any other fields
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl From<ClaudeCodeCredentials> for TokenSet {
+    fn from(credentials: ClaudeCodeCredentials) -> Self {
+        TokenSet {
+            access_token: credentials.access_token,
+            refresh_token: credentials.refresh_token,
+            expires_at: credentials.expires_at / 1000,
+            scope: (!credentials.scopes.is_empty()).then(|| credentials.scopes.join(" ")),
+            token_type: None,
+            organization: None,
+            obtained_at: None,
+            account_id: None,
+        }
+    }
+}
+
+impl From<&TokenSet> for ClaudeCodeCredentials {
+    fn from(tokens: &TokenSet) -> Self {
+        ClaudeCodeCredentials {
+            access_token: tokens.access_token.clone(),
+            refresh_token: tokens.refresh_token.clone(),
+            expires_at: tokens.expires_at.saturating_mul(1000),
+            scopes: tokens
+                .scope
+                .as_deref()
+                .map(|scope| scope.split_whitespace().map(str::to_string).collect())
+                .unwrap_or_default(),
+            subscription_type: None,
+            extra: Map::new(),
+        }
+    }
+}
+
+/// Read a token set from Claude Code's credentials JSON
+///
+/// # Errors
+///
+/// Returns [`AnthropicAuthError::InvalidTokenFormat`] if the JSON holds no
+/// `claudeAiOauth` entry, or a serialization error if it is malformed
+pub fn from_json(json: &str) -> Result<TokenSet> {
+    let document: Value = serde_json::from_str(json)?;
+    credentials_of(&document)?
+        .map(TokenSet::from)
+        .ok_or_else(|| {
+            AnthropicAuthError::InvalidTokenFormat(format!("no {} entry found", OAUTH_KEY))
+        })
+}
+
+/// Write a token set as Claude Code's credentials JSON
+///
+/// # Errors
+///
+/// Returns an error if serialization fails
+pub fn to_json(tokens: &TokenSet) -> Result<String> {
+    let mut document = Map::new();
+    document.insert(
+        OAUTH_KEY.to_string(),
+        serde_json::to_value(ClaudeCodeCredentials::from(tokens))?,
+    );
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// Get the path of Claude Code's credentials file
+///
+/// `$CLAUDE_CONFIG_DIR/.credentials.json` if set, else `~/.claude/.credentials.json`.
+/// Returns `None` if no home directory is known.
+pub fn default_path() -> Option<PathBuf> {
+    let dir = match std::env::var_os(ENV_CONFIG_DIR) {
+        Some(dir) => PathBuf::from(dir),
+        None => home_dir()?.join(".claude"),
+    };
+    Some(dir.join(".credentials.json"))
+}
+
+/// Token store reading and writing Claude Code's credentials
+///
+/// Saving updates only the OAuth tokens; other entries of the credentials (and
+/// unknown fields such as the subscription type) are kept.
+///
+/// **Note:** The Keychain backend is only available when the `keyring-store`
+/// feature is enabled.
+#[derive(Debug, Clone)]
+pub struct ClaudeCodeStore {
+    backend: Backend,
+}
+
+#[derive(Debug, Clone)]
+enum Backend {
+    File(PathBuf),
+    #[cfg(feature = "keyring-store")]
+    Keychain {
+        user: String,
+    },
+}
+
+impl ClaudeCodeStore {
+    /// Use the credentials file at `path`
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self {
+            backend: Backend::File(path.into()),
+        }
+    }
+
+    /// Use the Keychain entry Claude Code writes on macOS, for the given account
+    ///
+    /// Claude Code uses the login name (`$USER`) as the account.
+    #[cfg(feature = "keyring-store")]
+    pub fn keychain(user: impl Into<String>) -> Self {
+        Self {
+            backend: Backend::Keychain { user: user.into() },
+        }
+    }
+
+    /// Use wherever Claude Code keeps its credentials on this machine
+    ///
+    /// The Keychain on macOS (with the `keyring-store` feature), otherwise the
+    /// credentials file at [`default_path`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::Storage`] if no home directory is known
+    pub fn detect() -> Result<Self> {
+        #[cfg(all(feature = "keyring-store", target_os = "macos"))]
+        if let Some(user) = std::env::var_os("USER") {
+            return Ok(Self::keychain(user.to_string_lossy()));
+        }
+
+        default_path().map(Self::file).ok_or_else(|| {
+            AnthropicAuthError::Storage("Cannot locate Claude Code credentials".to_string())
+        })
+    }
+
+    fn read_document(&self) -> Result<Option<Value>> {
+        let json = match &self.backend {
+            Backend::File(path) => match fs::read_to_string(path) {
+                Ok(json) => json,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+                Err(e) => return Err(storage_error(path, e)),
+            },
+            #[cfg(feature = "keyring-store")]
+            Backend::Keychain { user } => match keychain_entry(user)?.get_password() {
+                Ok(json) => json,
+                Err(keyring::Error::NoEntry) => return Ok(None),
+                Err(e) => return Err(crate::store::keyring_error(e)),
+            },
+        };
+        Ok(Some(serde_json::from_str(&json)?))
+    }
+
+    fn write_document(&self, document: &Value) -> Result<()> {
+        let json = serde_json::to_string_pretty(document)?;
+        match &self.backend {
+            Backend::File(path) => write_atomic(path, json.as_bytes()),
+            #[cfg(feature = "keyring-store")]
+            Backend::Keychain { user } => keychain_entry(user)?
+                .set_password(&json)
+                .map_err(crate::store::keyring_error),
+        }
+    }
+}
+
+impl TokenStore for ClaudeCodeStore {
+    fn load(&self) -> Result<Option<TokenSet>> {
+        match self.read_document()? {
+            Some(document) => read_entry(&document),
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, tokens: &TokenSet) -> Result<()> {
+        let mut document = self
+            .read_document()?
+            .filter(Value::is_object)
+            .unwrap_or_else(|| Value::Object(Map::new()));
+        write_entry(&mut document, tokens)?;
+        self.write_document(&document)
+    }
+
+    fn clear(&self) -> Result<()> {
+        let Some(mut document) = self.read_document()? else {
+            return Ok(());
+        };
+        remove_entry(&mut document);
+        self.write_document(&document)
+    }
+}
+
+/// Read the tokens of a credentials document, if it has an OAuth entry
+pub(super) fn read_entry(document: &Value) -> Result<Option<TokenSet>> {
+    Ok(credentials_of(document)?.map(TokenSet::from))
+}
+
+/// Set the OAuth entry of a credentials document (an object), keeping other entries
+pub(super) fn write_entry(document: &mut Value, tokens: &TokenSet) -> Result<()> {
+    // Keep the fields of the existing entry this crate doesn't know about
+    let mut credentials = ClaudeCodeCredentials::from(tokens);
+    if let Some(existing) = credentials_of(document)? {
+        credentials.subscription_type = existing.subscription_type;
+        credentials.extra = existing.extra;
+    }
+    document[OAUTH_KEY] = serde_json::to_value(credentials)?;
+    Ok(())
+}
+
+/// Remove the OAuth entry of a credentials document
+pub(super) fn remove_entry(document: &mut Value) {
+    if let Some(entries) = document.as_object_mut() {
+        entries.remove(OAUTH_KEY);
+    }
+}
+
+/// Get the OAuth entry of a credentials document, if it has one
+fn credentials_of(document: &Value) -> Result<Option<ClaudeCodeCredentials>> {
+    match document.get(OAUTH_KEY) {
+        Some(entry) => Ok(Some(serde_json::from_value(entry.clone())?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(feature = "keyring-store")]
+fn keychain_entry(user: &str) -> Result<keyring::Entry> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, user).map_err(crate::store::keyring_error)
+}
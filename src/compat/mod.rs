@@ -0,0 +1,18 @@
+//! Interoperability with the credential formats of other Anthropic tools
+//!
+//! Lets apps built on this crate reuse a login made with another tool (and hand
+//! their own logins back), so users don't have to authenticate twice.
+
+pub mod auth_json;
+pub mod claude_code;
+
+use std::path::PathBuf;
+
+use crate::store::{storage_error, write_atomic};
+
+/// Get the home directory of the current user
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
@@ -0,0 +1,482 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use url::{Host, Url};
+
+use crate::{
+    AnthropicAuthError, ConfigError, Endpoints, OAuthConfig, OAuthConfigBuilder, ProfileOverrides,
+    Profiles, ProtocolProfile, Result, DEFAULT_PROFILE,
+};
+
+/// Environment variable overriding the OAuth client ID
+pub const ENV_CLIENT_ID: &str = "ANTHROPIC_OAUTH_CLIENT_ID";
+/// Environment variable overriding the redirect URI
+pub const ENV_REDIRECT_URI: &str = "ANTHROPIC_OAUTH_REDIRECT_URI";
+/// Environment variable overriding the requested scopes
+pub const ENV_SCOPE: &str = "ANTHROPIC_OAUTH_SCOPE";
+/// Environment variable overriding the authorization page (set together with
+/// [`ENV_TOKEN_URL`] and [`ENV_API_KEY_URL`])
+pub const ENV_AUTHORIZE_URL: &str = "ANTHROPIC_OAUTH_AUTHORIZE_URL";
+/// Environment variable overriding the token endpoint
+pub const ENV_TOKEN_URL: &str = "ANTHROPIC_OAUTH_TOKEN_URL";
+/// Environment variable overriding the API key creation endpoint
+pub const ENV_API_KEY_URL: &str = "ANTHROPIC_OAUTH_API_KEY_URL";
+/// Environment variable setting the proxy for OAuth requests, taking precedence
+/// over `HTTPS_PROXY` and `ALL_PROXY`
+pub const ENV_PROXY: &str = "ANTHROPIC_OAUTH_PROXY";
+
+/// Proxy variables consulted, in order, when [`ENV_PROXY`] is not set
+const PROXY_VARS: &[&str] = &[
+    ENV_PROXY,
+    "HTTPS_PROXY",
+    "https_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// Contents of a TOML or JSON config file; every field is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    client_id: Option<String>,
+    redirect_uri: Option<String>,
+    scope: Option<String>,
+    endpoints: Option<Endpoints>,
+    default_headers: BTreeMap<String, String>,
+    proxy: Option<String>,
+    connect_timeout_secs: Option<u64>,
+    request_timeout_secs: Option<u64>,
+    clock_skew_secs: Option<u64>,
+    expiry_buffer_secs: Option<u64>,
+    respect_rate_limits: Option<bool>,
+    min_state_entropy: Option<u32>,
+    protocol: Option<ProtocolProfile>,
+    debug_http: Option<bool>,
+    profiles: BTreeMap<String, ProfileOverrides>,
+}
+
+impl OAuthConfig {
+    /// Create a config from `ANTHROPIC_OAUTH_*` and proxy environment variables
+    ///
+    /// Unset variables keep their defaults. To combine the environment with code,
+    /// use [`OAuthConfigBuilder::with_env`] instead.
+    ///
+    /// | Variable | Setting |
+    /// |----------|---------|
+    /// | `ANTHROPIC_OAUTH_CLIENT_ID` | `client_id` |
+    /// | `ANTHROPIC_OAUTH_REDIRECT_URI` | `redirect` (see [`RedirectStrategy::from_uri`](crate::RedirectStrategy::from_uri)) |
+    /// | `ANTHROPIC_OAUTH_SCOPE` | `scope` |
+    /// | `ANTHROPIC_OAUTH_AUTHORIZE_URL`, `ANTHROPIC_OAUTH_TOKEN_URL`, `ANTHROPIC_OAUTH_API_KEY_URL` | `endpoints` (all three) |
+    /// | `ANTHROPIC_OAUTH_PROXY`, else `HTTPS_PROXY` or `ALL_PROXY` | `proxy` |
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] if only some of the endpoint
+    /// variables are set
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{OAuthClient, OAuthConfig};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = OAuthClient::new(OAuthConfig::from_env()?)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_env() -> Result<Self> {
+        Ok(Self::builder().with_env()?.build())
+    }
+
+    /// Create a config from a TOML or JSON file
+    ///
+    /// The format is chosen by the file extension (`.toml`, anything else is
+    /// read as JSON). Fields left out keep their defaults; unknown fields are
+    /// rejected so typos don't go unnoticed.
+    ///
+    /// ```toml
+    /// client_id = "my-client-id"
+    /// proxy = "http://proxy.internal:3128"
+    /// request_timeout_secs = 30
+    ///
+    /// [endpoints]
+    /// authorize_url = "https://staging.example.com/oauth/authorize"
+    /// token_url = "https://staging.example.com/v1/oauth/token"
+    /// api_key_url = "https://staging.example.com/api/oauth/create_api_key"
+    ///
+    /// [default_headers]
+    /// x-gateway-key = "secret"
+    ///
+    /// [profiles.staging]
+    /// client_id = "staging-client-id"
+    /// ```
+    ///
+    /// `[profiles.<name>]` sections hold [`ProfileOverrides`]; they are ignored here
+    /// and applied by [`from_file_profile`](Self::from_file_profile) and
+    /// [`Profiles::from_file`].
+    ///
+    /// **Note:** TOML files require the `config-toml` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] if the file cannot be read or parsed
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::builder().with_file(path)?.build())
+    }
+
+    /// Create a config from a TOML or JSON file, with the overrides of the
+    /// `[profiles.<profile>]` section applied (see [`from_file`](Self::from_file))
+    ///
+    /// [`DEFAULT_PROFILE`] resolves to the top-level settings unless the file has a
+    /// section for it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] if the file cannot be read or
+    /// parsed, or has no section for `profile`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{OAuthClient, OAuthConfig};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = OAuthConfig::from_file_profile("/etc/myapp/oauth.toml", "staging")?;
+    /// let client = OAuthClient::new(config)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_file_profile(path: impl AsRef<Path>, profile: &str) -> Result<Self> {
+        Ok(Self::builder().with_file_profile(path, profile)?.build())
+    }
+}
+
+impl Profiles {
+    /// Create a profile set from a TOML or JSON file
+    ///
+    /// The top-level settings form the base configuration and every
+    /// `[profiles.<name>]` section becomes a named profile (see
+    /// [`OAuthConfig::from_file`]).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] if the file cannot be read or parsed
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let mut file = read_config_file(path.as_ref())?;
+        let overrides = std::mem::take(&mut file.profiles);
+
+        let mut profiles = Profiles::new(OAuthConfig::builder().apply_file(file).build());
+        for (name, profile) in overrides {
+            profiles.insert(name, profile);
+        }
+        Ok(profiles)
+    }
+}
+
+impl OAuthConfigBuilder {
+    /// Apply the settings from environment variables (see [`OAuthConfig::from_env`])
+    ///
+    /// Settings made on the builder afterwards take precedence over the environment.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] if only some of the endpoint
+    /// variables are set
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::OAuthConfig;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// // File first, then the environment, then code
+    /// let config = OAuthConfig::builder()
+    ///     .with_file("/etc/myapp/oauth.toml")?
+    ///     .with_env()?
+    ///     .default_header("x-app", "myapp")
+    ///     .build();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_env(mut self) -> Result<Self> {
+        if let Some(client_id) = env_var(ENV_CLIENT_ID) {
+            self = self.client_id(client_id);
+        }
+        if let Some(redirect_uri) = env_var(ENV_REDIRECT_URI) {
+            self = self.redirect_uri(redirect_uri);
+        }
+        if let Some(scope) = env_var(ENV_SCOPE) {
+            self = self.scope(scope);
+        }
+
+        match (
+            env_var(ENV_AUTHORIZE_URL),
+            env_var(ENV_TOKEN_URL),
+            env_var(ENV_API_KEY_URL),
+        ) {
+            (Some(authorize_url), Some(token_url), Some(api_key_url)) => {
+                self = self.endpoints(Endpoints {
+                    authorize_url,
+                    token_url,
+                    api_key_url,
+                });
+            }
+            (None, None, None) => {}
+            _ => {
+                return Err(AnthropicAuthError::InvalidConfig(format!(
+                    "{}, {} and {} must be set together",
+                    ENV_AUTHORIZE_URL, ENV_TOKEN_URL, ENV_API_KEY_URL
+                )))
+            }
+        }
+
+        if let Some(proxy) = PROXY_VARS.iter().find_map(|name| env_var(name)) {
+            self = self.proxy(proxy);
+        }
+
+        Ok(self)
+    }
+
+    /// Apply the settings from a TOML or JSON file (see [`OAuthConfig::from_file`])
+    ///
+    /// Settings made on the builder afterwards take precedence over the file.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] if the file cannot be read or parsed
+    pub fn with_file(self, path: impl AsRef<Path>) -> Result<Self> {
+        Ok(self.apply_file(read_config_file(path.as_ref())?))
+    }
+
+    /// Apply the settings from a TOML or JSON file, then the overrides of its
+    /// `[profiles.<profile>]` section (see [`OAuthConfig::from_file_profile`])
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] if the file cannot be read or
+    /// parsed, or has no section for `profile`
+    pub fn with_file_profile(self, path: impl AsRef<Path>, profile: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = read_config_file(path)?;
+        let overrides = match file.profiles.remove(profile) {
+            Some(overrides) => overrides,
+            None if profile == DEFAULT_PROFILE => ProfileOverrides::default(),
+            None => {
+                return Err(AnthropicAuthError::InvalidConfig(format!(
+                    "Unknown profile '{}' in config file {}",
+                    profile,
+                    path.display()
+                )))
+            }
+        };
+        Ok(overrides.apply_builder(self.apply_file(file)))
+    }
+
+    fn apply_file(mut self, file: ConfigFile) -> Self {
+        if let Some(client_id) = file.client_id {
+            self = self.client_id(client_id);
+        }
+        if let Some(redirect_uri) = file.redirect_uri {
+            self = self.redirect_uri(redirect_uri);
+        }
+        if let Some(scope) = file.scope {
+            self = self.scope(scope);
+        }
+        if let Some(endpoints) = file.endpoints {
+            self = self.endpoints(endpoints);
+        }
+        for (name, value) in file.default_headers {
+            self = self.default_header(name, value);
+        }
+        if let Some(proxy) = file.proxy {
+            self = self.proxy(proxy);
+        }
+        if let Some(secs) = file.connect_timeout_secs {
+            self = self.connect_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = file.request_timeout_secs {
+            self = self.request_timeout(Duration::from_secs(secs));
+        }
+        if let Some(secs) = file.clock_skew_secs {
+            self = self.clock_skew(Duration::from_secs(secs));
+        }
+        if let Some(secs) = file.expiry_buffer_secs {
+            self = self.expiry_buffer(Duration::from_secs(secs));
+        }
+        if let Some(respect) = file.respect_rate_limits {
+            self = self.respect_rate_limits(respect);
+        }
+        if let Some(bits) = file.min_state_entropy {
+            self = self.min_state_entropy(bits);
+        }
+        if let Some(protocol) = file.protocol {
+            self = self.protocol(protocol);
+        }
+        if let Some(enabled) = file.debug_http {
+            self = self.debug_http(enabled);
+        }
+
+        self
+    }
+}
+
+impl OAuthConfig {
+    /// Check the config for mistakes that would otherwise only fail at Anthropic's side
+    ///
+    /// - The client ID must be a UUID
+    /// - The redirect URI must parse and use HTTPS, or HTTP on a loopback host
+    ///   (`localhost`, `127.0.0.1`, `[::1]`)
+    /// - Custom endpoints must use HTTPS, or HTTP on a loopback host (e.g. for a
+    ///   mock server in tests)
+    ///
+    /// [`OAuthConfigBuilder::try_build`] runs it when building.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::Config`] with the first problem found
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::{AnthropicAuthError, ConfigError, OAuthConfig};
+    ///
+    /// let config = OAuthConfig::builder().client_id("my-app").build();
+    /// assert!(matches!(
+    ///     config.validate(),
+    ///     Err(AnthropicAuthError::Config(ConfigError::InvalidClientId(_)))
+    /// ));
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        if !is_uuid(&self.client_id) {
+            return Err(ConfigError::InvalidClientId(self.client_id.clone()).into());
+        }
+
+        let redirect_error = |reason: &str| ConfigError::InvalidRedirectUri {
+            uri: self.redirect_uri(),
+            reason: reason.to_string(),
+        };
+        let redirect_uri =
+            Url::parse(&self.redirect_uri()).map_err(|e| redirect_error(&e.to_string()))?;
+        if !is_secure_or_loopback(&redirect_uri) {
+            return Err(redirect_error("must use HTTPS, or HTTP on localhost").into());
+        }
+
+        if let Some(endpoints) = &self.endpoints {
+            for (name, url) in [
+                ("authorize URL", &endpoints.authorize_url),
+                ("token URL", &endpoints.token_url),
+                ("API key URL", &endpoints.api_key_url),
+            ] {
+                let parsed = Url::parse(url).map_err(|e| ConfigError::InvalidEndpoint {
+                    name,
+                    url: url.clone(),
+                    reason: e.to_string(),
+                })?;
+                if !is_secure_or_loopback(&parsed) {
+                    return Err(ConfigError::InsecureEndpoint {
+                        name,
+                        url: url.clone(),
+                    }
+                    .into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl OAuthConfigBuilder {
+    /// Build the OAuthConfig and [validate](OAuthConfig::validate) it
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::Config`] if the client ID, redirect URI or
+    /// endpoints are invalid
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::OAuthConfig;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = OAuthConfig::builder().redirect_port(8080).try_build()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_build(self) -> Result<OAuthConfig> {
+        let config = self.build();
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+/// Check for the 8-4-4-4-12 hex digit UUID format
+fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// Whether a URL uses HTTPS, or HTTP on a loopback host
+fn is_secure_or_loopback(url: &Url) -> bool {
+    match url.scheme() {
+        "https" => true,
+        "http" => match url.host() {
+            Some(Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+            Some(Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// Read an environment variable, treating empty values as unset
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+fn read_config_file(path: &Path) -> Result<ConfigFile> {
+    let contents = std::fs::read_to_string(path).map_err(|e| {
+        AnthropicAuthError::InvalidConfig(format!(
+            "Failed to read config file {}: {}",
+            path.display(),
+            e
+        ))
+    })?;
+    parse_config_file(path, &contents)
+}
+
+fn parse_config_file(path: &Path, contents: &str) -> Result<ConfigFile> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        AnthropicAuthError::InvalidConfig(format!(
+            "Failed to parse config file {}: {}",
+            path.display(),
+            e
+        ))
+    };
+
+    let is_toml = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"));
+    if is_toml {
+        #[cfg(feature = "config-toml")]
+        {
+            return toml::from_str(contents).map_err(|e| invalid(&e));
+        }
+        #[cfg(not(feature = "config-toml"))]
+        {
+            return Err(invalid(
+                &"TOML config files require the `config-toml` feature",
+            ));
+        }
+    }
+
+    serde_json::from_str(contents).map_err(|e| invalid(&e))
+}
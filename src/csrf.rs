@@ -0,0 +1,191 @@
+use serde::{Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+
+use crate::{AnthropicAuthError, Result};
+
+/// Default minimum estimated entropy of a state token, in bits
+pub const DEFAULT_MIN_STATE_ENTROPY: u32 = 128;
+
+/// Compare a received state token with the expected one in constant time
+pub(crate) fn states_match(received: &str, expected: &str) -> bool {
+    received.as_bytes().ct_eq(expected.as_bytes()).into()
+}
+
+/// Estimate the entropy of a state token from its length and character classes
+///
+/// Assumes the characters were drawn uniformly from the classes present
+/// (digits, lowercase, uppercase, `-`/`_`, anything else), so it is an upper
+/// bound meant to catch obviously weak values like `"state"` or `"12345"`.
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+pub(crate) fn estimate_entropy_bits(state: &str) -> u32 {
+    let has = |f: fn(&char) -> bool| state.chars().any(|c| f(&c));
+    let mut alphabet = 0u32;
+    if has(char::is_ascii_digit) {
+        alphabet += 10;
+    }
+    if has(char::is_ascii_lowercase) {
+        alphabet += 26;
+    }
+    if has(char::is_ascii_uppercase) {
+        alphabet += 26;
+    }
+    if has(|c| *c == '-' || *c == '_') {
+        alphabet += 2;
+    }
+    if has(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_') {
+        alphabet += 32;
+    }
+    if alphabet < 2 {
+        return 0;
+    }
+
+    (state.chars().count() as f64 * f64::from(alphabet).log2()) as u32
+}
+
+/// Reject state tokens with less than `min_bits` of estimated entropy
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+pub(crate) fn check_state_entropy(state: &str, min_bits: u32) -> Result<()> {
+    let bits = estimate_entropy_bits(state);
+    if bits < min_bits {
+        return Err(AnthropicAuthError::OAuth(format!(
+            "State token is too weak: about {} bits of entropy, at least {} required",
+            bits, min_bits
+        )));
+    }
+    Ok(())
+}
+
+/// CSRF state token of an authorization flow
+///
+/// Validated on construction (non-empty, no whitespace, control characters or
+/// `#`, which separates code and state in pasted responses). A distinct type from
+/// [`PkceVerifier`](crate::PkceVerifier), so the two can't be swapped in an
+/// [`ExchangeRequest`](crate::ExchangeRequest). Comparisons with `==` run in constant time.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::CsrfState;
+///
+/// let state: CsrfState = "FgE6g_6khGKFFhXAw3tULPM00CPaqgE3Cq6id79Surg".parse()?;
+/// assert_eq!(state, "FgE6g_6khGKFFhXAw3tULPM00CPaqgE3Cq6id79Surg");
+/// assert!("code#state".parse::<CsrfState>().is_err());
+/// # Ok::<(), anthropic_auth::AnthropicAuthError>(())
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CsrfState(String);
+
+impl CsrfState {
+    /// Validate and wrap a state token
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::OAuth`] if the state is empty or contains
+    /// whitespace, control characters, or `#`
+    pub fn new(state: impl Into<String>) -> Result<Self> {
+        let state = state.into();
+        if state.is_empty() {
+            return Err(AnthropicAuthError::OAuth(
+                "State token is empty".to_string(),
+            ));
+        }
+        if state
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '#')
+        {
+            return Err(AnthropicAuthError::OAuth(
+                "State token contains invalid characters".to_string(),
+            ));
+        }
+        Ok(Self(state))
+    }
+
+    /// Create a state token from 32 random bytes (43 base64url characters)
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Self {
+        use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        use base64::Engine;
+
+        Self(URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    /// Get the state as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for CsrfState {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for CsrfState {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for CsrfState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("CsrfState").field(&self.0).finish()
+    }
+}
+
+impl std::fmt::Display for CsrfState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for CsrfState {
+    type Err = AnthropicAuthError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for CsrfState {
+    type Error = AnthropicAuthError;
+
+    fn try_from(state: String) -> Result<Self> {
+        Self::new(state)
+    }
+}
+
+impl From<CsrfState> for String {
+    fn from(state: CsrfState) -> Self {
+        state.0
+    }
+}
+
+impl PartialEq for CsrfState {
+    fn eq(&self, other: &Self) -> bool {
+        states_match(&self.0, &other.0)
+    }
+}
+
+impl Eq for CsrfState {}
+
+impl PartialEq<str> for CsrfState {
+    fn eq(&self, other: &str) -> bool {
+        states_match(&self.0, other)
+    }
+}
+
+impl PartialEq<&str> for CsrfState {
+    fn eq(&self, other: &&str) -> bool {
+        states_match(&self.0, other)
+    }
+}
+
+impl std::hash::Hash for CsrfState {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
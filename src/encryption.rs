@@ -0,0 +1,72 @@
+use crate::{Result, TokenSet};
+
+/// Hook for encrypting credentials before they are persisted
+///
+/// Implement this to plug existing key management (a KMS, libsodium sealed boxes,
+/// the platform keychain, ...) into token persistence without writing a whole
+/// storage layer. Ciphertext is opaque to this crate; `decrypt` must reverse `encrypt`.
+///
+/// Implementations should report failures with
+/// [`AnthropicAuthError::Encryption`](crate::AnthropicAuthError::Encryption).
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{Encryptor, Result};
+///
+/// /// Toy XOR cipher - use a real AEAD in practice
+/// struct Xor(u8);
+///
+/// impl Encryptor for Xor {
+///     fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+///         Ok(plaintext.iter().map(|b| b ^ self.0).collect())
+///     }
+///
+///     fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+///         self.encrypt(ciphertext)
+///     }
+/// }
+/// ```
+pub trait Encryptor: Send + Sync {
+    /// Encrypt serialized credentials before they are written
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decrypt credentials after they are read
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Pass-through [`Encryptor`] that stores credentials as plaintext
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoEncryption;
+
+impl Encryptor for NoEncryption {
+    fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        Ok(plaintext.to_vec())
+    }
+
+    fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        Ok(ciphertext.to_vec())
+    }
+}
+
+impl TokenSet {
+    /// Serialize the token set and encrypt it for persistence
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization or encryption fails
+    pub fn to_encrypted(&self, encryptor: &dyn Encryptor) -> Result<Vec<u8>> {
+        let plaintext = serde_json::to_vec(&self.versioned())?;
+        encryptor.encrypt(&plaintext)
+    }
+
+    /// Decrypt and deserialize a token set produced by [`TokenSet::to_encrypted`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if decryption or deserialization fails
+    pub fn from_encrypted(ciphertext: &[u8], encryptor: &dyn Encryptor) -> Result<TokenSet> {
+        let plaintext = encryptor.decrypt(ciphertext)?;
+        TokenSet::from_versioned(serde_json::from_slice(&plaintext)?)
+    }
+}
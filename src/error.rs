@@ -21,22 +21,50 @@ pub enum AnthropicAuthError {
     #[error("API key creation failed: {0}")]
     ApiKeyCreation(String),
 
+    #[error("OAuth flow has expired - please start a new authorization")]
+    FlowExpired,
+
+    #[error("Login did not complete after authorization: {}", .0.reason)]
+    LoginIncomplete(Box<crate::IncompleteLogin>),
+
+    #[error("Logged in, but post-login actions failed: {}", .failures.join("; "))]
+    PostLoginFailed {
+        tokens: Box<crate::TokenSet>,
+        failures: Vec<String>,
+    },
+
     #[error("Invalid OAuth mode for this operation")]
     InvalidMode,
 
+    #[error("Operation was interrupted")]
+    Interrupted,
+
+    #[error("I/O error: {0}")]
+    Io(String),
+
+    #[cfg(feature = "reqwest")]
     #[error("Network error: {0}")]
     Network(#[from] reqwest::Error),
 
+    #[error("Network error: {0}")]
+    Transport(String),
+
     #[error("HTTP error: {status}")]
     Http { status: u16, body: String },
 
+    #[error("Rate limited - retry after {}s", .retry_after.as_secs())]
+    RateLimited {
+        retry_after: std::time::Duration,
+        body: String,
+    },
+
     #[error("OAuth error: {0}")]
     OAuth(String),
 
     #[error("URL parse error: {0}")]
     UrlParse(#[from] url::ParseError),
 
-    #[cfg(feature = "callback-server")]
+    #[cfg(any(feature = "callback-server", feature = "callback-server-blocking"))]
     #[error("Callback server error: {0}")]
     CallbackServer(String),
 
@@ -44,15 +72,145 @@ pub enum AnthropicAuthError {
     #[error("Failed to open browser: {0}")]
     BrowserLaunch(String),
 
+    #[error("Port {port} is not available for the OAuth callback: {reason}")]
+    PortUnavailable { port: u16, reason: String },
+
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
     #[error("Invalid configuration: {0}")]
     InvalidConfig(String),
 
+    #[error("Invalid configuration: {0}")]
+    Config(#[from] ConfigError),
+
+    #[error("Storage error: {0}")]
+    Storage(String),
+
+    #[error("Credential storage is locked: {0} - unlock it and try again")]
+    StorageLocked(String),
+
+    #[error("Encryption error: {0}")]
+    Encryption(String),
+
+    #[error("Invalid token format: {0}")]
+    InvalidTokenFormat(String),
+
     #[error("Base64 decode error: {0}")]
     Base64Decode(#[from] base64::DecodeError),
 }
 
+/// What a program (or its user) can do about an [`AnthropicAuthError`]
+///
+/// Returned by [`AnthropicAuthError::recovery`], so callers can branch on the
+/// kind of failure instead of parsing error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RecoveryAction {
+    /// The failure is transient; try the same operation again
+    Retry {
+        /// How long to wait first, if the server said so
+        after: Option<std::time::Duration>,
+    },
+    /// The tokens, code or flow are no longer usable; the user must log in again
+    Reauthenticate,
+    /// The server couldn't be reached; check the connection or proxy settings
+    ///
+    /// Also transient: the same operation may succeed once the network is back.
+    CheckNetwork,
+    /// Retrying won't help; the configuration or the integration is wrong
+    ReportBug,
+}
+
+impl AnthropicAuthError {
+    /// Classify the error into what to do about it
+    ///
+    /// This is the crate's only classification of errors: retries, token managers
+    /// and audit records all decide through it. A [`PostLoginFailed`](Self::PostLoginFailed)
+    /// error already carries the tokens, so it is not retried.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{OAuthClient, OAuthConfig, RecoveryAction};
+    ///
+    /// # fn example(client: &OAuthClient, refresh_token: &str) {
+    /// match client.refresh_token(refresh_token) {
+    ///     Ok(tokens) => println!("Refreshed, expires in {:?}", tokens.expires_in()),
+    ///     Err(e) => match e.recovery() {
+    ///         RecoveryAction::Retry { after } => println!("Try again in {:?}", after),
+    ///         RecoveryAction::Reauthenticate => println!("Please log in again"),
+    ///         RecoveryAction::CheckNetwork => println!("Check your internet connection"),
+    ///         _ => eprintln!("Unexpected error: {}", e),
+    ///     },
+    /// }
+    /// # }
+    /// ```
+    pub fn recovery(&self) -> RecoveryAction {
+        match self {
+            Self::RateLimited { retry_after, .. } => RecoveryAction::Retry {
+                after: Some(*retry_after),
+            },
+            Self::Http { status, .. } => match status {
+                400 | 401 | 403 => RecoveryAction::Reauthenticate,
+                408 | 500..=599 => RecoveryAction::Retry { after: None },
+                _ => RecoveryAction::ReportBug,
+            },
+            #[cfg(feature = "reqwest")]
+            Self::Network(_) => RecoveryAction::CheckNetwork,
+            Self::Transport(_) => RecoveryAction::CheckNetwork,
+            Self::InvalidAuthorizationCode
+            | Self::TokenExchange(_)
+            | Self::TokenRefresh(_)
+            | Self::TokenExpired
+            | Self::FlowExpired
+            | Self::OAuth(_) => RecoveryAction::Reauthenticate,
+            Self::Interrupted
+            | Self::LoginIncomplete(_)
+            | Self::PortUnavailable { .. }
+            | Self::StorageLocked(_) => RecoveryAction::Retry { after: None },
+            #[cfg(any(feature = "callback-server", feature = "callback-server-blocking"))]
+            Self::CallbackServer(_) => RecoveryAction::Retry { after: None },
+            _ => RecoveryAction::ReportBug,
+        }
+    }
+
+    /// Check whether trying the same operation again may succeed
+    ///
+    /// True for [`RecoveryAction::Retry`] and [`RecoveryAction::CheckNetwork`].
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.recovery(),
+            RecoveryAction::Retry { .. } | RecoveryAction::CheckNetwork
+        )
+    }
+
+    /// Check whether the user must log in again
+    pub fn requires_reauth(&self) -> bool {
+        self.recovery() == RecoveryAction::Reauthenticate
+    }
+}
+
+/// Problem found by [`OAuthConfig::validate`](crate::OAuthConfig::validate)
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigError {
+    #[error("client ID '{0}' is not a UUID")]
+    InvalidClientId(String),
+
+    #[error("redirect URI '{uri}' is invalid: {reason}")]
+    InvalidRedirectUri { uri: String, reason: String },
+
+    #[error("{name} '{url}' is invalid: {reason}")]
+    InvalidEndpoint {
+        name: &'static str,
+        url: String,
+        reason: String,
+    },
+
+    #[error("{name} '{url}' must use HTTPS")]
+    InsecureEndpoint { name: &'static str, url: String },
+}
+
 /// Result type alias for Anthropic authentication operations
 pub type Result<T> = std::result::Result<T, AnthropicAuthError>;
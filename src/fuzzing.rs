@@ -0,0 +1,45 @@
+//! Entry points for fuzzing the crate's parsers
+//!
+//! Enabled with the `fuzzing` feature and used by the cargo-fuzz targets in
+//! `fuzz/`. Each function runs the same parsing code as the real flow on
+//! untrusted input (pasted responses, callback queries, token endpoint bodies).
+//! Not part of the stable API.
+
+use axum::extract::Query;
+use axum::http::Uri;
+
+use crate::callback::CallbackQuery;
+use crate::types::TokenResponse;
+use crate::{CallbackData, CsrfState, ProtocolProfile, Result, TokenSet};
+
+/// Parse a pasted `code#state` response and check it against `expected_state`
+pub fn parse_code_and_state(input: &str, expected_state: &str) -> Result<CallbackData> {
+    let expected_state = CsrfState::new(expected_state)?;
+    let (code, state) = crate::client::shared::parse_code_and_state(
+        input,
+        &expected_state,
+        ProtocolProfile::AnthropicV1,
+    )?;
+    Ok(CallbackData { code, state })
+}
+
+/// Parse the query string of a callback request like the callback route does
+///
+/// Returns `(code, state, error)`, or `None` if the query is rejected.
+pub fn parse_callback_query(
+    query: &str,
+) -> Option<(Option<String>, Option<String>, Option<String>)> {
+    let uri: Uri = format!("/callback?{}", query).parse().ok()?;
+    let Query(query) = Query::<CallbackQuery>::try_from_uri(&uri).ok()?;
+    Some((query.code, query.state, query.error))
+}
+
+/// Deserialize and validate a token endpoint response body
+pub fn parse_token_response(body: &[u8]) -> Result<TokenSet> {
+    let response: TokenResponse = serde_json::from_slice(body)?;
+    let tokens = TokenSet::from(response);
+    tokens
+        .validate()
+        .map_err(|e| crate::AnthropicAuthError::OAuth(format!("Invalid token response: {}", e)))?;
+    Ok(tokens)
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+type WaitingFn = Arc<dyn Fn(&WaitingEvent) -> bool + Send + Sync>;
+
+/// Periodic "still waiting" event while the user hasn't authorized yet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitingEvent {
+    /// How long the wait has been going on
+    pub elapsed: Duration,
+    /// How long until the wait gives up, if it has a limit
+    pub remaining: Option<Duration>,
+}
+
+/// Keeps waiting for a slow authorization, reporting progress instead of timing out
+///
+/// Users may take a long time to authorize (finding their password, passing SSO,
+/// switching devices). Waiting with a keep-alive emits a [`WaitingEvent`] every
+/// `interval` and keeps the callback server or prompt alive until the callback
+/// arrives, the handler returns `false`, or the optional
+/// [`max_wait`](Self::max_wait) is reached. Without a handler, the events are
+/// printed to stderr.
+///
+/// Used by `BlockingCallbackServer::wait_keep_alive`,
+/// `CallbackReceiver::recv_keep_alive`, and `OAuthClient::login_keep_alive`.
+/// For persisted flows, see [`PendingFlow::touch`](crate::PendingFlow::touch).
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::KeepAlive;
+/// use std::time::Duration;
+///
+/// let keep_alive = KeepAlive::new(Duration::from_secs(30))
+///     .max_wait(Duration::from_secs(3600))
+///     .on_waiting(|event| {
+///         println!("Still waiting for you to sign in ({}s)", event.elapsed.as_secs());
+///         true // keep waiting
+///     });
+/// ```
+#[derive(Clone)]
+pub struct KeepAlive {
+    interval: Duration,
+    max_wait: Option<Duration>,
+    on_waiting: Option<WaitingFn>,
+}
+
+impl fmt::Debug for KeepAlive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeepAlive")
+            .field("interval", &self.interval)
+            .field("max_wait", &self.max_wait)
+            .field("on_waiting", &self.on_waiting.as_ref().map(|_| ".."))
+            .finish()
+    }
+}
+
+impl KeepAlive {
+    /// Emit a waiting event every `interval`, without a time limit
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            max_wait: None,
+            on_waiting: None,
+        }
+    }
+
+    /// Give up with [`AnthropicAuthError::FlowExpired`](crate::AnthropicAuthError::FlowExpired)
+    /// after waiting this long in total
+    pub fn max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    /// Handle waiting events; return `false` to stop waiting
+    pub fn on_waiting<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&WaitingEvent) -> bool + Send + Sync + 'static,
+    {
+        self.on_waiting = Some(Arc::new(handler));
+        self
+    }
+
+    /// Get the time between waiting events
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Get the overall time limit, if any
+    pub fn max_wait_limit(&self) -> Option<Duration> {
+        self.max_wait
+    }
+
+    /// Get when a wait started at `started` gives up, if ever
+    #[cfg(any(
+        feature = "blocking",
+        feature = "blocking-ureq",
+        feature = "callback-server",
+        feature = "callback-server-blocking"
+    ))]
+    pub(crate) fn deadline(&self, started: std::time::Instant) -> Option<std::time::Instant> {
+        self.max_wait.map(|max_wait| started + max_wait)
+    }
+
+    /// Emit a waiting event, returning whether to keep waiting
+    #[cfg(any(
+        feature = "blocking",
+        feature = "blocking-ureq",
+        feature = "callback-server",
+        feature = "callback-server-blocking"
+    ))]
+    pub(crate) fn report(&self, started: std::time::Instant) -> bool {
+        let elapsed = started.elapsed();
+        let event = WaitingEvent {
+            elapsed,
+            remaining: self
+                .max_wait
+                .map(|max_wait| max_wait.saturating_sub(elapsed)),
+        };
+
+        match &self.on_waiting {
+            Some(handler) => handler(&event),
+            None => {
+                eprintln!(
+                    "Still waiting for authorization ({}m {}s elapsed)...",
+                    elapsed.as_secs() / 60,
+                    elapsed.as_secs() % 60
+                );
+                true
+            }
+        }
+    }
+}
@@ -8,14 +8,33 @@
 //! ## Features
 //!
 //! - **Sync API** (default): Blocking operations, no async runtime required
+//!   (`blocking-ureq` swaps reqwest for ureq so no tokio is pulled in at all; disable the
+//!   default features with it, otherwise the default `blocking` still compiles reqwest)
 //! - **Async API** (optional): Runtime-agnostic async operations
 //! - **PKCE Support**: Secure PKCE (SHA-256) authentication flow with separate CSRF state tokens
 //! - **Two OAuth Modes**: Max (subscription) and Console (API key creation)
-//! - **Configurable**: Custom client IDs, redirect URIs
+//! - **Configurable**: Custom client IDs, redirect URIs, scopes, and endpoints
+//! - **Profiles**: Named per-environment overrides (e.g. staging vs prod)
 //! - **Browser Integration**: Auto-open browser for authorization (default)
-//! - **Callback Server**: Local server for automatic callback handling (optional, requires tokio)
-//! - **API Key Creation**: Create API keys via Console OAuth
+//! - **Terminal Login**: Interactive prompt with a QR code for SSH sessions (optional)
+//! - **One-Call Login**: `OAuthClient::login` with browser, print-only, clipboard, or custom URL delivery
+//! - **Callback Server**: Local server for automatic callback handling, or a router to mount
+//!   into an existing axum/tower server (optional, requires tokio)
+//! - **Claude Code Interop**: Reuse and update the login of Claude Code (`compat::claude_code`)
+//! - **auth.json Interop**: Share logins with OpenCode and other agent tools (`compat::auth_json`)
+//! - **Blocking Callback Server**: Callback capture on `std::net` for sync tools, without tokio (optional)
+//! - **HTTPS Callback**: Serve the callback over TLS with a provided or self-signed certificate (optional)
+//! - **Token Manager**: Shared token holder with on-demand and background auto-refresh (optional, requires tokio)
+//! - **Blocking Token Manager**: Thread-safe, single-flight refreshing token holder without tokio (optional)
+//! - **Token Watcher**: Background refresh for long-lived streaming connections (optional, requires tokio)
+//! - **Global Token Cache**: Process-wide, profile-keyed token cache (optional)
+//! - **API Key Creation**: Create API keys via Console OAuth, one at a time or in bulk
+//! - **Resumable Flows**: Persist in-progress flows across requests or restarts
+//! - **Token Storage**: `TokenStore` trait and a multi-process-safe file store (optional)
 //! - **Token Validation**: Built-in validation for tokens and parameters
+//! - **Token Inspection**: Decode JWT access token claims (subject, org, scopes, expiry)
+//! - **Messages API Credentials**: Bearer and beta headers for Max-mode tokens (`sdk`)
+//! - **Capability Discovery**: `capabilities()` reports which features were compiled in
 //!
 //! ## Choosing Between Sync and Async
 //!
@@ -25,7 +44,7 @@
 //! ## Quick Start (Sync API)
 //!
 //! ```no_run
-//! use anthropic_auth::{OAuthClient, OAuthConfig, OAuthMode};
+//! use anthropic_auth::{ExchangeRequest, OAuthClient, OAuthConfig, OAuthMode};
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let client = OAuthClient::new(OAuthConfig::default())?;
@@ -36,7 +55,7 @@
 //!     let response = "code123#state456";
 //!     
 //!     // Library automatically parses and validates the state
-//!     let tokens = client.exchange_code(response, &flow.state, &flow.verifier)?;
+//!     let tokens = client.exchange(&ExchangeRequest::new(response, &flow))?;
 //!     println!("Got tokens!");
 //!     Ok(())
 //! }
@@ -47,7 +66,7 @@
 //! ```no_run
 //! # #[cfg(feature = "async")]
 //! # {
-//! use anthropic_auth::{AsyncOAuthClient, OAuthConfig, OAuthMode};
+//! use anthropic_auth::{AsyncOAuthClient, ExchangeRequest, OAuthConfig, OAuthMode};
 //!
 //! # #[tokio::main]
 //! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -59,37 +78,214 @@
 //! let response = "code123#state456";
 //!
 //! // Library automatically parses and validates the state
-//! let tokens = client.exchange_code(response, &flow.state, &flow.verifier).await?;
+//! let tokens = client.exchange(&ExchangeRequest::new(response, &flow)).await?;
 //! println!("Got tokens!");
 //! # Ok(())
 //! # }
 //! # }
 //! ```
 
+mod audit;
+mod capabilities;
+mod claims;
+mod clock;
+mod config;
+mod csrf;
+mod encryption;
 mod error;
+mod keepalive;
+mod notify;
+mod pending;
+mod pkce;
+mod preflight;
+mod profile;
+mod progress;
+mod store;
 mod types;
 
-#[cfg(any(feature = "blocking", feature = "async"))]
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
 mod client;
 
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+mod login;
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+mod actions;
+
 #[cfg(feature = "browser")]
 mod browser;
 
+#[cfg(any(feature = "callback-server", feature = "callback-server-blocking"))]
+mod callback;
+
 #[cfg(feature = "callback-server")]
 mod server;
 
+#[cfg(feature = "callback-server-blocking")]
+mod server_blocking;
+
+#[cfg(feature = "callback-server-tls")]
+mod tls;
+
+#[cfg(feature = "global-cache")]
+mod cache;
+
+#[cfg(feature = "token-watcher")]
+mod watcher;
+
+#[cfg(feature = "token-manager")]
+mod accounts;
+#[cfg(feature = "token-manager")]
+mod manager;
+
+#[cfg(all(
+    feature = "token-manager-blocking",
+    any(feature = "blocking", feature = "blocking-ureq")
+))]
+mod manager_blocking;
+
+#[cfg(all(
+    feature = "token-manager-blocking",
+    not(any(feature = "blocking", feature = "blocking-ureq"))
+))]
+compile_error!("the `token-manager-blocking` feature requires `blocking` or `blocking-ureq`");
+
+#[cfg(feature = "provisioning")]
+mod provision;
+
+#[cfg(feature = "smoke")]
+mod smoke;
+
+#[cfg(all(
+    feature = "terminal",
+    any(feature = "blocking", feature = "blocking-ureq")
+))]
+mod terminal;
+
+#[cfg(all(
+    feature = "terminal",
+    not(any(feature = "blocking", feature = "blocking-ureq"))
+))]
+compile_error!("the `terminal` feature requires `blocking` or `blocking-ureq`");
+
+pub mod compat;
+pub mod sdk;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing;
+
 // Public API exports
-pub use error::{AnthropicAuthError, Result};
-pub use types::{OAuthConfig, OAuthConfigBuilder, OAuthFlow, OAuthMode, TokenSet};
+pub use audit::{AuditEvent, AuditRecord, AuditSink, JsonLinesAuditSink};
+pub use capabilities::{capabilities, Capabilities};
+pub use claims::{inspect_token, TokenClaims};
+pub use clock::{Clock, FixedClock, SystemClock, DEFAULT_EXPIRY_BUFFER};
+pub use config::{
+    ENV_API_KEY_URL, ENV_AUTHORIZE_URL, ENV_CLIENT_ID, ENV_PROXY, ENV_REDIRECT_URI, ENV_SCOPE,
+    ENV_TOKEN_URL,
+};
+pub use csrf::{CsrfState, DEFAULT_MIN_STATE_ENTROPY};
+pub use encryption::{Encryptor, NoEncryption};
+pub use error::{AnthropicAuthError, ConfigError, RecoveryAction, Result};
+pub use keepalive::{KeepAlive, WaitingEvent};
+pub use notify::{
+    LifecycleEvent, WebhookNotifier, WEBHOOK_SIGNATURE_HEADER, WEBHOOK_TIMESTAMP_HEADER,
+};
+pub use pending::{PendingFlow, DEFAULT_FLOW_TTL};
+pub use pkce::PkceVerifier;
+pub use preflight::check_port_available;
+pub use profile::{ProfileOverrides, Profiles, DEFAULT_PROFILE};
+pub use progress::{AuthEvent, AuthEventHook};
+pub use store::{
+    retry_after_unlock, ApiKeyRecord, MemoryTokenStore, SessionFallbackStore, TokenStore,
+};
+pub use types::{
+    ApiKeyOptions, AuthState, CallbackData, Endpoints, ExchangeOptions, ExchangeRequest, FlowRng,
+    IncompleteLogin, OAuthConfig, OAuthConfigBuilder, OAuthFlow, OAuthMode, Organization,
+    ProtocolProfile, RedirectStrategy, TokenFingerprint, TokenSet, TokensUpdatedHook,
+};
 
-#[cfg(feature = "blocking")]
-pub use client::OAuthClient;
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+pub use client::{InterruptHandle, OAuthClient};
 
 #[cfg(feature = "async")]
 pub use client::AsyncOAuthClient;
 
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+pub use client::{AuthorizationUrlBuilder, HttpExchange};
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+pub use login::UrlDelivery;
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+pub use login::CreatedApiKey;
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+pub use actions::{PostLoginAction, PostLoginActions};
+
 #[cfg(feature = "browser")]
-pub use browser::open_browser;
+pub use browser::{open_browser, BrowserOpener, OpenOptions};
+
+#[cfg(feature = "audit-tracing")]
+pub use audit::TracingAuditSink;
+
+#[cfg(any(feature = "callback-server", feature = "callback-server-blocking"))]
+pub use callback::{CallbackServerConfig, DEFAULT_CALLBACK_PATH};
 
 #[cfg(feature = "callback-server")]
-pub use server::{run_callback_server, CallbackData};
+pub use server::{
+    callback_router, callback_router_at, callback_service, run_callback_server,
+    run_callback_server_with, CallbackReceiver, CallbackRegistry,
+};
+
+#[cfg(feature = "callback-server-blocking")]
+pub use server_blocking::{
+    run_callback_server_blocking, run_callback_server_blocking_with, BlockingCallbackServer,
+};
+
+#[cfg(feature = "file-store")]
+pub use store::FileTokenStore;
+
+#[cfg(feature = "keyring-store")]
+pub use store::KeyringTokenStore;
+
+#[cfg(all(feature = "web-storage", target_arch = "wasm32"))]
+pub use store::{WebStorage, WebStorageTokenStore};
+
+#[cfg(feature = "callback-server-tls")]
+pub use tls::{run_callback_server_tls, TlsCertificate};
+
+#[cfg(feature = "global-cache")]
+pub use cache::{
+    cache_token, cached_profiles, cached_token, clear_token_cache, current_token,
+    current_token_with, evict_token,
+};
+
+#[cfg(feature = "token-watcher")]
+pub use watcher::{TokenWatcher, DEFAULT_REFRESH_AHEAD};
+
+#[cfg(feature = "token-manager")]
+pub use accounts::{AccountManager, AccountRefresh};
+#[cfg(feature = "token-manager")]
+pub use manager::{AutoRefreshHandle, TokenEvent, TokenManager};
+
+#[cfg(all(
+    feature = "token-manager-blocking",
+    any(feature = "blocking", feature = "blocking-ureq")
+))]
+pub use manager_blocking::BlockingTokenManager;
+
+#[cfg(feature = "provisioning")]
+pub use provision::{KeyProvisioner, KeyRequest, KeyResult};
+
+#[cfg(all(
+    feature = "terminal",
+    any(feature = "blocking", feature = "blocking-ureq")
+))]
+pub use terminal::interactive_login;
+
+#[cfg(feature = "smoke")]
+pub use smoke::{run_smoke_tests, SmokeCheck, SmokeReport};
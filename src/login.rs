@@ -0,0 +1,549 @@
+use std::fmt;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use crate::Result;
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+use crate::{
+    AnthropicAuthError, ApiKeyOptions, ApiKeyRecord, AuthEvent, CallbackData, CsrfState,
+    ExchangeRequest, IncompleteLogin, KeepAlive, OAuthClient, OAuthFlow, OAuthMode, Organization,
+    TokenSet,
+};
+#[cfg(all(
+    feature = "callback-server-blocking",
+    any(feature = "blocking", feature = "blocking-ureq")
+))]
+use crate::{BlockingCallbackServer, CallbackServerConfig, RedirectStrategy};
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+use std::sync::mpsc::RecvTimeoutError;
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+use std::time::{Duration, Instant};
+
+type DeliverFn = Arc<dyn Fn(&str) -> Result<()> + Send + Sync>;
+
+/// How the high-level login flow hands the authorization URL to the user
+///
+/// The URL is always printed to stderr as well, so the user can still copy it
+/// when opening the browser or the clipboard fails.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::UrlDelivery;
+///
+/// // Show the URL in a GUI dialog instead of the terminal
+/// let delivery = UrlDelivery::custom(|url| {
+///     println!("Please visit {}", url);
+///     Ok(())
+/// });
+/// ```
+#[derive(Clone)]
+pub enum UrlDelivery {
+    /// Open the URL in the default browser (desktop)
+    ///
+    /// **Note:** Only available when the `browser` feature is enabled.
+    #[cfg(feature = "browser")]
+    OpenBrowser,
+    /// Open the URL with a [`BrowserOpener`](crate::BrowserOpener), such as
+    /// [`OpenOptions`](crate::OpenOptions) for a specific browser or private window
+    ///
+    /// **Note:** Only available when the `browser` feature is enabled.
+    #[cfg(feature = "browser")]
+    OpenWith(Arc<dyn crate::BrowserOpener>),
+    /// Only print the URL (SSH sessions, containers)
+    PrintOnly,
+    /// Copy the URL to the system clipboard (`pbcopy`, `clip`, `wl-copy`, `xclip` or `xsel`)
+    Clipboard,
+    /// Hand the URL to your own function (GUI apps)
+    Custom(DeliverFn),
+}
+
+impl Default for UrlDelivery {
+    /// [`UrlDelivery::OpenBrowser`] when the `browser` feature is enabled,
+    /// [`UrlDelivery::PrintOnly`] otherwise
+    fn default() -> Self {
+        #[cfg(feature = "browser")]
+        {
+            Self::OpenBrowser
+        }
+        #[cfg(not(feature = "browser"))]
+        {
+            Self::PrintOnly
+        }
+    }
+}
+
+impl fmt::Debug for UrlDelivery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "browser")]
+            Self::OpenBrowser => f.write_str("OpenBrowser"),
+            #[cfg(feature = "browser")]
+            Self::OpenWith(_) => f.write_str("OpenWith(..)"),
+            Self::PrintOnly => f.write_str("PrintOnly"),
+            Self::Clipboard => f.write_str("Clipboard"),
+            Self::Custom(_) => f.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl UrlDelivery {
+    /// Create a [`UrlDelivery::Custom`] from a closure
+    pub fn custom<F>(deliver: F) -> Self
+    where
+        F: Fn(&str) -> Result<()> + Send + Sync + 'static,
+    {
+        Self::Custom(Arc::new(deliver))
+    }
+
+    /// Create a [`UrlDelivery::OpenWith`] from an opener
+    ///
+    /// **Note:** Only available when the `browser` feature is enabled.
+    #[cfg(feature = "browser")]
+    pub fn open_with(opener: impl crate::BrowserOpener + 'static) -> Self {
+        Self::OpenWith(Arc::new(opener))
+    }
+
+    /// Deliver the authorization URL to the user
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of a [`UrlDelivery::Custom`] function. Browser and clipboard
+    /// failures fall back to printing the URL.
+    pub fn deliver(&self, url: &str) -> Result<()> {
+        self.deliver_to_user(url).map(|_| ())
+    }
+
+    /// Deliver the authorization URL, returning whether it was opened in the browser
+    pub(crate) fn deliver_to_user(&self, url: &str) -> Result<bool> {
+        match self {
+            #[cfg(feature = "browser")]
+            Self::OpenBrowser => Ok(report_opened(crate::open_browser(url).is_ok(), url)),
+            #[cfg(feature = "browser")]
+            Self::OpenWith(opener) => Ok(report_opened(opener.open(url).is_ok(), url)),
+            Self::PrintOnly => {
+                eprintln!("Visit this URL to authorize:\n\n  {}\n", url);
+                Ok(false)
+            }
+            Self::Clipboard => {
+                if copy_to_clipboard(url) {
+                    eprintln!(
+                        "Copied the authorization URL to your clipboard:\n\n  {}\n",
+                        url
+                    );
+                } else {
+                    eprintln!("Visit this URL to authorize:\n\n  {}\n", url);
+                }
+                Ok(false)
+            }
+            Self::Custom(deliver) => deliver(url).map(|()| false),
+        }
+    }
+}
+
+/// Tell the user whether the browser opened, returning `opened`
+#[cfg(feature = "browser")]
+fn report_opened(opened: bool, url: &str) -> bool {
+    if opened {
+        eprintln!(
+            "Opened your browser. If it didn't open, visit:\n\n  {}\n",
+            url
+        );
+    } else {
+        eprintln!("Visit this URL to authorize:\n\n  {}\n", url);
+    }
+    opened
+}
+
+/// Copy `text` with the first available clipboard command
+fn copy_to_clipboard(text: &str) -> bool {
+    let candidates: &[(&str, &[&str])] = if cfg!(target_os = "macos") {
+        &[("pbcopy", &[])]
+    } else if cfg!(windows) {
+        &[("clip", &[])]
+    } else {
+        &[
+            ("wl-copy", &[]),
+            ("xclip", &["-selection", "clipboard"]),
+            ("xsel", &["--clipboard", "--input"]),
+        ]
+    };
+
+    candidates.iter().any(|(program, args)| {
+        let Ok(mut child) = Command::new(program)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            return false;
+        };
+
+        let written = child
+            .stdin
+            .take()
+            .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+        child.wait().is_ok_and(|status| status.success()) && written
+    })
+}
+
+/// API key created by [`OAuthClient::create_api_key_via_oauth`]
+///
+/// **Note:** Only available when the `blocking` or `blocking-ureq` feature is enabled.
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+#[derive(Debug, Clone)]
+pub struct CreatedApiKey {
+    /// The new API key
+    pub api_key: String,
+    /// Name, workspace and fingerprint of the key, ready for
+    /// [`TokenStore::record_api_key`](crate::TokenStore::record_api_key)
+    pub record: ApiKeyRecord,
+    /// Organization the key belongs to, if reported by the server
+    pub organization: Option<Organization>,
+    /// The Console tokens the key was created with
+    pub tokens: TokenSet,
+}
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+impl OAuthClient {
+    /// Run the whole authorization flow in the terminal (blocking)
+    ///
+    /// Starts a flow, delivers the authorization URL with `delivery`, prompts on
+    /// stderr for the `code#state` shown after authorizing, and exchanges it.
+    /// With a [`RedirectStrategy::Localhost`](crate::RedirectStrategy::Localhost)
+    /// redirect and the `callback-server-blocking` feature, a local callback server
+    /// on that port captures the authorization response instead of the prompt.
+    ///
+    /// # Arguments
+    ///
+    /// * `mode` - The OAuth mode (Max for subscription, Console for API key creation)
+    /// * `delivery` - How to hand the authorization URL to the user
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the URL cannot be delivered, stdin cannot be read, or
+    /// the code exchange fails. If the exchange fails transiently (network error,
+    /// HTTP 429 or 5xx) after the code was pasted, returns
+    /// [`AnthropicAuthError::LoginIncomplete`] so the exchange can be retried with
+    /// [`resume_login`](Self::resume_login).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{OAuthClient, OAuthConfig, OAuthMode, UrlDelivery};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = OAuthClient::new(OAuthConfig::default())?;
+    ///
+    /// // Over SSH, don't try to open a browser
+    /// let tokens = client.login(OAuthMode::Max, &UrlDelivery::PrintOnly)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn login(&self, mode: OAuthMode, delivery: &UrlDelivery) -> Result<TokenSet> {
+        self.run_login(mode, delivery, None, None)
+    }
+
+    /// Run the whole authorization flow in the terminal with an overall time limit (blocking)
+    ///
+    /// Same as [`login`](Self::login), but gives up with
+    /// [`AnthropicAuthError::FlowExpired`] if no code was pasted within `timeout`.
+    /// The thread reading stdin is left behind until the next line is entered.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{AnthropicAuthError, OAuthClient, OAuthConfig, OAuthMode, UrlDelivery};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = OAuthClient::new(OAuthConfig::default())?;
+    ///
+    /// let delivery = UrlDelivery::default();
+    /// let tokens = match client.login_within(OAuthMode::Max, &delivery, Duration::from_secs(300)) {
+    ///     // Already authorized - retry the exchange instead of starting over
+    ///     Err(AnthropicAuthError::LoginIncomplete(incomplete)) => client.resume_login(&incomplete)?,
+    ///     result => result?,
+    /// };
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn login_within(
+        &self,
+        mode: OAuthMode,
+        delivery: &UrlDelivery,
+        timeout: Duration,
+    ) -> Result<TokenSet> {
+        self.run_login(mode, delivery, Some(Instant::now() + timeout), None)
+    }
+
+    /// Run the whole authorization flow in the terminal, emitting waiting events
+    /// while the user takes their time (blocking)
+    ///
+    /// Same as [`login`](Self::login), but reports on the wait every
+    /// [`KeepAlive::interval`] and gives up with [`AnthropicAuthError::FlowExpired`]
+    /// once the keep-alive's `max_wait` passed or its handler returns `false`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{KeepAlive, OAuthClient, OAuthConfig, OAuthMode, UrlDelivery};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = OAuthClient::new(OAuthConfig::default())?;
+    ///
+    /// // SSO can take a while; remind the user every two minutes, for up to an hour
+    /// let keep_alive = KeepAlive::new(Duration::from_secs(120)).max_wait(Duration::from_secs(3600));
+    /// let tokens = client.login_keep_alive(OAuthMode::Max, &UrlDelivery::default(), &keep_alive)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn login_keep_alive(
+        &self,
+        mode: OAuthMode,
+        delivery: &UrlDelivery,
+        keep_alive: &KeepAlive,
+    ) -> Result<TokenSet> {
+        self.run_login(mode, delivery, None, Some(keep_alive))
+    }
+
+    /// Log in with Console mode and create an API key in one call (blocking)
+    ///
+    /// Runs the Console flow like [`login`](Self::login) (prompting for the
+    /// `code#state`, or capturing the callback on a localhost redirect), then
+    /// creates an API key with `options`.
+    ///
+    /// # Arguments
+    ///
+    /// * `delivery` - How to hand the authorization URL to the user
+    /// * `options` - Key name and workspace
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the login fails (see [`login`](Self::login)) or the API
+    /// key cannot be created. In the latter case the Console tokens are lost; use
+    /// [`login`](Self::login) and [`create_api_key_with`](Self::create_api_key_with)
+    /// to retry the key creation separately.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{ApiKeyOptions, OAuthClient, OAuthConfig, UrlDelivery};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = OAuthClient::new(OAuthConfig::builder().redirect_port(1455).build())?;
+    /// let options = ApiKeyOptions {
+    ///     name: Some("ci-runner".to_string()),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let created = client.create_api_key_via_oauth(&UrlDelivery::default(), &options)?;
+    /// if let Some(organization) = &created.organization {
+    ///     println!("Created {} in {}", created.record.fingerprint, organization.uuid);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn create_api_key_via_oauth(
+        &self,
+        delivery: &UrlDelivery,
+        options: &ApiKeyOptions,
+    ) -> Result<CreatedApiKey> {
+        let tokens = self.login(OAuthMode::Console, delivery)?;
+        let api_key = self.create_api_key_with(&tokens.access_token, options)?;
+
+        Ok(CreatedApiKey {
+            record: ApiKeyRecord::new(&api_key, options),
+            organization: tokens.organization.clone(),
+            api_key,
+            tokens,
+        })
+    }
+
+    /// Retry the code exchange of a login that failed after authorization (blocking)
+    ///
+    /// The exchange repeats the flow's redirect URI, so the client doesn't need the
+    /// redirect configuration the login was started with.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the code exchange fails again
+    pub fn resume_login(&self, incomplete: &IncompleteLogin) -> Result<TokenSet> {
+        let state = CsrfState::new(incomplete.callback.state.as_str())?;
+        self.exchange(&ExchangeRequest {
+            mode: Some(&incomplete.mode),
+            redirect_uri: incomplete.redirect_uri.as_deref(),
+            ..ExchangeRequest::from_parts(&incomplete.callback.code, &state, &incomplete.verifier)
+        })
+    }
+
+    fn run_login(
+        &self,
+        mode: OAuthMode,
+        delivery: &UrlDelivery,
+        deadline: Option<Instant>,
+        keep_alive: Option<&KeepAlive>,
+    ) -> Result<TokenSet> {
+        let result = match self.config().redirect {
+            #[cfg(feature = "callback-server-blocking")]
+            RedirectStrategy::Localhost(port) => {
+                self.run_loopback_login(mode, delivery, port, deadline, keep_alive)
+            }
+            _ => self.run_paste_login(mode, delivery, deadline, keep_alive),
+        };
+        self.config().emit(match &result {
+            Ok(_) => AuthEvent::TokensReceived,
+            Err(error) => AuthEvent::Error(error.to_string()),
+        });
+        result
+    }
+
+    /// Run the flow with a prompt for the `code#state` shown after authorizing
+    fn run_paste_login(
+        &self,
+        mode: OAuthMode,
+        delivery: &UrlDelivery,
+        deadline: Option<Instant>,
+        keep_alive: Option<&KeepAlive>,
+    ) -> Result<TokenSet> {
+        let flow = self.start_flow(mode)?;
+        self.deliver_flow(&flow, delivery)?;
+
+        eprint!("Paste the authorization code: ");
+        let _ = std::io::stderr().flush();
+
+        let response = if deadline.is_none() && keep_alive.is_none() {
+            read_line()?
+        } else {
+            read_line_until(deadline, keep_alive)?
+        };
+        let response = response.trim();
+        self.config().emit(AuthEvent::CallbackReceived);
+
+        self.config().emit(AuthEvent::ExchangingCode);
+        self.exchange(&ExchangeRequest::new(response, &flow))
+            .map_err(|error| {
+                let (code, state) = response
+                    .split_once('#')
+                    .unwrap_or((response, flow.state.as_str()));
+                incomplete_login(error, code, state, &flow)
+            })
+    }
+
+    /// Run the flow with a local callback server on the redirect port instead of a prompt
+    #[cfg(feature = "callback-server-blocking")]
+    fn run_loopback_login(
+        &self,
+        mode: OAuthMode,
+        delivery: &UrlDelivery,
+        port: u16,
+        deadline: Option<Instant>,
+        keep_alive: Option<&KeepAlive>,
+    ) -> Result<TokenSet> {
+        // Listen before the browser is sent to the authorization page. The path
+        // stays fixed since the redirect URI must be registered for the client
+        // (see `RedirectStrategy::Localhost`).
+        let server = BlockingCallbackServer::bind(&CallbackServerConfig::with_port(port))?;
+        let flow = self.start_flow(mode)?;
+        self.deliver_flow(&flow, delivery)?;
+
+        let callback = match (deadline, keep_alive) {
+            (_, Some(keep_alive)) => server.wait_keep_alive(&flow.state, keep_alive)?,
+            (Some(deadline), None) => server.wait_timeout(
+                &flow.state,
+                deadline.saturating_duration_since(Instant::now()),
+            )?,
+            (None, None) => server.wait(&flow.state)?,
+        };
+        self.config().emit(AuthEvent::CallbackReceived);
+
+        self.config().emit(AuthEvent::ExchangingCode);
+        self.exchange_callback(&callback, &flow)
+            .map_err(|error| incomplete_login(error, &callback.code, &callback.state, &flow))
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+impl OAuthClient {
+    /// Hand the authorization URL of a started flow to the user
+    fn deliver_flow(&self, flow: &OAuthFlow, delivery: &UrlDelivery) -> Result<()> {
+        self.config().emit(AuthEvent::FlowStarted {
+            url: flow.authorization_url.clone(),
+        });
+        if delivery.deliver_to_user(&flow.authorization_url)? {
+            self.config().emit(AuthEvent::BrowserOpened);
+        }
+        self.config().emit(AuthEvent::WaitingForCallback);
+        Ok(())
+    }
+}
+
+/// Turn a transient exchange failure into [`AnthropicAuthError::LoginIncomplete`],
+/// keeping the authorization response so the exchange can be retried
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+fn incomplete_login(
+    error: AnthropicAuthError,
+    code: &str,
+    state: &str,
+    flow: &OAuthFlow,
+) -> AnthropicAuthError {
+    if !error.is_retryable() {
+        return error;
+    }
+
+    AnthropicAuthError::LoginIncomplete(Box::new(IncompleteLogin {
+        callback: CallbackData {
+            code: code.to_string(),
+            state: state.to_string(),
+        },
+        verifier: flow.verifier.clone(),
+        mode: flow.mode.clone(),
+        redirect_uri: Some(flow.redirect_uri.clone()),
+        reason: error.to_string(),
+    }))
+}
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+fn read_line() -> Result<String> {
+    let mut line = String::new();
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| AnthropicAuthError::Io(format!("Failed to read authorization code: {}", e)))?;
+    Ok(line)
+}
+
+/// Read a line from stdin on a helper thread, giving up at `deadline` and
+/// reporting on the wait with `keep_alive`
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+fn read_line_until(deadline: Option<Instant>, keep_alive: Option<&KeepAlive>) -> Result<String> {
+    let (tx, rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(read_line());
+    });
+
+    let started = Instant::now();
+    let deadline =
+        deadline.or_else(|| keep_alive.and_then(|keep_alive| keep_alive.deadline(started)));
+    loop {
+        let mut wait = keep_alive.map_or(Duration::MAX, KeepAlive::interval);
+        if let Some(deadline) = deadline {
+            wait = wait.min(deadline.saturating_duration_since(Instant::now()));
+        }
+
+        match rx.recv_timeout(wait) {
+            Ok(line) => return line,
+            Err(RecvTimeoutError::Disconnected) => return Err(AnthropicAuthError::FlowExpired),
+            Err(RecvTimeoutError::Timeout) => {}
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            return Err(AnthropicAuthError::FlowExpired);
+        }
+        if keep_alive.is_some_and(|keep_alive| !keep_alive.report(started)) {
+            return Err(AnthropicAuthError::FlowExpired);
+        }
+    }
+}
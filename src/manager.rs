@@ -0,0 +1,303 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::{
+    AnthropicAuthError, AsyncOAuthClient, AuditEvent, AuthState, LifecycleEvent, Result, TokenSet,
+};
+
+/// Delay between retries after a transient refresh failure
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Shortest time between two refreshes by the auto-refresh task, in case the
+/// server issues tokens that don't outlive the expiry buffer
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Events emitted by the background auto-refresh task
+#[derive(Debug, Clone)]
+pub enum TokenEvent {
+    /// Tokens were refreshed; persist them if you store tokens
+    Refreshed(TokenSet),
+    /// A refresh attempt failed and will be retried
+    RefreshFailed {
+        /// Description of the failure
+        error: String,
+        /// Delay until the next attempt
+        retry_in: Duration,
+    },
+    /// The tokens can't be refreshed anymore and the user must log in again
+    ///
+    /// The auto-refresh task stops after emitting this event.
+    ReauthRequired {
+        /// Why re-authentication is needed
+        reason: String,
+    },
+}
+
+/// Holds the current tokens and refreshes them on demand
+///
+/// Cloning a `TokenManager` is cheap and all clones share the same tokens, so it can
+/// be handed to every part of an application that needs an access token.
+/// Concurrent callers of [`get_valid_token`](Self::get_valid_token) share a single
+/// refresh instead of each hitting the token endpoint. To persist refreshed tokens,
+/// set [`on_tokens_updated`](crate::OAuthConfigBuilder::on_tokens_updated) on the
+/// client's config.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{AsyncOAuthClient, OAuthConfig, TokenManager};
+///
+/// # async fn example(tokens: anthropic_auth::TokenSet) -> Result<(), Box<dyn std::error::Error>> {
+/// let client = AsyncOAuthClient::new(OAuthConfig::default())?;
+/// let manager = TokenManager::with_tokens(client, tokens);
+///
+/// // Refreshes automatically when the token is about to expire
+/// let access_token = manager.get_valid_token().await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct TokenManager {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client: AsyncOAuthClient,
+    tokens: Mutex<Option<TokenSet>>,
+    /// Set when the server rejected the refresh token, until new tokens are set
+    reauth_required: AtomicBool,
+}
+
+impl TokenManager {
+    /// Create a manager without tokens (call [`set_tokens`](Self::set_tokens) after login)
+    pub fn new(client: AsyncOAuthClient) -> Self {
+        Self::from_parts(client, None)
+    }
+
+    /// Create a manager holding existing tokens
+    pub fn with_tokens(client: AsyncOAuthClient, tokens: TokenSet) -> Self {
+        Self::from_parts(client, Some(tokens))
+    }
+
+    fn from_parts(client: AsyncOAuthClient, tokens: Option<TokenSet>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                client,
+                tokens: Mutex::new(tokens),
+                reauth_required: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Get the OAuth client used for refreshing
+    pub fn client(&self) -> &AsyncOAuthClient {
+        &self.inner.client
+    }
+
+    /// Replace the managed tokens
+    pub async fn set_tokens(&self, tokens: TokenSet) {
+        *self.inner.tokens.lock().await = Some(tokens);
+        self.inner.reauth_required.store(false, Ordering::Relaxed);
+    }
+
+    /// Get a copy of the managed tokens, if any
+    pub async fn tokens(&self) -> Option<TokenSet> {
+        self.inner.tokens.lock().await.clone()
+    }
+
+    /// Remove the managed tokens (e.g. on logout)
+    ///
+    /// Records [`AuditEvent::TokensRevoked`] with the configured audit sink if
+    /// tokens were held.
+    pub async fn clear(&self) {
+        let removed = self.inner.tokens.lock().await.take();
+        self.inner.reauth_required.store(false, Ordering::Relaxed);
+        if let Some(tokens) = removed {
+            self.inner.client.config().audit(
+                AuditEvent::TokensRevoked {
+                    fingerprint: tokens.fingerprint(),
+                },
+                tokens.organization.as_ref(),
+            );
+        }
+    }
+
+    /// Get the coarse-grained authentication state, e.g. for a status indicator
+    ///
+    /// Reports [`AuthState::ReauthRequired`] once the server has rejected the
+    /// refresh token, until new tokens are set.
+    pub async fn state(&self) -> AuthState {
+        let guard = self.inner.tokens.lock().await;
+        if guard.is_some() && self.inner.reauth_required.load(Ordering::Relaxed) {
+            return AuthState::ReauthRequired;
+        }
+        AuthState::of_with(guard.as_ref(), self.inner.client.config())
+    }
+
+    /// Get a valid access token, refreshing first if it is expired or about to expire
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::TokenExpired`] if no tokens are held, or the
+    /// refresh error if refreshing fails
+    pub async fn get_valid_token(&self) -> Result<String> {
+        let mut guard = self.inner.tokens.lock().await;
+        let tokens = guard.as_ref().ok_or(AnthropicAuthError::TokenExpired)?;
+
+        if !self.inner.client.config().is_expired(tokens) {
+            return Ok(tokens.access_token.clone());
+        }
+
+        let fresh = self
+            .record_refresh(self.inner.client.refresh_token_with_fallback(tokens).await)
+            .await?;
+        let access_token = fresh.access_token.clone();
+        *guard = Some(fresh);
+        Ok(access_token)
+    }
+
+    /// Refresh the tokens now, regardless of expiry
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::TokenExpired`] if no tokens are held, or the
+    /// refresh error if refreshing fails
+    pub async fn refresh(&self) -> Result<TokenSet> {
+        let mut guard = self.inner.tokens.lock().await;
+        let tokens = guard.as_ref().ok_or(AnthropicAuthError::TokenExpired)?;
+
+        let fresh = self
+            .record_refresh(self.inner.client.refresh_token_with_fallback(tokens).await)
+            .await?;
+        *guard = Some(fresh.clone());
+        Ok(fresh)
+    }
+
+    /// Remember a rejected refresh token for [`state`](Self::state)
+    ///
+    /// The configured webhook is told the first time re-authentication is required.
+    async fn record_refresh(&self, result: Result<TokenSet>) -> Result<TokenSet> {
+        if let Err(e) = &result {
+            if e.requires_reauth() && !self.inner.reauth_required.swap(true, Ordering::Relaxed) {
+                self.inner
+                    .client
+                    .notify(&LifecycleEvent::ReauthRequired {
+                        reason: e.to_string(),
+                    })
+                    .await;
+            }
+        }
+        result
+    }
+
+    /// Refresh the tokens ahead of expiry in a background tokio task
+    ///
+    /// The task wakes up shortly before the access token expires, refreshes it, and
+    /// reports what happened as [`TokenEvent`]s. Transient failures are retried;
+    /// rejected refresh tokens (HTTP 400/401) or missing tokens end the task with
+    /// [`TokenEvent::ReauthRequired`].
+    ///
+    /// Must be called from within a tokio runtime. The task stops when the returned
+    /// handle is dropped.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{TokenEvent, TokenManager};
+    ///
+    /// # async fn example(manager: TokenManager) {
+    /// let mut auto_refresh = manager.spawn_auto_refresh();
+    /// while let Some(event) = auto_refresh.next_event().await {
+    ///     match event {
+    ///         TokenEvent::Refreshed(tokens) => println!("Refreshed, expires in {:?}", tokens.expires_in()),
+    ///         TokenEvent::RefreshFailed { error, .. } => eprintln!("Refresh failed: {}", error),
+    ///         TokenEvent::ReauthRequired { reason } => eprintln!("Please log in again: {}", reason),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn spawn_auto_refresh(&self) -> AutoRefreshHandle {
+        let (tx, events) = mpsc::unbounded_channel();
+        let manager = self.clone();
+
+        let task = tokio::spawn(async move {
+            loop {
+                let Some(tokens) = manager.tokens().await else {
+                    let _ = tx.send(TokenEvent::ReauthRequired {
+                        reason: "No tokens to refresh".to_string(),
+                    });
+                    break;
+                };
+
+                let config = manager.inner.client.config();
+                let wait = tokens
+                    .expires_in_with(config.clock(), config.clock_skew)
+                    .saturating_sub(config.expiry_buffer);
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                    // The tokens may have been replaced or refreshed while sleeping
+                    continue;
+                }
+
+                let event = match manager.refresh().await {
+                    Ok(fresh) => TokenEvent::Refreshed(fresh),
+                    Err(e) if e.requires_reauth() => TokenEvent::ReauthRequired {
+                        reason: match e {
+                            AnthropicAuthError::Http { body, .. } => body,
+                            e => e.to_string(),
+                        },
+                    },
+                    Err(e) => TokenEvent::RefreshFailed {
+                        error: e.to_string(),
+                        retry_in: RETRY_DELAY,
+                    },
+                };
+
+                let stop = matches!(event, TokenEvent::ReauthRequired { .. });
+                let delay = match event {
+                    TokenEvent::RefreshFailed { .. } => RETRY_DELAY,
+                    _ => MIN_REFRESH_INTERVAL,
+                };
+                if tx.send(event).is_err() || stop {
+                    break;
+                }
+                tokio::time::sleep(delay).await;
+            }
+        });
+
+        AutoRefreshHandle { events, task }
+    }
+}
+
+/// Handle to a background auto-refresh task started by
+/// [`TokenManager::spawn_auto_refresh`]
+///
+/// Dropping the handle stops the task.
+#[derive(Debug)]
+pub struct AutoRefreshHandle {
+    events: mpsc::UnboundedReceiver<TokenEvent>,
+    task: JoinHandle<()>,
+}
+
+impl AutoRefreshHandle {
+    /// Wait for the next event
+    ///
+    /// Returns `None` once the task has stopped.
+    pub async fn next_event(&mut self) -> Option<TokenEvent> {
+        self.events.recv().await
+    }
+
+    /// Stop the background task
+    pub fn stop(self) {
+        // Dropping aborts the task
+    }
+}
+
+impl Drop for AutoRefreshHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
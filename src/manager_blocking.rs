@@ -0,0 +1,176 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::{
+    AnthropicAuthError, AuditEvent, AuthState, LifecycleEvent, OAuthClient, Result, TokenSet,
+};
+
+/// Thread-safe holder of the current tokens that refreshes them on demand (blocking)
+///
+/// The blocking counterpart of [`TokenManager`](crate::TokenManager) for threaded
+/// servers and GUI apps without an async runtime. Cloning is cheap and all clones
+/// share the same tokens. Refreshes are single-flight: while one thread refreshes,
+/// other callers of [`get_valid_token`](Self::get_valid_token) wait and then reuse
+/// the fresh token instead of each hitting the token endpoint. To persist refreshed
+/// tokens, set [`on_tokens_updated`](crate::OAuthConfigBuilder::on_tokens_updated)
+/// on the client's config.
+///
+/// **Note:** Only available when the `token-manager-blocking` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{BlockingTokenManager, OAuthClient, OAuthConfig};
+///
+/// # fn example(tokens: anthropic_auth::TokenSet) -> Result<(), Box<dyn std::error::Error>> {
+/// let client = OAuthClient::new(OAuthConfig::default())?;
+/// let manager = BlockingTokenManager::with_tokens(client, tokens);
+///
+/// let workers: Vec<_> = (0..8)
+///     .map(|_| {
+///         let manager = manager.clone();
+///         std::thread::spawn(move || manager.get_valid_token())
+///     })
+///     .collect();
+/// for worker in workers {
+///     let access_token = worker.join().unwrap()?;
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct BlockingTokenManager {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    client: OAuthClient,
+    tokens: Mutex<Option<TokenSet>>,
+    /// Set when the server rejected the refresh token, until new tokens are set
+    reauth_required: AtomicBool,
+}
+
+impl BlockingTokenManager {
+    /// Create a manager without tokens (call [`set_tokens`](Self::set_tokens) after login)
+    pub fn new(client: OAuthClient) -> Self {
+        Self::from_parts(client, None)
+    }
+
+    /// Create a manager holding existing tokens
+    pub fn with_tokens(client: OAuthClient, tokens: TokenSet) -> Self {
+        Self::from_parts(client, Some(tokens))
+    }
+
+    fn from_parts(client: OAuthClient, tokens: Option<TokenSet>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                client,
+                tokens: Mutex::new(tokens),
+                reauth_required: AtomicBool::new(false),
+            }),
+        }
+    }
+
+    /// Get the OAuth client used for refreshing
+    pub fn client(&self) -> &OAuthClient {
+        &self.inner.client
+    }
+
+    /// Replace the managed tokens
+    pub fn set_tokens(&self, tokens: TokenSet) {
+        *self.lock() = Some(tokens);
+        self.inner.reauth_required.store(false, Ordering::Relaxed);
+    }
+
+    /// Get a copy of the managed tokens, if any
+    pub fn tokens(&self) -> Option<TokenSet> {
+        self.lock().clone()
+    }
+
+    /// Remove the managed tokens (e.g. on logout)
+    ///
+    /// Records [`AuditEvent::TokensRevoked`] with the configured audit sink if
+    /// tokens were held.
+    pub fn clear(&self) {
+        let removed = self.lock().take();
+        self.inner.reauth_required.store(false, Ordering::Relaxed);
+        if let Some(tokens) = removed {
+            self.inner.client.config().audit(
+                AuditEvent::TokensRevoked {
+                    fingerprint: tokens.fingerprint(),
+                },
+                tokens.organization.as_ref(),
+            );
+        }
+    }
+
+    /// Get the coarse-grained authentication state, e.g. for a status indicator
+    ///
+    /// Reports [`AuthState::ReauthRequired`] once the server has rejected the
+    /// refresh token, until new tokens are set.
+    pub fn state(&self) -> AuthState {
+        let guard = self.lock();
+        if guard.is_some() && self.inner.reauth_required.load(Ordering::Relaxed) {
+            return AuthState::ReauthRequired;
+        }
+        AuthState::of_with(guard.as_ref(), self.inner.client.config())
+    }
+
+    /// Get a valid access token, refreshing first if it is expired or about to expire
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::TokenExpired`] if no tokens are held, or the
+    /// refresh error if refreshing fails
+    pub fn get_valid_token(&self) -> Result<String> {
+        let mut guard = self.lock();
+        let tokens = guard.as_ref().ok_or(AnthropicAuthError::TokenExpired)?;
+
+        if !self.inner.client.config().is_expired(tokens) {
+            return Ok(tokens.access_token.clone());
+        }
+
+        // The lock is held during the refresh, so concurrent callers wait for it
+        let fresh = self.record_refresh(self.inner.client.refresh_token_with_fallback(tokens))?;
+        let access_token = fresh.access_token.clone();
+        *guard = Some(fresh);
+        Ok(access_token)
+    }
+
+    /// Refresh the tokens now, regardless of expiry
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::TokenExpired`] if no tokens are held, or the
+    /// refresh error if refreshing fails
+    pub fn refresh(&self) -> Result<TokenSet> {
+        let mut guard = self.lock();
+        let tokens = guard.as_ref().ok_or(AnthropicAuthError::TokenExpired)?;
+
+        let fresh = self.record_refresh(self.inner.client.refresh_token_with_fallback(tokens))?;
+        *guard = Some(fresh.clone());
+        Ok(fresh)
+    }
+
+    /// Remember a rejected refresh token for [`state`](Self::state)
+    ///
+    /// The configured webhook is told the first time re-authentication is required.
+    fn record_refresh(&self, result: Result<TokenSet>) -> Result<TokenSet> {
+        if let Err(e) = &result {
+            if e.requires_reauth() && !self.inner.reauth_required.swap(true, Ordering::Relaxed) {
+                self.inner.client.notify(&LifecycleEvent::ReauthRequired {
+                    reason: e.to_string(),
+                });
+            }
+        }
+        result
+    }
+
+    /// Lock the tokens, recovering from a panic in another thread
+    fn lock(&self) -> MutexGuard<'_, Option<TokenSet>> {
+        self.inner
+            .tokens
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,236 @@
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::Sha256;
+use std::fmt;
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+use crate::TokenSet;
+use crate::{Organization, TokenFingerprint};
+
+/// Header carrying the HMAC-SHA256 signature of a webhook event
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "x-anthropic-auth-signature";
+
+/// Header carrying the Unix timestamp (seconds) the signature covers
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "x-anthropic-auth-timestamp";
+
+/// Token lifecycle event reported by a [`WebhookNotifier`]
+///
+/// Events never contain tokens or API keys.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum LifecycleEvent {
+    /// An authorization code was exchanged for tokens
+    Login {
+        /// Unix timestamp (seconds) when the new access token expires
+        expires_at: u64,
+        /// Fingerprint of the new tokens
+        fingerprint: TokenFingerprint,
+        /// Organization the tokens were issued for, if reported
+        #[serde(skip_serializing_if = "Option::is_none")]
+        organization: Option<Organization>,
+    },
+    /// An access token was refreshed
+    Refresh {
+        /// Unix timestamp (seconds) when the new access token expires
+        expires_at: u64,
+        /// Fingerprint of the new tokens
+        fingerprint: TokenFingerprint,
+        /// Organization the tokens were issued for, if reported
+        #[serde(skip_serializing_if = "Option::is_none")]
+        organization: Option<Organization>,
+    },
+    /// The refresh token was rejected and the user must log in again
+    ReauthRequired {
+        /// Why re-authentication is needed
+        reason: String,
+    },
+    /// An API key was created
+    KeyCreated {
+        /// Fingerprint of the key (see [`ApiKeyRecord::fingerprint_of`](crate::ApiKeyRecord::fingerprint_of))
+        fingerprint: String,
+    },
+}
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+impl LifecycleEvent {
+    pub(crate) fn login(tokens: &TokenSet) -> Self {
+        Self::Login {
+            expires_at: tokens.expires_at,
+            fingerprint: tokens.fingerprint(),
+            organization: tokens.organization.clone(),
+        }
+    }
+
+    pub(crate) fn refresh(tokens: &TokenSet) -> Self {
+        Self::Refresh {
+            expires_at: tokens.expires_at,
+            fingerprint: tokens.fingerprint(),
+            organization: tokens.organization.clone(),
+        }
+    }
+}
+
+/// POSTs signed JSON [`LifecycleEvent`]s to an internal URL
+///
+/// Lets platform teams monitor credential health across developer machines and
+/// agents. Set it with [`OAuthConfigBuilder::notifier`](crate::OAuthConfigBuilder::notifier);
+/// the clients then report logins, refreshes and created API keys, and the token
+/// managers report when re-authentication is required. Delivery is best effort:
+/// a failing webhook never fails the OAuth operation.
+///
+/// Events are sent in the background (from a thread, or a task when the async
+/// client runs in a tokio runtime) with a separate HTTP client: a 5-second
+/// timeout, and neither the proxy nor the default headers of the OAuth client.
+/// A process exiting right after a login may therefore not deliver its event.
+///
+/// The body is the event plus `timestamp` and, if set, `source`:
+///
+/// ```json
+/// {"event": "refresh", "expires_at": 1700003600,
+///  "fingerprint": {"access": "3f2a9c1d8e7b6a50", "refresh": "a1b2c3d4e5f60718"},
+///  "timestamp": 1700000000, "source": "ci-runner-7"}
+/// ```
+///
+/// With a secret, the request carries `x-anthropic-auth-timestamp` and
+/// `x-anthropic-auth-signature: sha256=<hex>`, an HMAC-SHA256 over
+/// `"<timestamp>.<body>"`. Verify it with [`WebhookNotifier::verify`].
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{OAuthConfig, WebhookNotifier};
+///
+/// let config = OAuthConfig::builder()
+///     .notifier(
+///         WebhookNotifier::new("https://credentials.internal/events")
+///             .secret("shared-secret")
+///             .source("build-agent-3"),
+///     )
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    url: String,
+    secret: Option<Vec<u8>>,
+    source: Option<String>,
+}
+
+impl fmt::Debug for WebhookNotifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WebhookNotifier")
+            .field("url", &self.url)
+            .field("secret", &self.secret.as_ref().map(|_| "<redacted>"))
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl WebhookNotifier {
+    /// Send events to `url`, unsigned
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            secret: None,
+            source: None,
+        }
+    }
+
+    /// Sign events with an HMAC-SHA256 secret shared with the receiver
+    pub fn secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.secret = Some(secret.as_ref().to_vec());
+        self
+    }
+
+    /// Identify this machine or agent in every event
+    pub fn source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Get the URL events are sent to
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Build the JSON body and headers for an event
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+    pub(crate) fn request(&self, event: &LifecycleEvent) -> (String, Vec<(String, String)>) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut body = serde_json::to_value(event).unwrap_or_default();
+        body["timestamp"] = timestamp.into();
+        if let Some(source) = &self.source {
+            body["source"] = source.as_str().into();
+        }
+        let body = body.to_string();
+
+        let mut headers = Vec::new();
+        if let Some(secret) = &self.secret {
+            headers.push((WEBHOOK_TIMESTAMP_HEADER.to_string(), timestamp.to_string()));
+            headers.push((
+                WEBHOOK_SIGNATURE_HEADER.to_string(),
+                format!("sha256={}", sign(secret, timestamp, &body)),
+            ));
+        }
+        (body, headers)
+    }
+
+    /// Check the signature of a received event (for the receiving service)
+    ///
+    /// `timestamp` and `signature` are the values of the
+    /// `x-anthropic-auth-timestamp` and `x-anthropic-auth-signature` headers.
+    /// Reject old timestamps yourself to prevent replays.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::WebhookNotifier;
+    ///
+    /// let notifier = WebhookNotifier::new("https://credentials.internal/events").secret("s3cret");
+    /// assert!(!notifier.verify(r#"{"event":"refresh"}"#, "1700000000", "sha256=00"));
+    /// ```
+    pub fn verify(&self, body: &str, timestamp: &str, signature: &str) -> bool {
+        let (Some(secret), Ok(timestamp)) = (&self.secret, timestamp.parse::<u64>()) else {
+            return false;
+        };
+        let Some(signature) = signature.strip_prefix("sha256=").and_then(decode_hex) else {
+            return false;
+        };
+
+        mac(secret, timestamp, body)
+            .verify_slice(&signature)
+            .is_ok()
+    }
+}
+
+/// HMAC-SHA256 over `"<timestamp>.<body>"`
+fn mac(secret: &[u8], timestamp: u64, body: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts any key size");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    mac
+}
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+fn sign(secret: &[u8], timestamp: u64, body: &str) -> String {
+    mac(secret, timestamp, body)
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{AnthropicAuthError, CsrfState, OAuthFlow, OAuthMode, PkceVerifier, Result};
+
+/// How long a pending flow stays resumable by default (10 minutes)
+pub const DEFAULT_FLOW_TTL: Duration = Duration::from_secs(600);
+
+/// Serializable snapshot of an in-progress OAuth flow
+///
+/// [`OAuthFlow`] carries no timestamp, so a persisted one never goes stale. Convert
+/// it into a `PendingFlow` to persist it (in a session store, database, or file)
+/// between the "redirect out" and "callback in" requests of a web app, or across
+/// process restarts, then call [`PendingFlow::resume`] to get the `OAuthFlow` back
+/// for the token exchange.
+///
+/// The verifier is a secret: store pending flows server-side, never in a cookie
+/// or URL the user controls.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{ExchangeRequest, OAuthClient, OAuthConfig, OAuthMode, PendingFlow};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = OAuthClient::new(OAuthConfig::default())?;
+/// let flow = client.start_flow(OAuthMode::Max)?;
+///
+/// // Request 1: persist the flow and redirect the user
+/// let saved = serde_json::to_string(&PendingFlow::new(&flow))?;
+///
+/// // Request 2 (possibly another process): restore and complete the flow
+/// let pending: PendingFlow = serde_json::from_str(&saved)?;
+/// let flow = pending.resume()?;
+/// let tokens = client.exchange(&ExchangeRequest::new("code#state", &flow))?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingFlow {
+    /// The URL the user was sent to
+    pub authorization_url: String,
+    /// The PKCE verifier used to exchange the authorization code for tokens
+    pub verifier: PkceVerifier,
+    /// The state token for CSRF protection
+    pub state: CsrfState,
+    /// The OAuth mode (Max or Console)
+    pub mode: OAuthMode,
+    /// The redirect URI sent in the authorization URL
+    pub redirect_uri: String,
+    /// Unix timestamp (seconds) when the flow was saved
+    pub created_at: u64,
+}
+
+impl PendingFlow {
+    /// Snapshot an in-progress flow, stamped with the current time
+    pub fn new(flow: &OAuthFlow) -> Self {
+        Self {
+            authorization_url: flow.authorization_url.clone(),
+            verifier: flow.verifier.clone(),
+            state: flow.state.clone(),
+            mode: flow.mode.clone(),
+            redirect_uri: flow.redirect_uri.clone(),
+            created_at: now_secs(),
+        }
+    }
+
+    /// Re-stamp the flow with the current time, keeping it resumable for another TTL
+    ///
+    /// Call this whenever the user is known to still be working on the
+    /// authorization (e.g. on each [`WaitingEvent`](crate::WaitingEvent) of a
+    /// [`KeepAlive`](crate::KeepAlive)), then persist the flow again.
+    pub fn touch(&mut self) {
+        self.created_at = now_secs();
+    }
+
+    /// Get how long ago the flow was saved
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now_secs().saturating_sub(self.created_at))
+    }
+
+    /// Check if the flow is older than [`DEFAULT_FLOW_TTL`]
+    pub fn is_expired(&self) -> bool {
+        self.is_older_than(DEFAULT_FLOW_TTL)
+    }
+
+    /// Check if the flow is older than `max_age`
+    pub fn is_older_than(&self, max_age: Duration) -> bool {
+        self.age() > max_age
+    }
+
+    /// Restore the flow if it is younger than [`DEFAULT_FLOW_TTL`]
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::FlowExpired`] if the flow is too old
+    pub fn resume(self) -> Result<OAuthFlow> {
+        self.resume_within(DEFAULT_FLOW_TTL)
+    }
+
+    /// Restore the flow if it is younger than `max_age`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::FlowExpired`] if the flow is too old
+    pub fn resume_within(self, max_age: Duration) -> Result<OAuthFlow> {
+        if self.is_older_than(max_age) {
+            return Err(AnthropicAuthError::FlowExpired);
+        }
+
+        Ok(OAuthFlow {
+            authorization_url: self.authorization_url,
+            verifier: self.verifier,
+            state: self.state,
+            mode: self.mode,
+            redirect_uri: self.redirect_uri,
+        })
+    }
+}
+
+impl From<OAuthFlow> for PendingFlow {
+    fn from(flow: OAuthFlow) -> Self {
+        Self::new(&flow)
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
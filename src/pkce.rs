@@ -0,0 +1,124 @@
+use oauth2::{PkceCodeChallenge, PkceCodeVerifier};
+use serde::{Deserialize, Serialize};
+
+use crate::{AnthropicAuthError, Result};
+
+/// PKCE code verifier of an authorization flow
+///
+/// Validated on construction against RFC 7636 (43-128 characters from
+/// `A-Z a-z 0-9 - . _ ~`). A distinct type from [`CsrfState`](crate::CsrfState), so
+/// the two can't be swapped in an [`ExchangeRequest`](crate::ExchangeRequest). The verifier is a
+/// secret, so its `Debug` output is redacted.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::PkceVerifier;
+///
+/// let verifier: PkceVerifier = "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".parse()?;
+/// assert!(verifier.matches_challenge("E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"));
+/// assert!("too-short".parse::<PkceVerifier>().is_err());
+/// # Ok::<(), anthropic_auth::AnthropicAuthError>(())
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct PkceVerifier(String);
+
+impl PkceVerifier {
+    /// Validate and wrap a PKCE verifier
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::OAuth`] if the verifier has an invalid length
+    /// or contains characters outside the RFC 7636 alphabet
+    pub fn new(verifier: impl Into<String>) -> Result<Self> {
+        let verifier = verifier.into();
+        if verifier.len() < 43 || verifier.len() > 128 {
+            return Err(AnthropicAuthError::OAuth(
+                "PKCE verifier has invalid length (must be 43-128 characters)".to_string(),
+            ));
+        }
+        if !verifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'))
+        {
+            return Err(AnthropicAuthError::OAuth(
+                "PKCE verifier contains invalid characters".to_string(),
+            ));
+        }
+        Ok(Self(verifier))
+    }
+
+    /// Create a verifier from 32 random bytes (43 base64url characters)
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+    pub(crate) fn from_bytes(bytes: &[u8; 32]) -> Self {
+        use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+        use base64::Engine;
+
+        Self(URL_SAFE_NO_PAD.encode(bytes))
+    }
+
+    /// Get the verifier as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Compute the S256 code challenge sent in the authorization URL
+    pub fn challenge(&self) -> String {
+        self.pkce_challenge().as_str().to_string()
+    }
+
+    /// Check that `challenge` was derived from this verifier
+    ///
+    /// Useful as a round-trip self-check before exchanging a code, e.g. against
+    /// the `code_challenge` of a restored flow's authorization URL.
+    pub fn matches_challenge(&self, challenge: &str) -> bool {
+        crate::csrf::states_match(&self.challenge(), challenge)
+    }
+
+    pub(crate) fn pkce_challenge(&self) -> PkceCodeChallenge {
+        PkceCodeChallenge::from_code_verifier_sha256(&PkceCodeVerifier::new(self.0.clone()))
+    }
+}
+
+impl std::ops::Deref for PkceVerifier {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for PkceVerifier {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Debug for PkceVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PkceVerifier(..)")
+    }
+}
+
+impl std::str::FromStr for PkceVerifier {
+    type Err = AnthropicAuthError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for PkceVerifier {
+    type Error = AnthropicAuthError;
+
+    fn try_from(verifier: String) -> Result<Self> {
+        Self::new(verifier)
+    }
+}
+
+impl From<PkceVerifier> for String {
+    fn from(verifier: PkceVerifier) -> Self {
+        verifier.0
+    }
+}
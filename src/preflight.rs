@@ -0,0 +1,64 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpListener};
+use url::{Host, Url};
+
+use crate::{AnthropicAuthError, OAuthConfig, Result};
+
+/// Check that a local socket address can be bound right now
+///
+/// The listener is closed again immediately, so the port is free for the callback
+/// server afterwards (barring a race with another process).
+///
+/// # Errors
+///
+/// Returns [`AnthropicAuthError::PortUnavailable`] if the address cannot be bound
+pub fn check_port_available(addr: SocketAddr) -> Result<()> {
+    TcpListener::bind(addr)
+        .map(drop)
+        .map_err(|e| AnthropicAuthError::PortUnavailable {
+            port: addr.port(),
+            reason: e.to_string(),
+        })
+}
+
+impl OAuthConfig {
+    /// Check that the localhost port in the redirect URI can be bound
+    ///
+    /// Call this before starting a flow whose redirect points at a local callback
+    /// server, so a busy port is reported before the user authorizes in the browser
+    /// instead of ending in "connection refused". Call it *before* starting your own
+    /// callback server, since that server will hold the port.
+    ///
+    /// Redirect URIs that don't point at `localhost`, `127.0.0.1`, or `[::1]` are not
+    /// checked.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::PortUnavailable`] if the port is in use, or
+    /// [`AnthropicAuthError::UrlParse`] if the redirect URI is invalid
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::OAuthConfig;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let config = OAuthConfig::builder().redirect_port(8080).build();
+    /// config.check_redirect_port()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn check_redirect_port(&self) -> Result<()> {
+        let url = Url::parse(&self.redirect_uri())?;
+        let ip = match url.host() {
+            Some(Host::Domain("localhost")) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Some(Host::Ipv4(ip)) if ip.is_loopback() => IpAddr::V4(ip),
+            Some(Host::Ipv6(ip)) if ip == Ipv6Addr::LOCALHOST => IpAddr::V6(ip),
+            _ => return Ok(()),
+        };
+        let Some(port) = url.port_or_known_default() else {
+            return Ok(());
+        };
+
+        check_port_available(SocketAddr::new(ip, port))
+    }
+}
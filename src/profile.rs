@@ -0,0 +1,155 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    AnthropicAuthError, Endpoints, OAuthConfig, OAuthConfigBuilder, RedirectStrategy, Result,
+};
+
+/// Name of the profile that resolves to the base configuration unless overrides
+/// are registered under it
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Per-profile overrides layered on top of a base [`OAuthConfig`]
+///
+/// Unset fields fall back to the base configuration. In a config file these are
+/// the fields of a `[profiles.<name>]` section (see [`Profiles::from_file`]).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfileOverrides {
+    /// Override the OAuth client ID
+    pub client_id: Option<String>,
+    /// Override the redirect URI
+    pub redirect_uri: Option<String>,
+    /// Override the requested scopes
+    pub scope: Option<String>,
+    /// Override the OAuth endpoints
+    pub endpoints: Option<Endpoints>,
+}
+
+impl ProfileOverrides {
+    /// Apply the overrides to `config`
+    pub fn apply(&self, mut config: OAuthConfig) -> OAuthConfig {
+        if let Some(client_id) = &self.client_id {
+            config.client_id = client_id.clone();
+        }
+        if let Some(redirect_uri) = &self.redirect_uri {
+            config.redirect = RedirectStrategy::from_uri(redirect_uri.clone());
+        }
+        if let Some(scope) = &self.scope {
+            config.scope = scope.clone();
+        }
+        if let Some(endpoints) = &self.endpoints {
+            config.endpoints = Some(endpoints.clone());
+        }
+        config
+    }
+
+    pub(crate) fn apply_builder(self, mut builder: OAuthConfigBuilder) -> OAuthConfigBuilder {
+        if let Some(client_id) = self.client_id {
+            builder = builder.client_id(client_id);
+        }
+        if let Some(redirect_uri) = self.redirect_uri {
+            builder = builder.redirect_uri(redirect_uri);
+        }
+        if let Some(scope) = self.scope {
+            builder = builder.scope(scope);
+        }
+        if let Some(endpoints) = self.endpoints {
+            builder = builder.endpoints(endpoints);
+        }
+        builder
+    }
+}
+
+/// Named configuration profiles (e.g. "staging" vs "prod")
+///
+/// Lets one binary authenticate against multiple Anthropic environments. Each
+/// profile overlays its [`ProfileOverrides`] on a shared base configuration; the
+/// [`DEFAULT_PROFILE`] resolves to the base itself unless overrides are registered
+/// under that name. Profiles can also be loaded from the `[profiles.<name>]`
+/// sections of a config file with [`Profiles::from_file`].
+///
+/// Use the same profile name as the key when caching or storing the resulting
+/// tokens, e.g. with `FileTokenStore::for_profile` or as the user of a
+/// `KeyringTokenStore`.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{Endpoints, OAuthConfig, ProfileOverrides, Profiles};
+///
+/// let profiles = Profiles::new(OAuthConfig::default()).with_profile(
+///     "staging",
+///     ProfileOverrides {
+///         client_id: Some("staging-client-id".to_string()),
+///         endpoints: Some(Endpoints {
+///             authorize_url: "https://staging.example.com/oauth/authorize".to_string(),
+///             token_url: "https://staging.example.com/v1/oauth/token".to_string(),
+///             api_key_url: "https://staging.example.com/api/oauth/create_api_key".to_string(),
+///         }),
+///         ..Default::default()
+///     },
+/// );
+///
+/// let config = profiles.resolve("staging").unwrap();
+/// assert_eq!(config.client_id, "staging-client-id");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Profiles {
+    base: OAuthConfig,
+    overrides: BTreeMap<String, ProfileOverrides>,
+}
+
+impl Profiles {
+    /// Create a profile set with the given base configuration
+    pub fn new(base: OAuthConfig) -> Self {
+        Self {
+            base,
+            overrides: BTreeMap::new(),
+        }
+    }
+
+    /// Add or replace a named profile
+    pub fn with_profile(mut self, name: impl Into<String>, overrides: ProfileOverrides) -> Self {
+        self.insert(name, overrides);
+        self
+    }
+
+    /// Add or replace a named profile in place
+    pub fn insert(&mut self, name: impl Into<String>, overrides: ProfileOverrides) {
+        self.overrides.insert(name.into(), overrides);
+    }
+
+    /// Get the base configuration
+    pub fn base(&self) -> &OAuthConfig {
+        &self.base
+    }
+
+    /// List the configured profile names, including [`DEFAULT_PROFILE`]
+    pub fn names(&self) -> Vec<String> {
+        let mut names = vec![DEFAULT_PROFILE.to_string()];
+        names.extend(
+            self.overrides
+                .keys()
+                .filter(|name| *name != DEFAULT_PROFILE)
+                .cloned(),
+        );
+        names
+    }
+
+    /// Resolve the effective configuration for a profile
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] if the profile is unknown
+    pub fn resolve(&self, name: &str) -> Result<OAuthConfig> {
+        match self.overrides.get(name) {
+            Some(overrides) => Ok(overrides.apply(self.base.clone())),
+            None if name == DEFAULT_PROFILE => Ok(self.base.clone()),
+            None => Err(AnthropicAuthError::InvalidConfig(format!(
+                "Unknown profile '{}'",
+                name
+            ))),
+        }
+    }
+}
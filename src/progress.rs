@@ -0,0 +1,54 @@
+use std::fmt;
+use std::sync::Arc;
+
+/// Progress of a login run by the high-level helpers
+///
+/// Reported to the [`on_auth_event`](crate::OAuthConfigBuilder::on_auth_event)
+/// hook by `OAuthClient::login` and its variants and by `interactive_login`, so
+/// GUI apps can show what the login is waiting for. To let the user cancel a
+/// slow login, combine it with a [`KeepAlive`](crate::KeepAlive) whose handler
+/// returns `false`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AuthEvent {
+    /// The flow was started; the user should visit `url`
+    FlowStarted {
+        /// The authorization URL
+        url: String,
+    },
+    /// The authorization URL was opened in the default browser
+    BrowserOpened,
+    /// Waiting for the user to authorize (the callback, or the pasted `code#state`)
+    WaitingForCallback,
+    /// The authorization response arrived
+    CallbackReceived,
+    /// The authorization code is being exchanged for tokens
+    ExchangingCode,
+    /// The login completed
+    TokensReceived,
+    /// The login failed with this error
+    Error(String),
+}
+
+/// Callback receiving [`AuthEvent`]s, set with
+/// [`OAuthConfigBuilder::on_auth_event`](crate::OAuthConfigBuilder::on_auth_event)
+#[derive(Clone)]
+pub struct AuthEventHook(Arc<dyn Fn(&AuthEvent) + Send + Sync>);
+
+impl AuthEventHook {
+    /// Wrap a callback
+    pub fn new(hook: impl Fn(&AuthEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Run the callback
+    pub fn call(&self, event: &AuthEvent) {
+        (self.0)(event)
+    }
+}
+
+impl fmt::Debug for AuthEventHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AuthEventHook(..)")
+    }
+}
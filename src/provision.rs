@@ -0,0 +1,172 @@
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Mutex, Semaphore};
+use tokio::time::Instant;
+
+use crate::{AnthropicAuthError, ApiKeyOptions, AsyncOAuthClient, Result};
+
+/// A single API key to create
+#[derive(Debug, Clone)]
+pub struct KeyRequest {
+    /// Console OAuth access token for the organization the key belongs to
+    pub access_token: String,
+    /// Key name and workspace
+    pub options: ApiKeyOptions,
+}
+
+/// Outcome of a single [`KeyRequest`]
+#[derive(Debug)]
+pub struct KeyResult {
+    /// The request this result belongs to
+    pub request: KeyRequest,
+    /// The created API key, or the error after all retries were exhausted
+    pub result: Result<String>,
+    /// Number of attempts made
+    pub attempts: u32,
+}
+
+/// Creates API keys in bulk with bounded concurrency and retries
+///
+/// Meant for platform teams provisioning keys for many teams at once. Transient
+/// failures (network errors, HTTP 429 and 5xx) are retried with exponential backoff.
+/// A 429 pauses *all* workers for the backoff period, or longer if `Retry-After`
+/// asks, so a batch doesn't keep hammering a rate-limited endpoint.
+///
+/// **Note:** Only available when the `provisioning` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{ApiKeyOptions, AsyncOAuthClient, KeyProvisioner, KeyRequest, OAuthConfig};
+///
+/// # async fn example(access_token: String) -> Result<(), Box<dyn std::error::Error>> {
+/// let client = AsyncOAuthClient::new(OAuthConfig::default())?;
+/// let requests = ["team-a", "team-b", "team-c"].map(|team| KeyRequest {
+///     access_token: access_token.clone(),
+///     options: ApiKeyOptions {
+///         name: Some(format!("{}-key", team)),
+///         ..Default::default()
+///     },
+/// });
+///
+/// let results = KeyProvisioner::new(client).concurrency(2).provision(requests).await;
+/// for item in results {
+///     match item.result {
+///         Ok(_) => println!("{:?}: created", item.request.options.name),
+///         Err(e) => println!("{:?}: failed: {}", item.request.options.name, e),
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct KeyProvisioner {
+    client: AsyncOAuthClient,
+    concurrency: usize,
+    max_retries: u32,
+    base_delay: Duration,
+}
+
+impl KeyProvisioner {
+    /// Create a provisioner with 4 concurrent requests, 3 retries, and a 1 second base delay
+    pub fn new(client: AsyncOAuthClient) -> Self {
+        Self {
+            client,
+            concurrency: 4,
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+        }
+    }
+
+    /// Set the maximum number of keys created concurrently (at least 1)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set how many times a failed request is retried
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Set the initial backoff delay (doubled after every failed attempt)
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Create all requested keys and return one result per request, in input order
+    ///
+    /// Must be called from within a tokio runtime.
+    pub async fn provision(
+        &self,
+        requests: impl IntoIterator<Item = KeyRequest>,
+    ) -> Vec<KeyResult> {
+        let permits = Arc::new(Semaphore::new(self.concurrency));
+        let paused_until = Arc::new(Mutex::new(Instant::now()));
+
+        let handles = requests
+            .into_iter()
+            .map(|request| {
+                let provisioner = self.clone();
+                let permits = permits.clone();
+                let paused_until = paused_until.clone();
+                let handle = tokio::spawn({
+                    let request = request.clone();
+                    async move {
+                        let _permit = permits.acquire_owned().await;
+                        provisioner.provision_one(request, &paused_until).await
+                    }
+                });
+                (request, handle)
+            })
+            .collect::<Vec<_>>();
+
+        let mut results = Vec::with_capacity(handles.len());
+        for (request, handle) in handles {
+            // A panicking task only fails its own request
+            results.push(handle.await.unwrap_or_else(|e| KeyResult {
+                request,
+                result: Err(AnthropicAuthError::ApiKeyCreation(format!(
+                    "Provisioning task failed: {}",
+                    e
+                ))),
+                attempts: 0,
+            }));
+        }
+        results
+    }
+
+    async fn provision_one(&self, request: KeyRequest, paused_until: &Mutex<Instant>) -> KeyResult {
+        let mut attempts = 0;
+        loop {
+            // Honor a rate-limit pause triggered by any worker
+            let resume_at = *paused_until.lock().await;
+            tokio::time::sleep_until(resume_at).await;
+
+            attempts += 1;
+            let result = self
+                .client
+                .create_api_key_with(&request.access_token, &request.options)
+                .await;
+
+            let retryable = result.as_ref().is_err_and(AnthropicAuthError::is_retryable);
+            if !retryable || attempts > self.max_retries {
+                return KeyResult {
+                    request,
+                    result,
+                    attempts,
+                };
+            }
+
+            let delay = self.base_delay * 2u32.saturating_pow(attempts - 1);
+            if let Err(AnthropicAuthError::RateLimited { retry_after, .. }) = &result {
+                let mut paused_until = paused_until.lock().await;
+                *paused_until = (*paused_until).max(Instant::now() + delay.max(*retry_after));
+            } else {
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Request credentials for the Anthropic Messages API
+//!
+//! Max-mode access tokens are sent as a bearer token, and the API only accepts
+//! them together with the OAuth beta flag. [`Credentials`] bundles both (plus the
+//! API version) as plain header pairs, so they can be handed to any HTTP client
+//! or Anthropic SDK that accepts custom headers.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use anthropic_auth::sdk::Credentials;
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! # let tokens: anthropic_auth::TokenSet = todo!();
+//! let credentials = Credentials::from(&tokens);
+//! let response = reqwest::blocking::Client::new()
+//!     .post(anthropic_auth::sdk::MESSAGES_URL)
+//!     .headers(credentials.header_map()?)
+//!     .json(&serde_json::json!({
+//!         "model": "claude-sonnet-4-5",
+//!         "max_tokens": 1024,
+//!         "messages": [{"role": "user", "content": "Hello"}],
+//!     }))
+//!     .send()?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::TokenSet;
+
+/// Beta flag the Messages API requires for OAuth access tokens
+pub const OAUTH_BETA: &str = "oauth-2025-04-20";
+
+/// Messages API version sent in the `anthropic-version` header
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Anthropic's Messages API endpoint
+pub const MESSAGES_URL: &str = "https://api.anthropic.com/v1/messages";
+
+/// Headers authenticating a Messages API request with an OAuth access token
+///
+/// Build it from a [`TokenSet`] right before the request; it holds no refresh
+/// token and does not track expiry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Credentials {
+    /// The OAuth access token
+    pub access_token: String,
+    /// Values of the `anthropic-beta` header, [`OAUTH_BETA`] first
+    pub betas: Vec<String>,
+    /// Value of the `anthropic-version` header
+    pub version: String,
+}
+
+impl Credentials {
+    /// Create credentials for an access token, with the OAuth beta flag and the current API version
+    pub fn new(access_token: impl Into<String>) -> Self {
+        Self {
+            access_token: access_token.into(),
+            betas: vec![OAUTH_BETA.to_string()],
+            version: ANTHROPIC_VERSION.to_string(),
+        }
+    }
+
+    /// Opt into another beta feature in addition to OAuth
+    pub fn beta(mut self, beta: impl Into<String>) -> Self {
+        let beta = beta.into();
+        if !self.betas.contains(&beta) {
+            self.betas.push(beta);
+        }
+        self
+    }
+
+    /// Get the value of the `authorization` header (`Bearer <access token>`)
+    pub fn authorization(&self) -> String {
+        format!("Bearer {}", self.access_token)
+    }
+
+    /// Get the request headers as lowercase name/value pairs
+    ///
+    /// Includes `authorization`, `anthropic-beta` and `anthropic-version`.
+    pub fn headers(&self) -> Vec<(String, String)> {
+        vec![
+            ("authorization".to_string(), self.authorization()),
+            ("anthropic-beta".to_string(), self.betas.join(",")),
+            ("anthropic-version".to_string(), self.version.clone()),
+        ]
+    }
+
+    /// Get the request headers as a header map for `reqwest` or other `http`-based clients
+    ///
+    /// **Note:** Only available when the `blocking` or `async` feature is enabled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidTokenFormat`](crate::AnthropicAuthError::InvalidTokenFormat)
+    /// if a value is not a valid header value
+    #[cfg(feature = "reqwest")]
+    pub fn header_map(&self) -> crate::Result<reqwest::header::HeaderMap> {
+        use reqwest::header::{HeaderName, HeaderValue};
+
+        self.headers()
+            .into_iter()
+            .map(|(name, value)| {
+                let mut value = HeaderValue::from_str(&value).map_err(|e| {
+                    crate::AnthropicAuthError::InvalidTokenFormat(format!(
+                        "invalid {} header: {}",
+                        name, e
+                    ))
+                })?;
+                if name == "authorization" {
+                    value.set_sensitive(true);
+                }
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|e| crate::AnthropicAuthError::InvalidTokenFormat(e.to_string()))?;
+                Ok((name, value))
+            })
+            .collect()
+    }
+}
+
+impl From<&TokenSet> for Credentials {
+    fn from(tokens: &TokenSet) -> Self {
+        Self::new(tokens.access_token.clone())
+    }
+}
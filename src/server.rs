@@ -1,31 +1,314 @@
 use axum::{
+    body::Body,
     extract::Query,
-    response::{Html, IntoResponse},
-    routing::get,
+    http::{HeaderMap, StatusCode, Uri},
+    response::{Html, IntoResponse, Response},
+    routing::{get, RouterIntoService},
     Router,
 };
-use serde::Deserialize;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
 use tokio::sync::oneshot;
 
-use crate::{AnthropicAuthError, Result};
+use crate::callback::{
+    callback_outcome, normalize_path, unmatched_page, CallbackQuery, FORBIDDEN_PAGE,
+    MISDIRECTED_PAGE,
+};
+use crate::csrf::states_match;
+use crate::{
+    AnthropicAuthError, CallbackData, CallbackServerConfig, KeepAlive, Result,
+    DEFAULT_CALLBACK_PATH,
+};
 
-#[derive(Debug, Deserialize)]
-struct CallbackQuery {
-    code: Option<String>,
-    state: Option<String>,
-    error: Option<String>,
-}
+type CallbackSender = oneshot::Sender<Result<CallbackData>>;
 
+#[derive(Default)]
 struct ServerState {
-    tx: tokio::sync::Mutex<Option<oneshot::Sender<Result<CallbackData>>>>,
-    expected_state: String,
+    /// Response channels of the in-flight flows, keyed by expected state
+    pending: Mutex<HashMap<String, CallbackSender>>,
+}
+
+impl ServerState {
+    fn pending(&self) -> MutexGuard<'_, HashMap<String, CallbackSender>> {
+        self.pending.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Take the channel of the flow whose state a callback carries
+    ///
+    /// Each channel is taken at most once; callbacks without a matching state
+    /// leave every flow waiting.
+    fn take_sender(&self, received_state: Option<&str>) -> Option<CallbackSender> {
+        let received = received_state?;
+        let mut pending = self.pending();
+        let expected = pending
+            .keys()
+            .find(|expected| states_match(received, expected))
+            .cloned()?;
+        pending.remove(&expected)
+    }
 }
 
+/// Receiving end of a callback route created by [`callback_router`],
+/// [`callback_service`], or [`CallbackRegistry::register`]
+///
+/// Resolves once the flow's callback request has been handled.
 #[derive(Debug)]
-pub struct CallbackData {
-    pub code: String,
-    pub state: String,
+pub struct CallbackReceiver {
+    rx: oneshot::Receiver<Result<CallbackData>>,
+}
+
+impl CallbackReceiver {
+    /// Wait for the callback
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an OAuth error or invalid state was received, or if the
+    /// router was dropped (or the flow cancelled) before its callback arrived
+    pub async fn recv(self) -> Result<CallbackData> {
+        match self.rx.await {
+            Ok(result) => result,
+            Err(_) => Err(AnthropicAuthError::CallbackServer(
+                "Server shut down unexpectedly".to_string(),
+            )),
+        }
+    }
+
+    /// Wait for the callback, emitting waiting events while the user takes their time
+    ///
+    /// # Errors
+    ///
+    /// Same as [`recv`](Self::recv), and [`AnthropicAuthError::FlowExpired`] if the
+    /// keep-alive's `max_wait` passed or its handler stopped the wait
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{CallbackRegistry, KeepAlive};
+    /// use std::time::Duration;
+    ///
+    /// # async fn example(registry: CallbackRegistry) -> Result<(), Box<dyn std::error::Error>> {
+    /// let receiver = registry.register("state");
+    /// let keep_alive = KeepAlive::new(Duration::from_secs(60)).max_wait(Duration::from_secs(3600));
+    /// let callback = receiver.recv_keep_alive(&keep_alive).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn recv_keep_alive(mut self, keep_alive: &KeepAlive) -> Result<CallbackData> {
+        let started = std::time::Instant::now();
+        let deadline = keep_alive.deadline(started);
+        loop {
+            let mut wait = keep_alive.interval();
+            if let Some(deadline) = deadline {
+                let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                if remaining.is_zero() {
+                    return Err(AnthropicAuthError::FlowExpired);
+                }
+                wait = wait.min(remaining);
+            }
+
+            match tokio::time::timeout(wait, &mut self.rx).await {
+                Ok(Ok(result)) => return result,
+                Ok(Err(_)) => {
+                    return Err(AnthropicAuthError::CallbackServer(
+                        "Server shut down unexpectedly".to_string(),
+                    ))
+                }
+                Err(_)
+                    if deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline) =>
+                {
+                    return Err(AnthropicAuthError::FlowExpired)
+                }
+                Err(_) => {
+                    if !keep_alive.report(started) {
+                        return Err(AnthropicAuthError::FlowExpired);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Callback handler for several in-flight authorizations at once
+///
+/// One long-lived callback server can serve many concurrent flows (e.g. a
+/// multi-user desktop app or a web backend). [`register`](Self::register) each
+/// flow's state before sending the user to the authorization URL; every callback
+/// is dispatched to the flow whose state it carries. Callbacks with an unknown or
+/// already used state get a "link expired" page.
+///
+/// Cloning is cheap and all clones share the same pending flows.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{AsyncOAuthClient, CallbackRegistry, CallbackServerConfig, OAuthConfig, OAuthMode};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = AsyncOAuthClient::new(OAuthConfig::default())?;
+/// let registry = CallbackRegistry::new();
+/// tokio::spawn(registry.clone().serve(CallbackServerConfig::default()));
+///
+/// // For every login request:
+/// let flow = client.start_flow(OAuthMode::Max)?;
+/// let receiver = registry.register(&flow.state);
+/// println!("Visit: {}", flow.authorization_url);
+///
+/// let callback = receiver.recv().await?;
+/// let tokens = client.exchange_callback(&callback, &flow).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct CallbackRegistry {
+    state: Arc<ServerState>,
+}
+
+impl std::fmt::Debug for CallbackRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackRegistry")
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+impl CallbackRegistry {
+    /// Create a registry without pending flows
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a flow by its expected state and get the receiver for its callback
+    ///
+    /// Registering the same state again replaces the previous registration, whose
+    /// receiver then fails.
+    pub fn register(&self, expected_state: &str) -> CallbackReceiver {
+        let (tx, rx) = oneshot::channel();
+        self.state.pending().insert(expected_state.to_string(), tx);
+        CallbackReceiver { rx }
+    }
+
+    /// Stop waiting for a flow (e.g. the user cancelled the login)
+    ///
+    /// Returns `false` if the flow wasn't pending. Its receiver fails.
+    pub fn cancel(&self, expected_state: &str) -> bool {
+        self.state.pending().remove(expected_state).is_some()
+    }
+
+    /// Get the number of flows still waiting for their callback
+    pub fn pending(&self) -> usize {
+        self.state.pending().len()
+    }
+
+    /// Create an axum `Router` serving the callbacks on `/callback`
+    pub fn router(&self) -> Router {
+        self.router_at(DEFAULT_CALLBACK_PATH)
+    }
+
+    /// Create an axum `Router` serving the callbacks on a custom path
+    pub fn router_at(&self, path: &str) -> Router {
+        Router::new()
+            .route(&normalize_path(path.to_string()), get(handle_callback))
+            .with_state(self.state.clone())
+    }
+
+    /// Create an axum `Router` serving the callbacks as configured
+    ///
+    /// Serves `config.path` and the path of `config.public_url`, and checks
+    /// forwarded headers if `config.trust_forwarded_headers` is set. The bind
+    /// address is only used to decide whether `Host` headers must be loopback
+    /// names (see [`CallbackServerConfig::check_request_headers`]).
+    pub fn router_with(&self, config: &CallbackServerConfig) -> Router {
+        let config = Arc::new(config.clone());
+        let mut router = Router::new();
+        for path in config.callback_paths() {
+            let config = config.clone();
+            router = router.route(
+                &path,
+                get(move |uri: Uri, headers: HeaderMap, query, state| {
+                    handle_forwarded_callback(config, uri, headers, query, state)
+                }),
+            );
+        }
+        router.with_state(self.state.clone())
+    }
+
+    /// Run a callback server for the registered flows until it fails
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound or the server fails
+    pub async fn serve(self, config: CallbackServerConfig) -> Result<()> {
+        let addr = config.addr;
+        let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
+            AnthropicAuthError::CallbackServer(format!("Failed to bind to {}: {}", addr, e))
+        })?;
+
+        axum::serve(listener, self.router_with(&config))
+            .await
+            .map_err(|e| AnthropicAuthError::CallbackServer(e.to_string()))
+    }
+}
+
+/// Create an axum `Router` that handles the OAuth callback on `/callback`
+///
+/// Use this to mount the callback into an existing axum application instead of
+/// letting [`run_callback_server`] bind its own listener. The callback data is
+/// delivered through the returned [`CallbackReceiver`].
+///
+/// # Arguments
+///
+/// * `expected_state` - The CSRF state token to validate against
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{callback_router, AsyncOAuthClient, OAuthConfig, OAuthMode};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = AsyncOAuthClient::new(OAuthConfig::default())?;
+/// let flow = client.start_flow(OAuthMode::Max)?;
+///
+/// let (callback, receiver) = callback_router(&flow.state);
+/// let app = axum::Router::new()
+///     .route("/health", axum::routing::get(|| async { "ok" }))
+///     .merge(callback);
+///
+/// let listener = tokio::net::TcpListener::bind("127.0.0.1:1455").await?;
+/// tokio::spawn(async move { axum::serve(listener, app).await });
+///
+/// let callback = receiver.recv().await?;
+/// let tokens = client.exchange_callback(&callback, &flow).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn callback_router(expected_state: &str) -> (Router, CallbackReceiver) {
+    callback_router_at(DEFAULT_CALLBACK_PATH, expected_state)
+}
+
+/// Create an axum `Router` that handles the OAuth callback on a custom path
+///
+/// Same as [`callback_router`], but serves the callback on `path` instead of `/callback`.
+pub fn callback_router_at(path: &str, expected_state: &str) -> (Router, CallbackReceiver) {
+    let (registry, receiver) = single_flow_registry(expected_state);
+    (registry.router_at(path), receiver)
+}
+
+/// Create a registry serving exactly one flow
+fn single_flow_registry(expected_state: &str) -> (CallbackRegistry, CallbackReceiver) {
+    let registry = CallbackRegistry::new();
+    let receiver = registry.register(expected_state);
+    (registry, receiver)
+}
+
+/// Create a tower `Service` that handles the OAuth callback on `/callback`
+///
+/// Like [`callback_router`], but returns a plain tower service for HTTP stacks
+/// other than axum (e.g. hyper or tower-based servers).
+pub fn callback_service(expected_state: &str) -> (RouterIntoService<Body>, CallbackReceiver) {
+    let (router, receiver) = callback_router(expected_state);
+    (router.into_service(), receiver)
 }
 
 /// Run a local OAuth callback server
@@ -50,10 +333,12 @@ pub struct CallbackData {
 ///
 /// Returns an error if:
 /// - The server fails to start
-/// - An OAuth error is received
-/// - The state token doesn't match
+/// - An OAuth error carrying the flow's state is received
 /// - The callback times out
 ///
+/// Callbacks without the flow's state (e.g. from a stale tab or another local
+/// process) get a "link expired" page and don't end the wait.
+///
 /// # Example
 ///
 /// ```no_run
@@ -71,24 +356,43 @@ pub struct CallbackData {
 ///
 /// // Wait for callback
 /// let callback = callback_future.await?;
-/// let tokens = client.exchange_code(&callback.code, &callback.state, &flow.verifier).await?;
+/// let tokens = client.exchange_callback(&callback, &flow).await?;
 /// # Ok(())
 /// # }
 /// ```
 pub async fn run_callback_server(port: u16, expected_state: &str) -> Result<CallbackData> {
-    let (tx, rx) = oneshot::channel();
-
-    let state = Arc::new(ServerState {
-        tx: tokio::sync::Mutex::new(Some(tx)),
-        expected_state: expected_state.to_string(),
-    });
+    run_callback_server_with(&CallbackServerConfig::with_port(port), expected_state).await
+}
 
-    let app = Router::new()
-        .route("/callback", get(handle_callback))
-        .with_state(state);
+/// Run a local OAuth callback server with a custom bind address and path
+///
+/// Same as [`run_callback_server`], but binds to `config.addr` (IPv4 or IPv6)
+/// and serves the callback on `config.path` (and the path of `config.public_url`).
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{run_callback_server_with, CallbackServerConfig};
+/// use std::net::SocketAddr;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let expected_state = "state";
+/// // Reachable through a devcontainer port forward
+/// let config = CallbackServerConfig::new("0.0.0.0:1455".parse::<SocketAddr>()?);
+/// let callback = run_callback_server_with(&config, expected_state).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn run_callback_server_with(
+    config: &CallbackServerConfig,
+    expected_state: &str,
+) -> Result<CallbackData> {
+    let (registry, receiver) = single_flow_registry(expected_state);
+    let app = registry.router_with(config);
 
-    let addr = format!("127.0.0.1:{}", port);
-    let listener = tokio::net::TcpListener::bind(&addr).await.map_err(|e| {
+    let addr = config.addr;
+    let listener = tokio::net::TcpListener::bind(addr).await.map_err(|e| {
         AnthropicAuthError::CallbackServer(format!("Failed to bind to {}: {}", addr, e))
     })?;
 
@@ -100,108 +404,37 @@ pub async fn run_callback_server(port: u16, expected_state: &str) -> Result<Call
     });
 
     // Wait for callback
-    match rx.await {
-        Ok(Ok(callback_data)) => Ok(callback_data),
-        Ok(Err(e)) => Err(e),
-        Err(_) => Err(AnthropicAuthError::CallbackServer(
-            "Server shut down unexpectedly".to_string(),
-        )),
+    receiver.recv().await
+}
+
+async fn handle_forwarded_callback(
+    config: Arc<CallbackServerConfig>,
+    uri: Uri,
+    headers: HeaderMap,
+    query: Query<CallbackQuery>,
+    state: axum::extract::State<Arc<ServerState>>,
+) -> Response {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    if !config.accepts_forwarded(uri.path(), header) {
+        return (StatusCode::MISDIRECTED_REQUEST, Html(MISDIRECTED_PAGE)).into_response();
     }
+    if !config.accepts_request(header) {
+        return (StatusCode::FORBIDDEN, Html(FORBIDDEN_PAGE)).into_response();
+    }
+    handle_callback(query, state).await.into_response()
 }
 
 async fn handle_callback(
     Query(params): Query<CallbackQuery>,
     axum::extract::State(state): axum::extract::State<Arc<ServerState>>,
 ) -> impl IntoResponse {
-    // Check for OAuth errors
-    if let Some(error) = params.error {
-        let _ = state.tx.lock().await.take().map(|tx| {
-            tx.send(Err(AnthropicAuthError::OAuth(format!(
-                "OAuth error: {}",
-                error
-            ))))
-        });
-        return Html(format!(
-            r#"
-            <html>
-                <head><title>Authorization Failed</title></head>
-                <body>
-                    <h1>Authorization Failed</h1>
-                    <p>Error: {}</p>
-                    <p>You can close this window.</p>
-                </body>
-            </html>
-            "#,
-            error
-        ));
-    }
-
-    // Validate state
-    let received_state = params.state.as_deref().unwrap_or("");
-    if received_state != state.expected_state {
-        let _ = state.tx.lock().await.take().map(|tx| {
-            tx.send(Err(AnthropicAuthError::OAuth(
-                "State mismatch - possible CSRF attack".to_string(),
-            )))
-        });
-        return Html(
-            r#"
-            <html>
-                <head><title>Authorization Failed</title></head>
-                <body>
-                    <h1>Authorization Failed</h1>
-                    <p>Security validation failed. Please try again.</p>
-                    <p>You can close this window.</p>
-                </body>
-            </html>
-            "#
-            .to_string(),
-        );
-    }
+    // No matching sender means the flow already completed (e.g. a stale or reloaded
+    // tab) or is unknown
+    let Some(tx) = state.take_sender(params.state.as_deref()) else {
+        return Html(unmatched_page(&params).to_string());
+    };
 
-    // Extract code
-    match params.code {
-        Some(code) => {
-            let _ = state.tx.lock().await.take().map(|tx| {
-                tx.send(Ok(CallbackData {
-                    code: code.clone(),
-                    state: received_state.to_string(),
-                }))
-            });
-            Html(
-                r#"
-                <html>
-                    <head><title>Authorization Successful</title></head>
-                    <body>
-                        <h1>Authorization Successful!</h1>
-                        <p>You have successfully authorized the application.</p>
-                        <p>You can close this window and return to the terminal.</p>
-                    </body>
-                </html>
-                "#
-                .to_string(),
-            )
-        }
-        None => {
-            let _ = state
-                .tx
-                .lock()
-                .await
-                .take()
-                .map(|tx| tx.send(Err(AnthropicAuthError::InvalidAuthorizationCode)));
-            Html(
-                r#"
-                <html>
-                    <head><title>Authorization Failed</title></head>
-                    <body>
-                        <h1>Authorization Failed</h1>
-                        <p>No authorization code received.</p>
-                        <p>You can close this window.</p>
-                    </body>
-                </html>
-                "#
-                .to_string(),
-            )
-        }
-    }
+    let (result, page) = callback_outcome(params);
+    let _ = tx.send(result);
+    Html(page)
 }
@@ -0,0 +1,350 @@
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant};
+
+use crate::callback::{
+    callback_outcome, unmatched_page, CallbackQuery, FORBIDDEN_PAGE, MISDIRECTED_PAGE,
+};
+use crate::csrf::states_match;
+use crate::{AnthropicAuthError, CallbackData, CallbackServerConfig, KeepAlive, Result};
+
+/// Largest request line and headers accepted from the browser
+const MAX_REQUEST_HEAD: u64 = 16 * 1024;
+
+/// How long a connection may take to send its request
+///
+/// Browsers open speculative connections they never use; those are dropped after this.
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Most connections served at once
+///
+/// Further connections wait in the listen backlog until a slot frees up, so idle
+/// connections can delay the callback by at most [`READ_TIMEOUT`] but not exhaust threads.
+const MAX_CONNECTIONS: usize = 16;
+
+/// How often the listener checks for new connections and finished requests
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Local OAuth callback server built on `std::net`, without tokio
+///
+/// Binding and waiting are separate steps, so the port is already listening when
+/// the browser is sent to the authorization page. Requests to other paths (such
+/// as `/favicon.ico`) get a 404 and don't end the wait. Behind a reverse proxy,
+/// see [`CallbackServerConfig::public_url`].
+///
+/// **Note:** Only available when the `callback-server-blocking` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{BlockingCallbackServer, CallbackServerConfig, OAuthClient, OAuthConfig, OAuthMode};
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = OAuthClient::new(OAuthConfig::default())?;
+/// let server = BlockingCallbackServer::bind(&CallbackServerConfig::default())?;
+/// let flow = client.start_flow(OAuthMode::Max)?;
+///
+/// println!("Visit: {}", flow.authorization_url);
+/// let callback = server.wait_timeout(&flow.state, Duration::from_secs(300))?;
+/// let tokens = client.exchange_callback(&callback, &flow)?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct BlockingCallbackServer {
+    listener: TcpListener,
+    config: Arc<CallbackServerConfig>,
+}
+
+impl BlockingCallbackServer {
+    /// Start listening on `config.addr`, serving the callback on `config.path`
+    /// (and the path of `config.public_url`)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound
+    pub fn bind(config: &CallbackServerConfig) -> Result<Self> {
+        let listener = TcpListener::bind(config.addr).map_err(|e| {
+            AnthropicAuthError::CallbackServer(format!("Failed to bind to {}: {}", config.addr, e))
+        })?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| AnthropicAuthError::CallbackServer(e.to_string()))?;
+
+        Ok(Self {
+            listener,
+            config: Arc::new(config.clone()),
+        })
+    }
+
+    /// Get the address the server is listening on, e.g. to learn a port picked by the OS
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket address cannot be read
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        self.listener
+            .local_addr()
+            .map_err(|e| AnthropicAuthError::CallbackServer(e.to_string()))
+    }
+
+    /// Wait for the callback of the flow with the given state
+    ///
+    /// Callbacks without the flow's state (e.g. from a stale tab or another local
+    /// process) get a "link expired" page and don't end the wait.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - An OAuth error carrying the flow's state is received
+    /// - The listener fails
+    pub fn wait(&self, expected_state: &str) -> Result<CallbackData> {
+        self.wait_until(expected_state, None, None)
+    }
+
+    /// Wait for the callback of the flow with the given state, giving up after `timeout`
+    ///
+    /// # Errors
+    ///
+    /// Same as [`wait`](Self::wait), and [`AnthropicAuthError::FlowExpired`] if no
+    /// callback arrived within `timeout`
+    pub fn wait_timeout(&self, expected_state: &str, timeout: Duration) -> Result<CallbackData> {
+        self.wait_until(expected_state, Some(Instant::now() + timeout), None)
+    }
+
+    /// Wait for the callback of the flow with the given state, emitting waiting
+    /// events instead of timing out
+    ///
+    /// # Errors
+    ///
+    /// Same as [`wait`](Self::wait), and [`AnthropicAuthError::FlowExpired`] if the
+    /// keep-alive's `max_wait` passed or its handler stopped the wait
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::{BlockingCallbackServer, CallbackServerConfig, KeepAlive};
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let server = BlockingCallbackServer::bind(&CallbackServerConfig::default())?;
+    /// let keep_alive = KeepAlive::new(Duration::from_secs(60));
+    /// let callback = server.wait_keep_alive("state", &keep_alive)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_keep_alive(
+        &self,
+        expected_state: &str,
+        keep_alive: &KeepAlive,
+    ) -> Result<CallbackData> {
+        let started = Instant::now();
+        self.wait_until(
+            expected_state,
+            keep_alive.deadline(started),
+            Some((keep_alive, started)),
+        )
+    }
+
+    fn wait_until(
+        &self,
+        expected_state: &str,
+        deadline: Option<Instant>,
+        keep_alive: Option<(&KeepAlive, Instant)>,
+    ) -> Result<CallbackData> {
+        // Each connection is served on its own thread, so an idle one can't hold up the rest
+        let (tx, rx) = mpsc::channel();
+        let active = Arc::new(AtomicUsize::new(0));
+        let mut next_ping = keep_alive.map(|(keep_alive, started)| started + keep_alive.interval());
+        loop {
+            if let Ok(result) = rx.try_recv() {
+                return result;
+            }
+            let now = Instant::now();
+            if deadline.is_some_and(|deadline| now >= deadline) {
+                return Err(AnthropicAuthError::FlowExpired);
+            }
+            if let (Some((keep_alive, started)), Some(ping)) = (keep_alive, next_ping) {
+                if now >= ping {
+                    if !keep_alive.report(started) {
+                        return Err(AnthropicAuthError::FlowExpired);
+                    }
+                    next_ping = Some(now + keep_alive.interval());
+                }
+            }
+
+            if active.load(Ordering::Acquire) >= MAX_CONNECTIONS {
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            match self.listener.accept() {
+                Ok((stream, _)) => {
+                    let tx = tx.clone();
+                    let config = self.config.clone();
+                    let expected_state = expected_state.to_string();
+                    let active = active.clone();
+                    active.fetch_add(1, Ordering::AcqRel);
+                    std::thread::spawn(move || {
+                        let result = serve_connection(stream, &config, &expected_state);
+                        active.fetch_sub(1, Ordering::AcqRel);
+                        if let Some(result) = result {
+                            let _ = tx.send(result);
+                        }
+                    });
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(AnthropicAuthError::CallbackServer(e.to_string())),
+            }
+        }
+    }
+}
+
+/// Run a local OAuth callback server without tokio
+///
+/// Blocking counterpart of `run_callback_server`: listens on `127.0.0.1:port`
+/// and returns the first callback for the flow. Prefer
+/// [`BlockingCallbackServer`] to bind before opening the browser or to set a timeout.
+///
+/// **Note:** Only available when the `callback-server-blocking` feature is enabled.
+///
+/// # Errors
+///
+/// Returns an error if the server fails to start or an OAuth error carrying the
+/// flow's state is received
+pub fn run_callback_server_blocking(port: u16, expected_state: &str) -> Result<CallbackData> {
+    run_callback_server_blocking_with(&CallbackServerConfig::with_port(port), expected_state)
+}
+
+/// Run a local OAuth callback server without tokio, with a custom bind address and path
+///
+/// Same as [`run_callback_server_blocking`], but binds to `config.addr` and serves
+/// the callback on `config.path`.
+///
+/// # Errors
+///
+/// Same as [`run_callback_server_blocking`]
+pub fn run_callback_server_blocking_with(
+    config: &CallbackServerConfig,
+    expected_state: &str,
+) -> Result<CallbackData> {
+    BlockingCallbackServer::bind(config)?.wait(expected_state)
+}
+
+/// Answer one connection, returning the flow's result if it was the callback
+fn serve_connection(
+    mut stream: TcpStream,
+    config: &CallbackServerConfig,
+    expected_state: &str,
+) -> Option<Result<CallbackData>> {
+    // Accepted sockets may inherit the listener's non-blocking mode
+    stream.set_nonblocking(false).ok()?;
+    stream.set_read_timeout(Some(READ_TIMEOUT)).ok()?;
+
+    let Some((target, headers)) = read_request_head(&stream) else {
+        respond(&mut stream, "400 Bad Request", "");
+        return None;
+    };
+    let Some(target) = target.strip_prefix("GET ") else {
+        respond(&mut stream, "405 Method Not Allowed", "");
+        return None;
+    };
+    let (request_path, query) = target.split_once('?').unwrap_or((target, ""));
+    if !config
+        .callback_paths()
+        .iter()
+        .any(|path| path == request_path)
+    {
+        respond(&mut stream, "404 Not Found", "");
+        return None;
+    }
+    let header = |name: &str| {
+        headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    };
+    if !config.accepts_forwarded(request_path, header) {
+        respond(&mut stream, "421 Misdirected Request", MISDIRECTED_PAGE);
+        return None;
+    }
+    if !config.accepts_request(header) {
+        respond(&mut stream, "403 Forbidden", FORBIDDEN_PAGE);
+        return None;
+    }
+
+    let params = parse_query(query);
+    let state_matched = params
+        .state
+        .as_deref()
+        .is_some_and(|received| states_match(received, expected_state));
+    if !state_matched {
+        respond(&mut stream, "200 OK", unmatched_page(&params));
+        return None;
+    }
+    let (result, page) = callback_outcome(params);
+    respond(&mut stream, "200 OK", &page);
+    Some(result)
+}
+
+/// Read the request head, returning the method and target (e.g. `GET /callback?code=..`)
+/// and the headers
+fn read_request_head(stream: &TcpStream) -> Option<(String, Vec<(String, String)>)> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_HEAD));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+
+    // Read all headers, also so closing the socket doesn't reset the connection
+    let mut headers = Vec::new();
+    let mut header = String::new();
+    loop {
+        header.clear();
+        match reader.read_line(&mut header) {
+            Ok(0) => return None,
+            Ok(_) if header.trim_end().is_empty() => break,
+            Ok(_) => {
+                if let Some((name, value)) = header.split_once(':') {
+                    headers.push((name.trim().to_string(), value.trim().to_string()));
+                }
+            }
+            Err(_) => return None,
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target, version) = (parts.next()?, parts.next()?, parts.next()?);
+    if !version.starts_with("HTTP/1.") {
+        return None;
+    }
+    Some((format!("{} {}", method, target), headers))
+}
+
+/// Parse the callback parameters from a query string, keeping the first of each
+fn parse_query(query: &str) -> CallbackQuery {
+    let mut params = CallbackQuery::default();
+    for (name, value) in url::form_urlencoded::parse(query.as_bytes()) {
+        let field = match name.as_ref() {
+            "code" => &mut params.code,
+            "state" => &mut params.state,
+            "error" => &mut params.error,
+            _ => continue,
+        };
+        field.get_or_insert_with(|| value.into_owned());
+    }
+    params
+}
+
+fn respond(stream: &mut TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    );
+    let _ = stream
+        .write_all(response.as_bytes())
+        .and_then(|()| stream.flush());
+}
@@ -0,0 +1,247 @@
+use std::fmt;
+
+use crate::{CsrfState, NoEncryption, OAuthConfig, OAuthMode, PendingFlow, PkceVerifier, TokenSet};
+
+/// Outcome of a single smoke check
+#[derive(Debug, Clone)]
+pub struct SmokeCheck {
+    /// Short name of the check
+    pub name: &'static str,
+    /// `Err` holds a description of what went wrong
+    pub result: std::result::Result<(), String>,
+}
+
+/// Results of [`run_smoke_tests`]
+#[derive(Debug, Clone, Default)]
+pub struct SmokeReport {
+    /// Individual check results, in execution order
+    pub checks: Vec<SmokeCheck>,
+}
+
+impl SmokeReport {
+    /// Check whether every check passed
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.result.is_ok())
+    }
+
+    /// Get the checks that failed
+    pub fn failures(&self) -> impl Iterator<Item = &SmokeCheck> {
+        self.checks.iter().filter(|check| check.result.is_err())
+    }
+
+    fn record(&mut self, name: &'static str, result: std::result::Result<(), String>) {
+        self.checks.push(SmokeCheck { name, result });
+    }
+}
+
+impl fmt::Display for SmokeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            match &check.result {
+                Ok(()) => writeln!(f, "ok   {}", check.name)?,
+                Err(e) => writeln!(f, "FAIL {}: {}", check.name, e)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Run offline self-tests of this build
+///
+/// Exercises flow start and authorization URL construction, input validation, and
+/// serialization/encryption round-trips without touching the network, so packagers
+/// can verify a build (feature set, TLS backend, target) actually works.
+///
+/// **Note:** Only available when the `smoke` feature is enabled.
+///
+/// # Example
+///
+/// ```
+/// let report = anthropic_auth::run_smoke_tests();
+/// print!("{}", report);
+/// assert!(report.passed());
+/// ```
+pub fn run_smoke_tests() -> SmokeReport {
+    let mut report = SmokeReport::default();
+
+    report.record("config builder", check_config_builder());
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+    {
+        report.record("authorization url", check_authorization_url());
+        report.record("invalid default header", check_invalid_header());
+    }
+    report.record("token validation", check_token_validation());
+    report.record("token serialization", check_token_round_trip());
+    report.record("pending flow round-trip", check_pending_flow());
+
+    report
+}
+
+fn ensure(condition: bool, message: &str) -> std::result::Result<(), String> {
+    if condition {
+        Ok(())
+    } else {
+        Err(message.to_string())
+    }
+}
+
+fn sample_tokens() -> TokenSet {
+    TokenSet {
+        access_token: "smoke-access-token".to_string(),
+        refresh_token: "smoke-refresh-token".to_string(),
+        expires_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            + 3600,
+        scope: Some("user:inference".to_string()),
+        token_type: Some("Bearer".to_string()),
+        organization: None,
+        obtained_at: None,
+        account_id: None,
+    }
+}
+
+fn check_config_builder() -> std::result::Result<(), String> {
+    let config = OAuthConfig::builder()
+        .client_id("smoke-client")
+        .redirect_port(4242)
+        .build();
+    ensure(config.client_id == "smoke-client", "client_id not applied")?;
+    ensure(
+        config.redirect_uri() == "http://localhost:4242/callback",
+        "redirect_port not applied",
+    )
+}
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+fn new_client(config: OAuthConfig) -> crate::Result<impl StartFlow> {
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+    return crate::OAuthClient::new(config);
+    #[cfg(not(any(feature = "blocking", feature = "blocking-ureq")))]
+    return crate::AsyncOAuthClient::new(config);
+}
+
+/// Common surface of the sync and async clients used by the smoke checks
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+trait StartFlow {
+    fn start(&self, mode: OAuthMode) -> crate::Result<crate::OAuthFlow>;
+}
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+impl StartFlow for crate::OAuthClient {
+    fn start(&self, mode: OAuthMode) -> crate::Result<crate::OAuthFlow> {
+        self.start_flow(mode)
+    }
+}
+
+#[cfg(all(
+    feature = "async",
+    not(any(feature = "blocking", feature = "blocking-ureq"))
+))]
+impl StartFlow for crate::AsyncOAuthClient {
+    fn start(&self, mode: OAuthMode) -> crate::Result<crate::OAuthFlow> {
+        self.start_flow(mode)
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+fn check_authorization_url() -> std::result::Result<(), String> {
+    let client = new_client(OAuthConfig::default()).map_err(|e| e.to_string())?;
+
+    for mode in [OAuthMode::Max, OAuthMode::Console] {
+        let flow = client.start(mode).map_err(|e| e.to_string())?;
+        let url = url::Url::parse(&flow.authorization_url).map_err(|e| e.to_string())?;
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+
+        ensure(url.scheme() == "https", "authorization url is not https")?;
+        ensure(
+            param("state").as_deref() == Some(flow.state.as_str()),
+            "state mismatch",
+        )?;
+        ensure(
+            param("code_challenge_method").as_deref() == Some("S256"),
+            "PKCE method is not S256",
+        )?;
+        ensure(param("code_challenge").is_some(), "missing code challenge")?;
+        ensure(
+            param("code_challenge").as_deref() == Some(flow.verifier.challenge().as_str()),
+            "code challenge doesn't match the verifier",
+        )?;
+    }
+
+    let first = client.start(OAuthMode::Max).map_err(|e| e.to_string())?;
+    let second = client.start(OAuthMode::Max).map_err(|e| e.to_string())?;
+    ensure(first.state != second.state, "state is not random")?;
+    ensure(first.verifier != second.verifier, "verifier is not random")
+}
+
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+fn check_invalid_header() -> std::result::Result<(), String> {
+    let config = OAuthConfig::builder()
+        .default_header("bad header", "value")
+        .build();
+    ensure(
+        new_client(config).is_err(),
+        "invalid header name was accepted",
+    )
+}
+
+fn check_token_validation() -> std::result::Result<(), String> {
+    let tokens = sample_tokens();
+    tokens.validate().map_err(str::to_string)?;
+    ensure(!tokens.is_expired(), "fresh token reported as expired")?;
+
+    let mut expired = sample_tokens();
+    expired.expires_at = 1;
+    ensure(expired.is_expired(), "expired token reported as valid")?;
+
+    let mut empty = sample_tokens();
+    empty.access_token.clear();
+    ensure(empty.validate().is_err(), "empty access token accepted")
+}
+
+fn check_token_round_trip() -> std::result::Result<(), String> {
+    let tokens = sample_tokens();
+    let bytes = tokens
+        .to_encrypted(&NoEncryption)
+        .map_err(|e| e.to_string())?;
+    let restored = TokenSet::from_encrypted(&bytes, &NoEncryption).map_err(|e| e.to_string())?;
+    ensure(
+        restored.access_token == tokens.access_token
+            && restored.refresh_token == tokens.refresh_token
+            && restored.expires_at == tokens.expires_at
+            && restored.scope == tokens.scope,
+        "token set changed during round-trip",
+    )
+}
+
+fn check_pending_flow() -> std::result::Result<(), String> {
+    let pending = PendingFlow {
+        authorization_url: "https://claude.ai/oauth/authorize".to_string(),
+        verifier: PkceVerifier::new("v".repeat(43)).map_err(|e| e.to_string())?,
+        state: CsrfState::new("smoke-state").map_err(|e| e.to_string())?,
+        mode: OAuthMode::Console,
+        redirect_uri: "http://localhost:1455/callback".to_string(),
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs(),
+    };
+
+    let json = serde_json::to_string(&pending).map_err(|e| e.to_string())?;
+    let restored: PendingFlow = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    let flow = restored.resume().map_err(|e| e.to_string())?;
+    ensure(
+        flow.state == "smoke-state" && flow.mode == OAuthMode::Console,
+        "pending flow changed during round-trip",
+    )?;
+
+    let mut stale = pending;
+    stale.created_at = 0;
+    ensure(stale.resume().is_err(), "stale flow was resumed")
+}
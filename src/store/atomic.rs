@@ -0,0 +1,51 @@
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{AnthropicAuthError, Result};
+
+/// Distinguishes the temporary files of concurrent writes within one process
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Replace the file at `path` with `bytes`, readable only by its owner (on Unix)
+///
+/// The bytes go to a temporary file in the same directory, which is synced and
+/// then renamed over `path`, so a crash or a concurrent reader never sees a
+/// half-written file. Missing parent directories are created.
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| storage_error(dir, e))?;
+    }
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(format!(
+        ".tmp.{}.{}",
+        std::process::id(),
+        TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let mut options = OpenOptions::new();
+    options.create(true).truncate(true).write(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let written = options.open(&tmp_path).and_then(|mut file| {
+        file.write_all(bytes)?;
+        file.sync_all()
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(storage_error(&tmp_path, e));
+    }
+
+    fs::rename(&tmp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        storage_error(path, e)
+    })
+}
+
+pub(crate) fn storage_error(path: &Path, e: std::io::Error) -> AnthropicAuthError {
+    AnthropicAuthError::Storage(format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::{ApiKeyRecord, MemoryTokenStore, TokenStore};
+use crate::{AnthropicAuthError, Result, TokenSet};
+
+/// Token store that falls back to memory while its primary store is locked
+///
+/// Wraps a persistent store such as the OS keyring. While the primary store
+/// reports [`AnthropicAuthError::StorageLocked`], tokens are kept in a
+/// session-only [`MemoryTokenStore`] instead of failing, so a locked keyring
+/// doesn't look like lost credentials. Once the primary store is reachable again,
+/// tokens saved during the session are written back to it.
+///
+/// Other storage errors are returned as usual.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{SessionFallbackStore, TokenStore};
+///
+/// # fn example(keyring_store: impl TokenStore) -> Result<(), Box<dyn std::error::Error>> {
+/// // E.g. a `KeyringTokenStore` (`keyring-store` feature)
+/// let store = SessionFallbackStore::new(keyring_store);
+///
+/// let tokens = store.load()?;
+/// if store.is_degraded() {
+///     eprintln!("Keyring is locked - tokens will only be kept for this session");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct SessionFallbackStore<S> {
+    primary: S,
+    session: MemoryTokenStore,
+    degraded: AtomicBool,
+}
+
+impl<S: TokenStore> SessionFallbackStore<S> {
+    /// Wrap `primary` with a session-only fallback
+    pub fn new(primary: S) -> Self {
+        Self {
+            primary,
+            session: MemoryTokenStore::new(),
+            degraded: AtomicBool::new(false),
+        }
+    }
+
+    /// Get the wrapped primary store
+    pub fn primary(&self) -> &S {
+        &self.primary
+    }
+
+    /// Whether the last operation fell back to memory because the primary store was locked
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// Run `op` on the primary store, or `fallback` on the session store if it is locked
+    fn with_fallback<T>(
+        &self,
+        op: impl FnOnce(&S) -> Result<T>,
+        fallback: impl FnOnce(&MemoryTokenStore) -> Result<T>,
+    ) -> Result<T> {
+        match op(&self.primary) {
+            Err(AnthropicAuthError::StorageLocked(_)) => {
+                self.degraded.store(true, Ordering::Relaxed);
+                fallback(&self.session)
+            }
+            result => {
+                self.degraded.store(false, Ordering::Relaxed);
+                result
+            }
+        }
+    }
+}
+
+impl<S: TokenStore> TokenStore for SessionFallbackStore<S> {
+    fn load(&self) -> Result<Option<TokenSet>> {
+        let stored = self.with_fallback(|primary| primary.load(), |session| session.load())?;
+        if self.is_degraded() {
+            return Ok(stored);
+        }
+
+        // Tokens saved while the primary store was locked are newer; write them back
+        match self.session.load()? {
+            Some(tokens) => {
+                self.primary.save(&tokens)?;
+                self.session.clear()?;
+                Ok(Some(tokens))
+            }
+            None => Ok(stored),
+        }
+    }
+
+    fn save(&self, tokens: &TokenSet) -> Result<()> {
+        self.with_fallback(
+            |primary| primary.save(tokens),
+            |session| session.save(tokens),
+        )?;
+        if !self.is_degraded() {
+            self.session.clear()?;
+        }
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        // Tokens left in a locked primary store can't be removed; report that
+        self.session.clear()?;
+        self.primary.clear()
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        self.with_fallback(|primary| primary.api_keys(), |session| session.api_keys())
+    }
+
+    fn record_api_key(&self, record: &ApiKeyRecord) -> Result<()> {
+        self.with_fallback(
+            |primary| primary.record_api_key(record),
+            |session| session.record_api_key(record),
+        )
+    }
+
+    fn forget_api_key(&self, fingerprint: &str) -> Result<()> {
+        self.with_fallback(
+            |primary| primary.forget_api_key(fingerprint),
+            |session| session.forget_api_key(fingerprint),
+        )
+    }
+}
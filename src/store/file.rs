@@ -0,0 +1,240 @@
+use fs4::fs_std::FileExt;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::{storage_error, write_atomic, ApiKeyRecord, TokenStore};
+use crate::{AnthropicAuthError, Encryptor, NoEncryption, OAuthConfig, Result, TokenSet};
+
+/// Token store backed by a single file, safe to share between processes
+///
+/// Several processes (e.g. concurrent CLI invocations) can use the same file:
+///
+/// - Writes go to a temporary file that is atomically renamed over the target, so
+///   readers never see a half-written file.
+/// - Read-modify-write cycles are serialized with an advisory lock on a sidecar
+///   `<file>.lock` file.
+/// - [`refresh_locked`](Self::refresh_locked) reloads the file under the lock before
+///   refreshing, so a token already refreshed by a sibling process is reused instead
+///   of being refreshed again with a now-revoked rotated refresh token.
+///
+/// Tokens are written with [`NoEncryption`] unless an [`Encryptor`] is supplied. On
+/// Unix the file is created with mode `0600`. Recorded API keys are kept as JSON in
+/// a sidecar `<file>.keys` file.
+///
+/// **Note:** Only available when the `file-store` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{FileTokenStore, OAuthClient, OAuthConfig, TokenStore};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = OAuthClient::new(OAuthConfig::default())?;
+/// let store = FileTokenStore::new("/home/me/.config/my-app/tokens.json");
+///
+/// // Refreshes (once, across processes) only if the stored token is expired
+/// let tokens = store.refresh_locked_with(client.config(), |old| {
+///     client.refresh_token_with_fallback(old)
+/// })?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct FileTokenStore {
+    path: PathBuf,
+    encryptor: Arc<dyn Encryptor>,
+}
+
+impl std::fmt::Debug for FileTokenStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FileTokenStore")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FileTokenStore {
+    /// Store tokens as plaintext JSON at `path`
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self::with_encryptor(path, Arc::new(NoEncryption))
+    }
+
+    /// Store tokens at `path`, encrypted with `encryptor`
+    pub fn with_encryptor(path: impl Into<PathBuf>, encryptor: Arc<dyn Encryptor>) -> Self {
+        Self {
+            path: path.into(),
+            encryptor,
+        }
+    }
+
+    /// Store tokens as plaintext JSON at `<dir>/<profile>.json`, one file per
+    /// [profile](crate::Profiles)
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidConfig`] unless `profile` is a plain file
+    /// name made of ASCII letters, digits, `-`, `_` and `.` that doesn't start with `.`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use anthropic_auth::FileTokenStore;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let store = FileTokenStore::for_profile("/home/me/.config/my-app", "staging")?;
+    /// assert!(store.path().ends_with("staging.json"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn for_profile(dir: impl AsRef<Path>, profile: &str) -> Result<Self> {
+        let valid = !profile.is_empty()
+            && !profile.starts_with('.')
+            && profile
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+        if !valid {
+            return Err(AnthropicAuthError::InvalidConfig(format!(
+                "Profile name '{}' cannot be used as a file name",
+                profile
+            )));
+        }
+        Ok(Self::new(dir.as_ref().join(format!("{}.json", profile))))
+    }
+
+    /// Get the path of the token file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Refresh the stored tokens if they are expired, coordinating with other processes
+    ///
+    /// Takes the file lock, reloads the tokens from disk, and only calls `refresh`
+    /// if the reloaded tokens are expired (see [`TokenSet::is_expired`]). The
+    /// refreshed tokens are saved before the lock is released.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::TokenExpired`] if no tokens are stored, a
+    /// storage error, or the error returned by `refresh`
+    pub fn refresh_locked<F>(&self, refresh: F) -> Result<TokenSet>
+    where
+        F: FnOnce(&TokenSet) -> Result<TokenSet>,
+    {
+        self.refresh_locked_with(&OAuthConfig::default(), refresh)
+    }
+
+    /// Like [`refresh_locked`](Self::refresh_locked), judging expiry by
+    /// [`OAuthConfig::is_expired`] so the configured clock and expiry buffer apply
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::TokenExpired`] if no tokens are stored, a
+    /// storage error, or the error returned by `refresh`
+    pub fn refresh_locked_with<F>(&self, config: &OAuthConfig, refresh: F) -> Result<TokenSet>
+    where
+        F: FnOnce(&TokenSet) -> Result<TokenSet>,
+    {
+        let _lock = self.lock()?;
+
+        let current = self.read()?.ok_or(AnthropicAuthError::TokenExpired)?;
+        if !config.is_expired(&current) {
+            // Still valid, or already refreshed by another process
+            return Ok(current);
+        }
+
+        let fresh = refresh(&current)?;
+        self.write(&fresh)?;
+        Ok(fresh)
+    }
+
+    /// Take the advisory lock; it is released when the returned file is dropped
+    fn lock(&self) -> Result<File> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| storage_error(parent, e))?;
+        }
+
+        let lock_path = self.sidecar("lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&lock_path)
+            .map_err(|e| storage_error(&lock_path, e))?;
+        FileExt::lock_exclusive(&file).map_err(|e| storage_error(&lock_path, e))?;
+        Ok(file)
+    }
+
+    fn read(&self) -> Result<Option<TokenSet>> {
+        match fs::read(&self.path) {
+            Ok(bytes) => Ok(Some(TokenSet::from_encrypted(&bytes, &*self.encryptor)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(storage_error(&self.path, e)),
+        }
+    }
+
+    fn write(&self, tokens: &TokenSet) -> Result<()> {
+        let bytes = tokens.to_encrypted(&*self.encryptor)?;
+        write_atomic(&self.path, &bytes)
+    }
+
+    fn read_api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        let path = self.sidecar("keys");
+        match fs::read(&path) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(storage_error(&path, e)),
+        }
+    }
+
+    fn write_api_keys(&self, records: &[ApiKeyRecord]) -> Result<()> {
+        let bytes = serde_json::to_vec_pretty(records)?;
+        write_atomic(&self.sidecar("keys"), &bytes)
+    }
+
+    fn sidecar(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(suffix);
+        self.path.with_file_name(name)
+    }
+}
+
+impl TokenStore for FileTokenStore {
+    fn load(&self) -> Result<Option<TokenSet>> {
+        self.read()
+    }
+
+    fn save(&self, tokens: &TokenSet) -> Result<()> {
+        let _lock = self.lock()?;
+        self.write(tokens)
+    }
+
+    fn clear(&self) -> Result<()> {
+        let _lock = self.lock()?;
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(storage_error(&self.path, e)),
+        }
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        self.read_api_keys()
+    }
+
+    fn record_api_key(&self, record: &ApiKeyRecord) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut records = self.read_api_keys()?;
+        records.retain(|existing| existing.fingerprint != record.fingerprint);
+        records.push(record.clone());
+        self.write_api_keys(&records)
+    }
+
+    fn forget_api_key(&self, fingerprint: &str) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut records = self.read_api_keys()?;
+        records.retain(|existing| existing.fingerprint != fingerprint);
+        self.write_api_keys(&records)
+    }
+}
@@ -0,0 +1,92 @@
+use keyring::Entry;
+
+use super::TokenStore;
+use crate::{AnthropicAuthError, Result, TokenSet};
+
+/// Token store backed by the OS keyring (macOS Keychain, Windows Credential
+/// Manager, Linux kernel keyring)
+///
+/// Tokens are stored as JSON under the given service and user name. A keyring
+/// that is present but locked, or to which access was denied, is reported as
+/// [`AnthropicAuthError::StorageLocked`] rather than a generic storage error;
+/// retry with [`retry_after_unlock`](crate::retry_after_unlock) or wrap the store in
+/// a [`SessionFallbackStore`](crate::SessionFallbackStore).
+///
+/// On Linux the kernel keyring does not survive a reboot. For the persistent
+/// Secret Service, also enable the `sync-secret-service` feature of the
+/// `keyring` crate in your application.
+///
+/// **Note:** Only available when the `keyring-store` feature is enabled.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{KeyringTokenStore, TokenStore};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let store = KeyringTokenStore::new("my-app", "default");
+/// if let Some(tokens) = store.load()? {
+///     println!("Expires in {:?}", tokens.expires_in());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct KeyringTokenStore {
+    service: String,
+    user: String,
+}
+
+impl KeyringTokenStore {
+    /// Store tokens under `service` (usually your application name) and `user`
+    /// (e.g. a profile name)
+    pub fn new(service: impl Into<String>, user: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            user: user.into(),
+        }
+    }
+
+    /// Get the keyring service name
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    /// Get the keyring user name
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    fn entry(&self) -> Result<Entry> {
+        Entry::new(&self.service, &self.user).map_err(keyring_error)
+    }
+}
+
+impl TokenStore for KeyringTokenStore {
+    fn load(&self) -> Result<Option<TokenSet>> {
+        match self.entry()?.get_password() {
+            Ok(json) => Ok(Some(TokenSet::from_json(&json)?)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(keyring_error(e)),
+        }
+    }
+
+    fn save(&self, tokens: &TokenSet) -> Result<()> {
+        let json = serde_json::to_string(&tokens.versioned())?;
+        self.entry()?.set_password(&json).map_err(keyring_error)
+    }
+
+    fn clear(&self) -> Result<()> {
+        match self.entry()?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(keyring_error(e)),
+        }
+    }
+}
+
+pub(crate) fn keyring_error(e: keyring::Error) -> AnthropicAuthError {
+    match e {
+        keyring::Error::NoStorageAccess(e) => AnthropicAuthError::StorageLocked(e.to_string()),
+        e => AnthropicAuthError::Storage(format!("Keyring: {}", e)),
+    }
+}
@@ -0,0 +1,66 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use super::{ApiKeyRecord, TokenStore};
+use crate::{Result, TokenSet};
+
+/// Session-only token store that keeps everything in memory
+///
+/// Nothing survives the process. Useful as a fallback when persistent storage is
+/// unavailable (see [`SessionFallbackStore`](crate::SessionFallbackStore)) and in tests.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{MemoryTokenStore, TokenStore};
+///
+/// let store = MemoryTokenStore::new();
+/// assert!(store.load().unwrap().is_none());
+/// ```
+#[derive(Debug, Default)]
+pub struct MemoryTokenStore {
+    tokens: Mutex<Option<TokenSet>>,
+    api_keys: Mutex<Vec<ApiKeyRecord>>,
+}
+
+impl MemoryTokenStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl TokenStore for MemoryTokenStore {
+    fn load(&self) -> Result<Option<TokenSet>> {
+        Ok(lock(&self.tokens).clone())
+    }
+
+    fn save(&self, tokens: &TokenSet) -> Result<()> {
+        *lock(&self.tokens) = Some(tokens.clone());
+        Ok(())
+    }
+
+    fn clear(&self) -> Result<()> {
+        *lock(&self.tokens) = None;
+        Ok(())
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        Ok(lock(&self.api_keys).clone())
+    }
+
+    fn record_api_key(&self, record: &ApiKeyRecord) -> Result<()> {
+        let mut api_keys = lock(&self.api_keys);
+        api_keys.retain(|existing| existing.fingerprint != record.fingerprint);
+        api_keys.push(record.clone());
+        Ok(())
+    }
+
+    fn forget_api_key(&self, fingerprint: &str) -> Result<()> {
+        lock(&self.api_keys).retain(|record| record.fingerprint != fingerprint);
+        Ok(())
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
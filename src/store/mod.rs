@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::{AnthropicAuthError, ApiKeyOptions, Result, TokenSet};
+
+mod atomic;
+mod fallback;
+mod memory;
+pub(crate) use atomic::{storage_error, write_atomic};
+pub use fallback::SessionFallbackStore;
+pub use memory::MemoryTokenStore;
+
+#[cfg(feature = "file-store")]
+mod file;
+#[cfg(feature = "file-store")]
+pub use file::FileTokenStore;
+
+#[cfg(feature = "keyring-store")]
+mod keyring;
+#[cfg(feature = "keyring-store")]
+pub(crate) use self::keyring::keyring_error;
+#[cfg(feature = "keyring-store")]
+pub use self::keyring::KeyringTokenStore;
+
+#[cfg(all(feature = "web-storage", target_arch = "wasm32"))]
+mod web;
+#[cfg(all(feature = "web-storage", target_arch = "wasm32"))]
+pub use web::{WebStorage, WebStorageTokenStore};
+
+/// Persistence backend for a single set of tokens
+///
+/// Implement this to plug token persistence into the rest of the crate. Backends
+/// should treat the tokens as secrets (restrictive permissions, optional
+/// [`Encryptor`](crate::Encryptor)).
+pub trait TokenStore: Send + Sync {
+    /// Load the stored tokens, or `None` if nothing is stored
+    fn load(&self) -> Result<Option<TokenSet>>;
+
+    /// Store the tokens, replacing any previous ones
+    fn save(&self, tokens: &TokenSet) -> Result<()>;
+
+    /// Remove the stored tokens
+    ///
+    /// Recorded API keys are kept.
+    fn clear(&self) -> Result<()>;
+
+    /// List the API keys recorded with [`record_api_key`](Self::record_api_key)
+    ///
+    /// Stores without an API key audit return an empty list.
+    fn api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        Ok(Vec::new())
+    }
+
+    /// Record an API key created by this application
+    ///
+    /// Stores without an API key audit ignore the record.
+    fn record_api_key(&self, record: &ApiKeyRecord) -> Result<()> {
+        let _ = record;
+        Ok(())
+    }
+
+    /// Remove the record with the given fingerprint (e.g. after revoking the key)
+    fn forget_api_key(&self, fingerprint: &str) -> Result<()> {
+        let _ = fingerprint;
+        Ok(())
+    }
+}
+
+/// How often [`retry_after_unlock`] retries a locked store
+const UNLOCK_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Retry a storage operation until the store is unlocked or `timeout` elapses
+///
+/// Keeps calling `op` while it fails with [`AnthropicAuthError::StorageLocked`],
+/// e.g. after asking the user to unlock their keyring. Any other result is
+/// returned immediately.
+///
+/// # Errors
+///
+/// Returns the last [`AnthropicAuthError::StorageLocked`] if the store is still
+/// locked after `timeout`, or the error of `op`
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{retry_after_unlock, AnthropicAuthError, TokenStore};
+/// use std::time::Duration;
+///
+/// # fn example(store: &dyn TokenStore) -> anthropic_auth::Result<()> {
+/// let tokens = match store.load() {
+///     Err(AnthropicAuthError::StorageLocked(_)) => {
+///         eprintln!("Please unlock your keyring...");
+///         retry_after_unlock(Duration::from_secs(60), || store.load())?
+///     }
+///     result => result?,
+/// };
+/// # Ok(())
+/// # }
+/// ```
+pub fn retry_after_unlock<T>(timeout: Duration, mut op: impl FnMut() -> Result<T>) -> Result<T> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        match op() {
+            Err(AnthropicAuthError::StorageLocked(reason)) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    return Err(AnthropicAuthError::StorageLocked(reason));
+                }
+                std::thread::sleep(remaining.min(UNLOCK_POLL_INTERVAL));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Audit record of an API key created by this application
+///
+/// Only a fingerprint of the key is kept, never the key itself, so the audit can
+/// be used to find the keys this application owns for later cleanup or rotation.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{ApiKeyOptions, ApiKeyRecord, OAuthClient, OAuthConfig, TokenStore};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client = OAuthClient::new(OAuthConfig::default())?;
+/// # let access_token = "access_token";
+/// # fn store() -> Box<dyn TokenStore> { unimplemented!() }
+/// let store = store();
+/// let options = ApiKeyOptions {
+///     name: Some("ci-runner".to_string()),
+///     ..Default::default()
+/// };
+///
+/// let api_key = client.create_api_key_with(access_token, &options)?;
+/// store.record_api_key(&ApiKeyRecord::new(&api_key, &options))?;
+///
+/// for record in store.api_keys()? {
+///     println!("{} {:?} created at {}", record.fingerprint, record.name, record.created_at);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyRecord {
+    /// Server-side key ID, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Display name the key was created with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Workspace the key was created in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+    /// Short SHA-256 fingerprint of the key (see [`ApiKeyRecord::fingerprint_of`])
+    pub fingerprint: String,
+    /// Unix timestamp (seconds) when the key was created
+    pub created_at: u64,
+}
+
+impl ApiKeyRecord {
+    /// Create a record for a key that was just created with `options`
+    pub fn new(api_key: &str, options: &ApiKeyOptions) -> Self {
+        Self {
+            id: None,
+            name: options.name.clone(),
+            workspace_id: options.workspace_id.clone(),
+            fingerprint: Self::fingerprint_of(api_key),
+            created_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        }
+    }
+
+    /// Compute the fingerprint of an API key
+    ///
+    /// The first 16 hex characters of the key's SHA-256 hash.
+    pub fn fingerprint_of(api_key: &str) -> String {
+        crate::types::fingerprint_hex(api_key)
+    }
+
+    /// Check whether this record belongs to `api_key`
+    pub fn matches(&self, api_key: &str) -> bool {
+        self.fingerprint == Self::fingerprint_of(api_key)
+    }
+}
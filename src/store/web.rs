@@ -0,0 +1,172 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::sync::Arc;
+
+use super::{ApiKeyRecord, TokenStore};
+use crate::{AnthropicAuthError, Encryptor, NoEncryption, Result, TokenSet};
+
+/// Which browser storage area a [`WebStorageTokenStore`] uses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WebStorage {
+    /// `window.localStorage`: survives closing the browser
+    #[default]
+    Local,
+    /// `window.sessionStorage`: cleared when the tab is closed
+    Session,
+}
+
+/// Token store backed by the browser's `localStorage` or `sessionStorage` (WASM)
+///
+/// Tokens are stored under a single key as base64, after passing through the
+/// configured [`Encryptor`] ([`NoEncryption`] by default). Recorded API keys are
+/// kept as JSON under `<key>.keys`.
+///
+/// Anything readable by scripts on the page can read this storage, so prefer
+/// [`WebStorage::Session`] and an `Encryptor` whose key isn't stored alongside
+/// the tokens.
+///
+/// WebCrypto (`SubtleCrypto`) is not supported: it only offers promise-based
+/// operations, while [`Encryptor`] and [`TokenStore`](crate::TokenStore) are
+/// synchronous. Encrypt with a cipher implemented in Rust and compiled to WASM
+/// instead.
+///
+/// **Note:** Only available on `wasm32` targets when the `web-storage` feature is enabled.
+///
+/// # Example
+///
+/// ```ignore
+/// use anthropic_auth::{TokenStore, WebStorage, WebStorageTokenStore};
+///
+/// let store = WebStorageTokenStore::new("my-app.tokens").storage(WebStorage::Session);
+/// if let Some(tokens) = store.load()? {
+///     // ...
+/// }
+/// ```
+#[derive(Clone)]
+pub struct WebStorageTokenStore {
+    key: String,
+    storage: WebStorage,
+    encryptor: Arc<dyn Encryptor>,
+}
+
+impl std::fmt::Debug for WebStorageTokenStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebStorageTokenStore")
+            .field("key", &self.key)
+            .field("storage", &self.storage)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebStorageTokenStore {
+    /// Store tokens in `localStorage` under `key`
+    pub fn new(key: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            storage: WebStorage::default(),
+            encryptor: Arc::new(NoEncryption),
+        }
+    }
+
+    /// Use another storage area
+    pub fn storage(mut self, storage: WebStorage) -> Self {
+        self.storage = storage;
+        self
+    }
+
+    /// Encrypt the tokens with `encryptor` before storing them
+    pub fn encryptor(mut self, encryptor: Arc<dyn Encryptor>) -> Self {
+        self.encryptor = encryptor;
+        self
+    }
+
+    /// Get the storage key of the tokens
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Get the storage area, which is unavailable e.g. in some private browsing modes
+    fn area(&self) -> Result<web_sys::Storage> {
+        let window = web_sys::window()
+            .ok_or_else(|| AnthropicAuthError::Storage("No browser window".to_string()))?;
+        let area = match self.storage {
+            WebStorage::Local => window.local_storage(),
+            WebStorage::Session => window.session_storage(),
+        };
+        area.ok().flatten().ok_or_else(|| {
+            AnthropicAuthError::Storage(format!("{:?} storage is unavailable", self.storage))
+        })
+    }
+
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        self.area()?
+            .get_item(key)
+            .map_err(|e| web_storage_error(key, e))
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<()> {
+        // Fails when the storage quota is exceeded
+        self.area()?
+            .set_item(key, value)
+            .map_err(|e| web_storage_error(key, e))
+    }
+
+    fn keys_key(&self) -> String {
+        format!("{}.keys", self.key)
+    }
+
+    fn read_api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        match self.get(&self.keys_key())? {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    fn write_api_keys(&self, records: &[ApiKeyRecord]) -> Result<()> {
+        self.set(&self.keys_key(), &serde_json::to_string(records)?)
+    }
+}
+
+impl TokenStore for WebStorageTokenStore {
+    fn load(&self) -> Result<Option<TokenSet>> {
+        match self.get(&self.key)? {
+            Some(encoded) => {
+                let bytes = STANDARD.decode(encoded)?;
+                Ok(Some(TokenSet::from_encrypted(&bytes, &*self.encryptor)?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn save(&self, tokens: &TokenSet) -> Result<()> {
+        let bytes = tokens.to_encrypted(&*self.encryptor)?;
+        self.set(&self.key, &STANDARD.encode(bytes))
+    }
+
+    fn clear(&self) -> Result<()> {
+        self.area()?
+            .remove_item(&self.key)
+            .map_err(|e| web_storage_error(&self.key, e))
+    }
+
+    fn api_keys(&self) -> Result<Vec<ApiKeyRecord>> {
+        self.read_api_keys()
+    }
+
+    fn record_api_key(&self, record: &ApiKeyRecord) -> Result<()> {
+        let mut records = self.read_api_keys()?;
+        records.retain(|existing| existing.fingerprint != record.fingerprint);
+        records.push(record.clone());
+        self.write_api_keys(&records)
+    }
+
+    fn forget_api_key(&self, fingerprint: &str) -> Result<()> {
+        let mut records = self.read_api_keys()?;
+        records.retain(|existing| existing.fingerprint != fingerprint);
+        self.write_api_keys(&records)
+    }
+}
+
+fn web_storage_error(key: &str, e: wasm_bindgen::JsValue) -> AnthropicAuthError {
+    AnthropicAuthError::Storage(format!("{}: {:?}", key, e))
+}
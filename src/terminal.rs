@@ -0,0 +1,148 @@
+use qrcode::render::unicode::Dense1x2;
+use qrcode::QrCode;
+use std::io::{BufRead, Write};
+
+use crate::{
+    AnthropicAuthError, AuthEvent, ExchangeRequest, OAuthClient, OAuthFlow, OAuthMode, Result,
+    TokenSet,
+};
+
+/// How many times the user may paste an invalid response before giving up
+const MAX_ATTEMPTS: usize = 3;
+
+/// Run the authorization flow interactively in the terminal (blocking)
+///
+/// Prints the authorization URL together with a QR code (so the URL can be opened
+/// on a phone when the terminal is an SSH session), then prompts for the
+/// `code#state` response. Responses with a missing code or a mismatched state are
+/// rejected with an explanation and the user is asked again, up to three times.
+///
+/// **Note:** Only available when the `terminal` feature is enabled.
+///
+/// # Arguments
+///
+/// * `client` - The client to run the flow with
+/// * `mode` - The OAuth mode (Max for subscription, Console for API key creation)
+///
+/// # Errors
+///
+/// Returns an error if stdin is closed, the user pastes invalid responses three
+/// times, or the code exchange fails for another reason
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{interactive_login, OAuthClient, OAuthConfig, OAuthMode};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = OAuthClient::new(OAuthConfig::default())?;
+/// let tokens = interactive_login(&client, OAuthMode::Max)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn interactive_login(client: &OAuthClient, mode: OAuthMode) -> Result<TokenSet> {
+    let result = client.start_flow(mode).and_then(|flow| {
+        client.config().emit(AuthEvent::FlowStarted {
+            url: flow.authorization_url.clone(),
+        });
+        run(
+            client,
+            &flow,
+            &mut std::io::stdin().lock(),
+            &mut std::io::stderr(),
+        )
+    });
+    client.config().emit(match &result {
+        Ok(_) => AuthEvent::TokensReceived,
+        Err(error) => AuthEvent::Error(error.to_string()),
+    });
+    result
+}
+
+fn run(
+    client: &OAuthClient,
+    flow: &OAuthFlow,
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+) -> Result<TokenSet> {
+    let io_error = |e: std::io::Error| AnthropicAuthError::Io(format!("Terminal error: {}", e));
+
+    writeln!(
+        output,
+        "Visit this URL to authorize:\n\n  {}\n",
+        flow.authorization_url
+    )
+    .map_err(io_error)?;
+    if let Some(qr) = render_qr(&flow.authorization_url) {
+        writeln!(
+            output,
+            "Or scan this QR code with another device:\n\n{}",
+            qr
+        )
+        .map_err(io_error)?;
+    }
+
+    client.config().emit(AuthEvent::WaitingForCallback);
+    for attempt in 1..=MAX_ATTEMPTS {
+        write!(output, "Paste the authorization code (code#state): ").map_err(io_error)?;
+        output.flush().map_err(io_error)?;
+
+        let mut line = String::new();
+        if input.read_line(&mut line).map_err(io_error)? == 0 {
+            return Err(AnthropicAuthError::OAuth(
+                "No authorization code entered".to_string(),
+            ));
+        }
+        let response = line.trim().trim_matches(|c| c == '"' || c == '\'');
+        client.config().emit(AuthEvent::CallbackReceived);
+
+        client.config().emit(AuthEvent::ExchangingCode);
+
+        match client.exchange(&ExchangeRequest::new(response, flow)) {
+            Ok(tokens) => return Ok(tokens),
+            Err(error) if attempt < MAX_ATTEMPTS && is_input_error(&error) => {
+                writeln!(
+                    output,
+                    "{}. Please copy the whole code and try again.",
+                    describe(&error)
+                )
+                .map_err(io_error)?;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    unreachable!("the last attempt always returns")
+}
+
+/// Whether the pasted response was wrong, as opposed to the exchange failing
+fn is_input_error(error: &AnthropicAuthError) -> bool {
+    match error {
+        AnthropicAuthError::InvalidAuthorizationCode => true,
+        AnthropicAuthError::OAuth(message) => message.starts_with("State mismatch"),
+        AnthropicAuthError::Http { status, .. } => *status == 400,
+        _ => false,
+    }
+}
+
+fn describe(error: &AnthropicAuthError) -> &'static str {
+    match error {
+        AnthropicAuthError::InvalidAuthorizationCode => {
+            "That doesn't look like an authorization code"
+        }
+        AnthropicAuthError::OAuth(_) => "That code belongs to a different login attempt",
+        _ => "The code was rejected",
+    }
+}
+
+/// Render `text` as a QR code with Unicode half blocks, or `None` if it is too long
+fn render_qr(text: &str) -> Option<String> {
+    let code = QrCode::new(text.as_bytes()).ok()?;
+    Some(
+        code.render::<Dense1x2>()
+            .dark_color(Dense1x2::Light)
+            .light_color(Dense1x2::Dark)
+            .quiet_zone(true)
+            .build(),
+    )
+}
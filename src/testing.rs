@@ -0,0 +1,656 @@
+//! Utilities for testing code built on this crate
+//!
+//! Enabled with the `testing` feature. Provides an in-process mock of Anthropic's
+//! OAuth endpoints, a callback server your tests can play the browser against
+//! ([`spawn_test_callback`]), canned [`TokenSet`] builders, and deterministic
+//! PKCE/state values, so applications can test their authentication flows without hitting
+//! Anthropic.
+//!
+//! # Example
+//!
+//! ```
+//! use anthropic_auth::testing::MockServer;
+//! use anthropic_auth::{AsyncOAuthClient, ExchangeRequest};
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let server = MockServer::start().await?;
+//! let client = AsyncOAuthClient::new(server.config())?;
+//!
+//! let flow = client.start_flow(anthropic_auth::OAuthMode::Console)?;
+//! let tokens = client
+//!     .exchange(&ExchangeRequest::new("mock-authorization-code", &flow))
+//!     .await?;
+//! let api_key = client.create_api_key(&tokens.access_token).await?;
+//! assert!(api_key.starts_with("sk-ant-mock-"));
+//! # Ok(())
+//! # }
+//! ```
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::{
+    callback_router, AnthropicAuthError, CallbackData, CallbackReceiver, CsrfState, Endpoints,
+    OAuthConfig, PkceVerifier, Result, TokenSet, DEFAULT_CALLBACK_PATH,
+};
+
+const TOKEN_PATH: &str = "/v1/oauth/token";
+const API_KEY_PATH: &str = "/api/oauth/claude_cli/create_api_key";
+
+/// Request received by a [`MockServer`]
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// Request path
+    pub path: String,
+    /// Value of the `authorization` header, if any
+    pub authorization: Option<String>,
+    /// JSON request body (`Value::Null` if the body wasn't JSON)
+    pub body: Value,
+}
+
+#[derive(Default)]
+struct MockState {
+    counter: u64,
+    expires_in: u64,
+    rotate_refresh_tokens: bool,
+    access_tokens: HashSet<String>,
+    refresh_tokens: HashSet<String>,
+    failures: VecDeque<(u16, String)>,
+    requests: Vec<RecordedRequest>,
+}
+
+impl MockState {
+    fn next_id(&mut self) -> u64 {
+        self.counter += 1;
+        self.counter
+    }
+}
+
+/// In-process mock of Anthropic's token and API key endpoints
+///
+/// Listens on a random `127.0.0.1` port. Point a client at it with
+/// [`MockServer::config`] (or [`MockServer::endpoints`]). The mock:
+///
+/// - issues tokens for any authorization code (`mock-access-N` / `mock-refresh-N`)
+/// - refreshes only refresh tokens it issued, answering `400 invalid_grant` otherwise
+/// - creates API keys (`sk-ant-mock-N`) only for access tokens it issued
+/// - returns queued failures first (see [`MockServer::fail_next`])
+///
+/// The server stops when the `MockServer` is dropped.
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Start the mock server on the current tokio runtime
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no local port can be bound
+    pub async fn start() -> Result<Self> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .map_err(|e| AnthropicAuthError::CallbackServer(e.to_string()))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| AnthropicAuthError::CallbackServer(e.to_string()))?;
+
+        let state = Arc::new(Mutex::new(MockState {
+            expires_in: 3600,
+            ..Default::default()
+        }));
+        let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let app = Router::new()
+            .route(TOKEN_PATH, post(handle_token))
+            .route(API_KEY_PATH, post(handle_api_key))
+            .with_state(state.clone());
+
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app)
+                .with_graceful_shutdown(async {
+                    let _ = shutdown_rx.await;
+                })
+                .await;
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Start the mock server on a background thread with its own tokio runtime
+    ///
+    /// Use this from synchronous tests of the blocking client.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runtime cannot be created or no local port can be bound
+    pub fn start_in_background() -> Result<Self> {
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    let _ = tx.send(Err(AnthropicAuthError::CallbackServer(e.to_string())));
+                    return;
+                }
+            };
+
+            runtime.block_on(async move {
+                match MockServer::start().await {
+                    Ok(server) => {
+                        // Hand out a handle and keep this runtime alive until it's dropped
+                        let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel();
+                        let _ = tx.send(Ok(MockServer {
+                            addr: server.addr,
+                            state: server.state.clone(),
+                            shutdown: Some(shutdown),
+                        }));
+                        let _ = shutdown_rx.await;
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(e));
+                    }
+                }
+            });
+        });
+
+        rx.recv().map_err(|_| {
+            AnthropicAuthError::CallbackServer("Mock server thread exited".to_string())
+        })?
+    }
+
+    /// Get the base URL, e.g. `http://127.0.0.1:38123`
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Get endpoint overrides pointing at the mock
+    pub fn endpoints(&self) -> Endpoints {
+        Endpoints {
+            authorize_url: format!("{}/oauth/authorize", self.url()),
+            token_url: format!("{}{}", self.url(), TOKEN_PATH),
+            api_key_url: format!("{}{}", self.url(), API_KEY_PATH),
+        }
+    }
+
+    /// Get a default client configuration pointing at the mock
+    pub fn config(&self) -> OAuthConfig {
+        OAuthConfig::builder().endpoints(self.endpoints()).build()
+    }
+
+    /// Set the `expires_in` (seconds) of issued tokens (default: 3600)
+    pub fn set_expires_in(&self, expires_in: u64) {
+        self.lock().expires_in = expires_in;
+    }
+
+    /// Issue a new refresh token on every refresh (default: return the same one)
+    pub fn set_rotate_refresh_tokens(&self, rotate: bool) {
+        self.lock().rotate_refresh_tokens = rotate;
+    }
+
+    /// Answer the next request with the given status and body
+    ///
+    /// Can be called repeatedly to queue several failures.
+    pub fn fail_next(&self, status: u16, body: impl Into<String>) {
+        self.lock().failures.push_back((status, body.into()));
+    }
+
+    /// Get all requests received so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+impl std::fmt::Debug for MockServer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MockServer")
+            .field("addr", &self.addr)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Record the request and pop a queued failure, if any
+fn begin(
+    state: &Mutex<MockState>,
+    path: &str,
+    headers: &HeaderMap,
+    body: &Value,
+) -> Option<Response> {
+    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+    state.requests.push(RecordedRequest {
+        path: path.to_string(),
+        authorization: headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+        body: body.clone(),
+    });
+
+    state.failures.pop_front().map(|(status, body)| {
+        (
+            StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR),
+            body,
+        )
+            .into_response()
+    })
+}
+
+fn invalid_grant() -> Response {
+    (
+        StatusCode::BAD_REQUEST,
+        Json(json!({"error": "invalid_grant"})),
+    )
+        .into_response()
+}
+
+async fn handle_token(
+    State(state): State<Arc<Mutex<MockState>>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Response {
+    let body = body.map(|Json(body)| body).unwrap_or(Value::Null);
+    if let Some(response) = begin(&state, TOKEN_PATH, &headers, &body) {
+        return response;
+    }
+
+    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+    let field = |name: &str| body.get(name).and_then(Value::as_str).unwrap_or_default();
+
+    let rotate = match field("grant_type") {
+        "authorization_code" if !field("code").is_empty() && !field("code_verifier").is_empty() => {
+            true
+        }
+        "refresh_token" if state.refresh_tokens.contains(field("refresh_token")) => {
+            state.rotate_refresh_tokens
+        }
+        _ => return invalid_grant(),
+    };
+
+    let id = state.next_id();
+    let access_token = format!("mock-access-{}", id);
+    state.access_tokens.insert(access_token.clone());
+
+    let refresh_token = if rotate {
+        state.refresh_tokens.remove(field("refresh_token"));
+        let refresh_token = format!("mock-refresh-{}", id);
+        state.refresh_tokens.insert(refresh_token.clone());
+        refresh_token
+    } else {
+        field("refresh_token").to_string()
+    };
+
+    let response = json!({
+        "token_type": "Bearer",
+        "access_token": access_token,
+        "refresh_token": refresh_token,
+        "expires_in": state.expires_in,
+        "scope": "org:create_api_key user:profile user:inference",
+    });
+
+    Json(response).into_response()
+}
+
+async fn handle_api_key(
+    State(state): State<Arc<Mutex<MockState>>>,
+    headers: HeaderMap,
+    body: Option<Json<Value>>,
+) -> Response {
+    let body = body.map(|Json(body)| body).unwrap_or(Value::Null);
+    if let Some(response) = begin(&state, API_KEY_PATH, &headers, &body) {
+        return response;
+    }
+
+    let mut state = state.lock().unwrap_or_else(PoisonError::into_inner);
+    let token = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if !state.access_tokens.contains(token) {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(json!({"error": "invalid_token"})),
+        )
+            .into_response();
+    }
+
+    let id = state.next_id();
+    Json(json!({ "raw_key": format!("sk-ant-mock-{}", id) })).into_response()
+}
+
+/// Response a simulated browser got from a callback server
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowserResponse {
+    /// HTTP status code
+    pub status: u16,
+    /// HTML page shown to the user
+    pub body: String,
+}
+
+/// A real callback server on a random local port, driven by your test
+///
+/// Created by [`spawn_test_callback`]. Hand [`url`](Self::url) to the code under
+/// test as its redirect URI and the [`CallbackReceiver`] from
+/// [`take_receiver`](Self::take_receiver) to its login glue, then play the
+/// browser with [`authorize`](Self::authorize) or [`deny`](Self::deny).
+///
+/// The server stops when the `TestCallback` is dropped.
+pub struct TestCallback {
+    addr: SocketAddr,
+    expected_state: String,
+    receiver: Option<CallbackReceiver>,
+    shutdown: Option<tokio::sync::oneshot::Sender<()>>,
+}
+
+/// Start a callback server for `expected_state` on a random `127.0.0.1` port
+///
+/// Lets applications test their whole login orchestration, including UI glue,
+/// without a real browser.
+///
+/// # Errors
+///
+/// Returns an error if no local port can be bound
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::testing::{deterministic_state, spawn_test_callback};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let state = deterministic_state(1);
+/// let mut callback = spawn_test_callback(&state).await?;
+/// let receiver = callback.take_receiver().unwrap();
+///
+/// // The app under test would wait on `receiver` while the "browser" is redirected
+/// let page = callback.authorize("test-authorization-code").await?;
+/// assert_eq!(page.status, 200);
+///
+/// let data = receiver.recv().await?;
+/// assert_eq!(data.code, "test-authorization-code");
+/// # Ok(())
+/// # }
+/// ```
+pub async fn spawn_test_callback(expected_state: &str) -> Result<TestCallback> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| AnthropicAuthError::CallbackServer(e.to_string()))?;
+    let addr = listener
+        .local_addr()
+        .map_err(|e| AnthropicAuthError::CallbackServer(e.to_string()))?;
+
+    let (app, receiver) = callback_router(expected_state);
+    let (shutdown, shutdown_rx) = tokio::sync::oneshot::channel::<()>();
+
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, app)
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            })
+            .await;
+    });
+
+    Ok(TestCallback {
+        addr,
+        expected_state: expected_state.to_string(),
+        receiver: Some(receiver),
+        shutdown: Some(shutdown),
+    })
+}
+
+impl TestCallback {
+    /// Get the callback URL to use as redirect URI, e.g. `http://127.0.0.1:38123/callback`
+    pub fn url(&self) -> String {
+        format!("http://{}{}", self.addr, DEFAULT_CALLBACK_PATH)
+    }
+
+    /// Get the bound socket address
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Get the URL the authorization server would redirect the browser to for `code`
+    pub fn redirect_url(&self, code: &str) -> String {
+        self.url_with_query(&[("code", code), ("state", &self.expected_state)])
+    }
+
+    /// Take the receiver the callback data arrives on
+    ///
+    /// Returns `None` if it was already taken.
+    pub fn take_receiver(&mut self) -> Option<CallbackReceiver> {
+        self.receiver.take()
+    }
+
+    /// Wait for the callback data, if the receiver wasn't taken
+    ///
+    /// # Errors
+    ///
+    /// Returns the callback's error, or [`AnthropicAuthError::CallbackServer`] if
+    /// the receiver was already taken
+    pub async fn recv(&mut self) -> Result<CallbackData> {
+        match self.receiver.take() {
+            Some(receiver) => receiver.recv().await,
+            None => Err(AnthropicAuthError::CallbackServer(
+                "Callback receiver was already taken".to_string(),
+            )),
+        }
+    }
+
+    /// Simulate the user approving: redirect with `code` and the expected state
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be reached
+    pub async fn authorize(&self, code: &str) -> Result<BrowserResponse> {
+        simulate_browser(&self.redirect_url(code)).await
+    }
+
+    /// Simulate the user denying: redirect with an OAuth `error` such as `access_denied`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be reached
+    pub async fn deny(&self, error: &str) -> Result<BrowserResponse> {
+        simulate_browser(&self.url_with_query(&[("error", error)])).await
+    }
+
+    /// Simulate a redirect with arbitrary query parameters (e.g. a forged state)
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the server cannot be reached
+    pub async fn visit(&self, query: &[(&str, &str)]) -> Result<BrowserResponse> {
+        simulate_browser(&self.url_with_query(query)).await
+    }
+
+    fn url_with_query(&self, query: &[(&str, &str)]) -> String {
+        let mut url = url::Url::parse(&self.url()).expect("callback URL is valid");
+        url.query_pairs_mut().extend_pairs(query);
+        url.into()
+    }
+}
+
+impl Drop for TestCallback {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+    }
+}
+
+impl std::fmt::Debug for TestCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestCallback")
+            .field("addr", &self.addr)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Simulate a browser following a redirect to `url`
+///
+/// Use this when the code under test runs its own callback server, e.g. via
+/// [`run_callback_server`](crate::run_callback_server).
+///
+/// # Errors
+///
+/// Returns an error if the server cannot be reached
+pub async fn simulate_browser(url: &str) -> Result<BrowserResponse> {
+    let response = reqwest::get(url).await?;
+    Ok(BrowserResponse {
+        status: response.status().as_u16(),
+        body: response.text().await?,
+    })
+}
+
+/// Builder for canned [`TokenSet`]s
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::testing::TokenSetBuilder;
+/// use std::time::Duration;
+///
+/// let tokens = TokenSetBuilder::new().expires_in(Duration::from_secs(60)).build();
+/// assert!(tokens.is_expired()); // within the 5 minute buffer
+/// ```
+#[derive(Debug, Clone)]
+pub struct TokenSetBuilder {
+    tokens: TokenSet,
+}
+
+impl Default for TokenSetBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TokenSetBuilder {
+    /// Start from a valid token set expiring in one hour
+    pub fn new() -> Self {
+        Self {
+            tokens: TokenSet {
+                access_token: "test-access-token".to_string(),
+                refresh_token: "test-refresh-token".to_string(),
+                expires_at: now_secs() + 3600,
+                scope: Some("org:create_api_key user:profile user:inference".to_string()),
+                token_type: Some("Bearer".to_string()),
+                organization: None,
+                obtained_at: None,
+                account_id: None,
+            },
+        }
+    }
+
+    /// Set the access token
+    pub fn access_token(mut self, access_token: impl Into<String>) -> Self {
+        self.tokens.access_token = access_token.into();
+        self
+    }
+
+    /// Set the refresh token
+    pub fn refresh_token(mut self, refresh_token: impl Into<String>) -> Self {
+        self.tokens.refresh_token = refresh_token.into();
+        self
+    }
+
+    /// Expire the tokens `expires_in` from now
+    pub fn expires_in(mut self, expires_in: Duration) -> Self {
+        self.tokens.expires_at = now_secs() + expires_in.as_secs();
+        self
+    }
+
+    /// Make the tokens already expired
+    pub fn expired(mut self) -> Self {
+        self.tokens.expires_at = now_secs().saturating_sub(60);
+        self
+    }
+
+    /// Set the granted scopes
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.tokens.scope = Some(scope.into());
+        self
+    }
+
+    /// Build the token set
+    pub fn build(self) -> TokenSet {
+        self.tokens
+    }
+}
+
+/// Get a valid token set expiring in one hour
+pub fn valid_tokens() -> TokenSet {
+    TokenSetBuilder::new().build()
+}
+
+/// Get a token set that has already expired
+pub fn expired_tokens() -> TokenSet {
+    TokenSetBuilder::new().expired().build()
+}
+
+/// Get a deterministic, valid PKCE verifier derived from `seed`
+///
+/// To make a client's [`start_flow`](crate::AsyncOAuthClient::start_flow) reproducible,
+/// configure it with [`FlowRng::from_seed`](crate::FlowRng::from_seed) instead.
+pub fn deterministic_verifier(seed: u64) -> PkceVerifier {
+    // 32 bytes -> 43 base64url characters, the minimum PKCE verifier length
+    PkceVerifier::from_bytes(&seeded_bytes(seed ^ 0x5eed))
+}
+
+/// Get a deterministic state token derived from `seed`
+pub fn deterministic_state(seed: u64) -> CsrfState {
+    CsrfState::from_bytes(&seeded_bytes(seed))
+}
+
+/// Expand a seed into 32 bytes with splitmix64 (not cryptographically secure)
+fn seeded_bytes(seed: u64) -> [u8; 32] {
+    let mut state = seed;
+    let mut bytes = [0u8; 32];
+    for chunk in bytes.chunks_mut(8) {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        chunk.copy_from_slice(&(z ^ (z >> 31)).to_le_bytes());
+    }
+    bytes
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
@@ -0,0 +1,177 @@
+use rustls::crypto::ring::default_provider;
+use rustls::ServerConfig;
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::server::callback_router_at;
+use crate::{AnthropicAuthError, CallbackData, CallbackServerConfig, Result};
+
+/// Certificate and private key used by the HTTPS callback server
+///
+/// Either load an existing PEM pair with [`TlsCertificate::from_pem`], or generate
+/// an ephemeral self-signed certificate with [`TlsCertificate::self_signed`].
+///
+/// Browsers will warn about a self-signed certificate until it is trusted. Write it
+/// out with [`TlsCertificate::write_cert_pem`] and add it to the trust store, e.g.
+/// `security add-trusted-cert -r trustRoot -k ~/Library/Keychains/login.keychain-db cert.pem`
+/// on macOS, `certutil -addstore -user Root cert.pem` on Windows, or
+/// `certutil -d sql:$HOME/.pki/nssdb -A -t C,, -n anthropic-auth -i cert.pem` for
+/// Chromium/Firefox on Linux.
+#[derive(Clone)]
+pub struct TlsCertificate {
+    cert_pem: String,
+    key_pem: String,
+}
+
+impl std::fmt::Debug for TlsCertificate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Never print the private key
+        f.debug_struct("TlsCertificate")
+            .field("cert_pem", &self.cert_pem)
+            .finish_non_exhaustive()
+    }
+}
+
+impl TlsCertificate {
+    /// Use an existing PEM-encoded certificate chain and private key
+    pub fn from_pem(cert_pem: impl Into<String>, key_pem: impl Into<String>) -> Self {
+        Self {
+            cert_pem: cert_pem.into(),
+            key_pem: key_pem.into(),
+        }
+    }
+
+    /// Generate a self-signed certificate for `localhost`, `127.0.0.1`, and `::1`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if certificate generation fails
+    pub fn self_signed() -> Result<Self> {
+        Self::self_signed_for(&["localhost", "127.0.0.1", "::1"])
+    }
+
+    /// Generate a self-signed certificate for the given host names and IP addresses
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a name is invalid or certificate generation fails
+    pub fn self_signed_for(names: &[&str]) -> Result<Self> {
+        let names = names
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        let certified = rcgen::generate_simple_self_signed(names).map_err(|e| {
+            AnthropicAuthError::CallbackServer(format!("Failed to generate certificate: {}", e))
+        })?;
+
+        Ok(Self {
+            cert_pem: certified.cert.pem(),
+            key_pem: certified.key_pair.serialize_pem(),
+        })
+    }
+
+    /// Get the PEM-encoded certificate (safe to share)
+    pub fn cert_pem(&self) -> &str {
+        &self.cert_pem
+    }
+
+    /// Write the certificate (not the key) to `path` so it can be added to a trust store
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written
+    pub fn write_cert_pem(&self, path: impl AsRef<Path>) -> Result<PathBuf> {
+        let path = path.as_ref();
+        std::fs::write(path, &self.cert_pem).map_err(|e| {
+            AnthropicAuthError::CallbackServer(format!(
+                "Failed to write certificate to {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Ok(path.to_path_buf())
+    }
+
+    /// Build a rustls server configuration from the certificate and key
+    fn server_config(&self) -> Result<ServerConfig> {
+        let tls_error = |e: &dyn std::fmt::Display| {
+            AnthropicAuthError::CallbackServer(format!("Invalid TLS certificate: {}", e))
+        };
+
+        let certs = CertificateDer::pem_slice_iter(self.cert_pem.as_bytes())
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|e| tls_error(&e))?;
+        let key =
+            PrivateKeyDer::from_pem_slice(self.key_pem.as_bytes()).map_err(|e| tls_error(&e))?;
+
+        let mut config = ServerConfig::builder_with_provider(Arc::new(default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| tls_error(&e))?
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(|e| tls_error(&e))?;
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        Ok(config)
+    }
+}
+
+/// Run a local OAuth callback server over HTTPS
+///
+/// Same as [`run_callback_server_with`](crate::run_callback_server_with), but
+/// terminates TLS with `certificate`. Use this when the registered redirect URI must
+/// be `https://localhost/...`.
+///
+/// **Note:** Only available when the `callback-server-tls` feature is enabled.
+///
+/// # Errors
+///
+/// Returns an error if the certificate is invalid, the server fails to start, or
+/// the callback is rejected (see [`run_callback_server`](crate::run_callback_server))
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{run_callback_server_tls, CallbackServerConfig, TlsCertificate};
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let expected_state = "state";
+/// let certificate = TlsCertificate::self_signed()?;
+/// let callback = run_callback_server_tls(
+///     &CallbackServerConfig::with_port(8443),
+///     expected_state,
+///     &certificate,
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn run_callback_server_tls(
+    config: &CallbackServerConfig,
+    expected_state: &str,
+    certificate: &TlsCertificate,
+) -> Result<CallbackData> {
+    let tls_config =
+        axum_server::tls_rustls::RustlsConfig::from_config(Arc::new(certificate.server_config()?));
+    let (app, receiver) = callback_router_at(&config.path, expected_state);
+
+    let listener = std::net::TcpListener::bind(config.addr)
+        .and_then(|listener| listener.set_nonblocking(true).map(|_| listener))
+        .map_err(|e| {
+            AnthropicAuthError::CallbackServer(format!("Failed to bind to {}: {}", config.addr, e))
+        })?;
+
+    // Spawn server task
+    tokio::spawn(async move {
+        axum_server::from_tcp_rustls(listener, tls_config)
+            .serve(app.into_make_service())
+            .await
+            .expect("Server failed to start");
+    });
+
+    // Wait for callback
+    receiver.recv().await
+}
@@ -1,17 +1,78 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::{Clock, CsrfState, PkceVerifier, SystemClock, DEFAULT_EXPIRY_BUFFER};
+
+/// Anthropic's production token endpoint
+pub(crate) const TOKEN_URL: &str = "https://console.anthropic.com/v1/oauth/token";
+/// Anthropic's production API key creation endpoint
+pub(crate) const API_KEY_URL: &str =
+    "https://api.anthropic.com/api/oauth/claude_cli/create_api_key";
+/// Anthropic's page showing the authorization response for the user to paste
+pub(crate) const MANUAL_REDIRECT_URI: &str = "https://console.anthropic.com/oauth/code/callback";
+
 /// OAuth mode for Anthropic authentication
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Serializes as `"max"`, `"console"`, or `{"custom": {...}}`, so stored profiles
+/// can remember their mode.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OAuthMode {
     /// Claude Pro/Max subscription (uses claude.ai)
     Max,
     /// API key creation (uses console.anthropic.com)
     Console,
+    /// Any other authorization surface, e.g. a regional or staging domain
+    ///
+    /// Flows in this mode use these endpoints, even over
+    /// [`OAuthConfig::endpoints`]. Exchange their codes with `exchange_callback` or
+    /// an [`ExchangeRequest::new`] built from the flow, which know the flow's mode;
+    /// [`ExchangeRequest::from_parts`] only sees the configured endpoints.
+    /// Refreshes and API key creation also use the configured endpoints, so set
+    /// those too for clients that outlive the login.
+    Custom(Endpoints),
+}
+
+impl OAuthMode {
+    /// Get the endpoints flows in this mode talk to
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::OAuthMode;
+    ///
+    /// assert_eq!(
+    ///     OAuthMode::Max.endpoints().authorize_url,
+    ///     "https://claude.ai/oauth/authorize"
+    /// );
+    /// ```
+    pub fn endpoints(&self) -> Endpoints {
+        let base_domain = match self {
+            OAuthMode::Max => "claude.ai",
+            OAuthMode::Console => "console.anthropic.com",
+            OAuthMode::Custom(endpoints) => return endpoints.clone(),
+        };
+        Endpoints {
+            authorize_url: format!("https://{}/oauth/authorize", base_domain),
+            token_url: TOKEN_URL.to_string(),
+            api_key_url: API_KEY_URL.to_string(),
+        }
+    }
 }
 
 /// OAuth token set containing access token, refresh token, and expiration info
+///
+/// Deserialization also accepts token files written by older releases and other
+/// tools: `created_at` or `issued_at` for `obtained_at`, `expires_in` plus
+/// `obtained_at` instead of `expires_at`, timestamps in milliseconds, and
+/// `scopes` as a list.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "TokenSetFields")]
 pub struct TokenSet {
     /// The access token used to authenticate API requests
     pub access_token: String,
@@ -19,37 +80,234 @@ pub struct TokenSet {
     pub refresh_token: String,
     /// Unix timestamp (seconds) when the access token expires
     pub expires_at: u64,
+    /// Space-separated scopes granted to the access token, if reported by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Token type (usually "Bearer"), if reported by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    /// Organization the tokens were issued for, if reported by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization: Option<Organization>,
+    /// Unix timestamp (seconds) when the tokens were issued, if known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub obtained_at: Option<u64>,
+    /// UUID of the account the tokens were issued to, if reported by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+}
+
+/// Short, stable hashes identifying a token set without revealing it
+///
+/// Each part is the first 16 hex characters of the token's SHA-256 hash, the same
+/// scheme as [`ApiKeyRecord::fingerprint_of`](crate::ApiKeyRecord::fingerprint_of).
+/// Log it, or compare it across systems, to tell which credential was used;
+/// the parts change independently when the access token is refreshed or the
+/// refresh token rotated. Displays as `<access>/<refresh>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TokenFingerprint {
+    /// Fingerprint of the access token
+    pub access: String,
+    /// Fingerprint of the refresh token
+    pub refresh: String,
+}
+
+impl fmt::Display for TokenFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.access, self.refresh)
+    }
+}
+
+/// First 16 hex characters of the SHA-256 hash of a secret
+pub(crate) fn fingerprint_hex(secret: &str) -> String {
+    Sha256::digest(secret.as_bytes())[..8]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Organization information returned alongside OAuth tokens
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Organization {
+    /// Organization UUID
+    pub uuid: String,
+    /// Human-readable organization name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
 }
 
 impl TokenSet {
     /// Check if the token is expired or will expire soon (within 5 minutes)
     ///
-    /// This includes a 5-minute buffer to prevent race conditions where a token
-    /// expires between checking and using it.
+    /// This includes a 5-minute buffer ([`DEFAULT_EXPIRY_BUFFER`]) to prevent race
+    /// conditions where a token expires between checking and using it.
     pub fn is_expired(&self) -> bool {
-        self.expires_in() <= Duration::from_secs(300)
+        self.is_expired_with_skew(Duration::ZERO)
+    }
+
+    /// Check if the token is expired or will expire soon, allowing for clock skew
+    ///
+    /// Like [`is_expired`](Self::is_expired), but the local clock may run up to
+    /// `skew` ahead of the clock `expires_at` was computed with.
+    pub fn is_expired_with_skew(&self, skew: Duration) -> bool {
+        self.is_expired_with(&SystemClock, skew, DEFAULT_EXPIRY_BUFFER)
+    }
+
+    /// Check if the token is expired or expires within `buffer`, by the time of `clock`
+    ///
+    /// Like [`is_expired_with_skew`](Self::is_expired_with_skew), with the clock and
+    /// expiry buffer of your choice. [`OAuthConfig::clock`] and
+    /// [`OAuthConfig::expiry_buffer`] hold the configured ones.
+    pub fn is_expired_with(&self, clock: &dyn Clock, skew: Duration, buffer: Duration) -> bool {
+        self.expires_in_with(clock, skew) <= buffer
     }
 
     /// Get the duration until the token expires
     ///
     /// Returns `Duration::ZERO` if the token is already expired.
     pub fn expires_in(&self) -> Duration {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        self.expires_in_with_skew(Duration::ZERO)
+    }
+
+    /// Get the duration until the token expires, allowing for clock skew
+    ///
+    /// The local clock may run up to `skew` ahead of the clock `expires_at` was
+    /// computed with, e.g. for tokens shared with another machine.
+    /// Returns `Duration::ZERO` if the token is expired even then.
+    pub fn expires_in_with_skew(&self, skew: Duration) -> Duration {
+        self.expires_in_with(&SystemClock, skew)
+    }
+
+    /// Get the duration until the token expires by the time of `clock`, allowing for clock skew
+    ///
+    /// Returns `Duration::ZERO` if the token is expired even then.
+    pub fn expires_in_with(&self, clock: &dyn Clock, skew: Duration) -> Duration {
+        let now = clock.unix_secs();
+        let expires_at = self.expires_at.saturating_add(skew.as_secs());
 
-        if self.expires_at > now {
-            Duration::from_secs(self.expires_at - now)
+        if expires_at > now {
+            Duration::from_secs(expires_at - now)
         } else {
             Duration::ZERO
         }
     }
 
+    /// Get the time when the access token expires
+    pub fn expires_at_system_time(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(self.expires_at)
+    }
+
+    /// Get the time when the access token expires as a `chrono` date and time
+    ///
+    /// Returns `None` if `expires_at` is out of `chrono`'s range.
+    ///
+    /// **Note:** Only available when the `chrono` feature is enabled.
+    #[cfg(feature = "chrono")]
+    pub fn expires_at_datetime(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::from_timestamp(i64::try_from(self.expires_at).ok()?, 0)
+    }
+
+    /// Get the time when the access token expires as a `time` date and time
+    ///
+    /// Returns `None` if `expires_at` is out of `time`'s range.
+    ///
+    /// **Note:** Only available when the `time` feature is enabled.
+    #[cfg(feature = "time")]
+    pub fn expires_at_offset_datetime(&self) -> Option<time::OffsetDateTime> {
+        time::OffsetDateTime::from_unix_timestamp(i64::try_from(self.expires_at).ok()?).ok()
+    }
+
+    /// Get how long the access token was issued for, if `obtained_at` is known
+    pub fn lifetime(&self) -> Option<Duration> {
+        self.obtained_at
+            .map(|obtained_at| Duration::from_secs(self.expires_at.saturating_sub(obtained_at)))
+    }
+
+    /// Get how long ago the tokens were issued by the time of `clock`, if `obtained_at` is known
+    pub fn age_with(&self, clock: &dyn Clock) -> Option<Duration> {
+        self.obtained_at
+            .map(|obtained_at| Duration::from_secs(clock.unix_secs().saturating_sub(obtained_at)))
+    }
+
+    /// Get the granted scopes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::TokenSet;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let json = r#"{"access_token":"a","refresh_token":"r","expires_at":1700000000,
+    ///               "scopes":["user:profile","user:inference"]}"#;
+    /// let tokens = TokenSet::from_json(json)?;
+    /// assert_eq!(tokens.scopes(), ["user:profile", "user:inference"]);
+    /// assert!(tokens.has_scope("user:inference"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scopes(&self) -> Vec<&str> {
+        self.scope
+            .as_deref()
+            .map_or_else(Vec::new, |scope| scope.split_whitespace().collect())
+    }
+
+    /// Check whether a scope was granted
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().contains(&scope)
+    }
+
+    /// Get a fingerprint identifying these tokens in logs without revealing them
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # let tokens = anthropic_auth::TokenSet {
+    /// #     access_token: "sk-ant-oat01-access".to_string(),
+    /// #     refresh_token: "sk-ant-ort01-refresh".to_string(),
+    /// #     expires_at: 1700000000,
+    /// #     scope: None,
+    /// #     token_type: None,
+    /// #     organization: None,
+    /// #     obtained_at: None,
+    /// #     account_id: None,
+    /// # };
+    /// let fingerprint = tokens.fingerprint();
+    /// println!("Using credential {}", fingerprint);
+    /// assert_eq!(fingerprint, tokens.clone().fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> TokenFingerprint {
+        TokenFingerprint {
+            access: fingerprint_hex(&self.access_token),
+            refresh: fingerprint_hex(&self.refresh_token),
+        }
+    }
+
+    /// Get the value of an `authorization` header carrying the access token
+    /// (`Bearer <access token>`)
+    ///
+    /// Messages API requests also need the OAuth beta header; see
+    /// [`sdk::Credentials`](crate::sdk::Credentials) for the full set.
+    pub fn as_bearer(&self) -> String {
+        format!("Bearer {}", self.access_token)
+    }
+
     /// Validate the token structure
     ///
     /// Checks that the token fields are non-empty and properly formatted.
     pub fn validate(&self) -> Result<(), &'static str> {
+        self.validate_with_skew(Duration::ZERO)
+    }
+
+    /// Validate the token structure, allowing for clock skew
+    ///
+    /// Like [`validate`](Self::validate), but the local clock may run up to `skew`
+    /// behind, so `expires_at` may be up to `skew` further in the future.
+    pub fn validate_with_skew(&self, skew: Duration) -> Result<(), &'static str> {
+        self.validate_with(&SystemClock, skew)
+    }
+
+    /// Validate the token structure by the time of `clock`, allowing for clock skew
+    pub fn validate_with(&self, clock: &dyn Clock, skew: Duration) -> Result<(), &'static str> {
         if self.access_token.is_empty() {
             return Err("access_token is empty");
         }
@@ -60,32 +318,467 @@ impl TokenSet {
             return Err("expires_at is invalid");
         }
         // Check if expires_at is reasonable (not too far in past or future)
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let now = clock.unix_secs();
         // Token shouldn't be more than 1 year in the future
-        if self.expires_at > now + 31536000 {
+        if self.expires_at > now + 31536000 + skew.as_secs() {
             return Err("expires_at is too far in the future");
         }
         Ok(())
     }
+
+    /// Fill in fields the server omitted from a refresh response using the previous token set
+    ///
+    /// Anthropic only returns a new refresh token when it rotates it, so an omitted
+    /// refresh token means the previous one is still valid.
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+    pub(crate) fn carry_forward(&mut self, previous: &TokenSet) {
+        if self.refresh_token.is_empty() {
+            self.refresh_token = previous.refresh_token.clone();
+        }
+        if self.scope.is_none() {
+            self.scope = previous.scope.clone();
+        }
+        if self.token_type.is_none() {
+            self.token_type = previous.token_type.clone();
+        }
+        if self.organization.is_none() {
+            self.organization = previous.organization.clone();
+        }
+        if self.account_id.is_none() {
+            self.account_id = previous.account_id.clone();
+        }
+    }
+
+    /// Version of the JSON format written by [`to_json_pretty`](Self::to_json_pretty)
+    pub const FORMAT_VERSION: u32 = 1;
+
+    /// Serialize the token set as pretty-printed JSON tagged with `"version": 1`
+    ///
+    /// The version lets future releases migrate stored tokens. Token stores write
+    /// the same versioned format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization fails
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::TokenSet;
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let json = r#"{"access_token":"a","refresh_token":"r","expires_at":1700000000}"#;
+    /// let tokens = TokenSet::from_json(json)?;
+    /// assert!(tokens.to_json_pretty()?.contains(r#""version": 1"#));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_json_pretty(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string_pretty(&self.versioned())?)
+    }
+
+    /// Deserialize a token set written by [`to_json_pretty`](Self::to_json_pretty)
+    ///
+    /// JSON without a `version` (written by earlier releases) is read as version 1.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AnthropicAuthError::InvalidTokenFormat`](crate::AnthropicAuthError::InvalidTokenFormat)
+    /// if the JSON comes from a newer, unsupported format version, or a
+    /// serialization error if it isn't a token set
+    pub fn from_json(json: &str) -> crate::Result<Self> {
+        Self::from_versioned(serde_json::from_str(json)?)
+    }
+
+    /// Wrap the token set with the current format version for serialization
+    pub(crate) fn versioned(&self) -> VersionedTokenSet<'_> {
+        VersionedTokenSet {
+            version: Self::FORMAT_VERSION,
+            tokens: self,
+        }
+    }
+
+    /// Unwrap a deserialized token set, checking its format version
+    pub(crate) fn from_versioned(versioned: StoredTokenSet) -> crate::Result<Self> {
+        match versioned.version {
+            None | Some(Self::FORMAT_VERSION) => Ok(versioned.tokens),
+            Some(version) => Err(crate::AnthropicAuthError::InvalidTokenFormat(format!(
+                "unsupported token format version {} (this release reads version {})",
+                version,
+                Self::FORMAT_VERSION
+            ))),
+        }
+    }
+}
+
+/// A token set tagged with its format version, for serialization
+#[derive(Serialize)]
+pub(crate) struct VersionedTokenSet<'a> {
+    version: u32,
+    #[serde(flatten)]
+    tokens: &'a TokenSet,
+}
+
+/// A stored token set with its format version, if it has one
+#[derive(Deserialize)]
+pub(crate) struct StoredTokenSet {
+    #[serde(default)]
+    version: Option<u32>,
+    #[serde(flatten)]
+    tokens: TokenSet,
+}
+
+/// The fields of a serialized token set, in any of the accepted conventions
+#[derive(Deserialize)]
+struct TokenSetFields {
+    access_token: String,
+    refresh_token: String,
+    #[serde(default)]
+    expires_at: Option<u64>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+    #[serde(default, alias = "created_at", alias = "issued_at")]
+    obtained_at: Option<u64>,
+    #[serde(default, alias = "scopes", deserialize_with = "scope_or_list")]
+    scope: Option<String>,
+    #[serde(default)]
+    token_type: Option<String>,
+    #[serde(default)]
+    organization: Option<Organization>,
+    #[serde(default, alias = "account_uuid")]
+    account_id: Option<String>,
+}
+
+impl TryFrom<TokenSetFields> for TokenSet {
+    type Error = String;
+
+    fn try_from(fields: TokenSetFields) -> std::result::Result<Self, Self::Error> {
+        let obtained_at = fields.obtained_at.map(secs_from_secs_or_millis);
+        let expires_at = match (fields.expires_at, fields.expires_in, obtained_at) {
+            (Some(expires_at), _, _) => secs_from_secs_or_millis(expires_at),
+            (None, Some(expires_in), Some(obtained_at)) => obtained_at.saturating_add(expires_in),
+            (None, Some(_), None) => {
+                return Err("`expires_in` needs `obtained_at` or `created_at`".to_string())
+            }
+            (None, None, _) => return Err("missing field `expires_at`".to_string()),
+        };
+
+        Ok(TokenSet {
+            access_token: fields.access_token,
+            refresh_token: fields.refresh_token,
+            expires_at,
+            scope: fields.scope,
+            token_type: fields.token_type,
+            organization: fields.organization,
+            obtained_at,
+            account_id: fields.account_id,
+        })
+    }
+}
+
+/// Read a scope as a space-separated string or a list of scopes
+fn scope_or_list<'de, D>(deserializer: D) -> std::result::Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Scope {
+        Joined(String),
+        List(Vec<String>),
+    }
+
+    Ok(
+        Option::<Scope>::deserialize(deserializer)?.map(|scope| match scope {
+            Scope::Joined(scope) => scope,
+            Scope::List(scopes) => scopes.join(" "),
+        }),
+    )
+}
+
+/// Convert a Unix timestamp to seconds, treating implausibly large values as milliseconds
+fn secs_from_secs_or_millis(timestamp: u64) -> u64 {
+    // 10^11 seconds is in the year 5138, while millisecond timestamps passed it in 1973
+    if timestamp >= 100_000_000_000 {
+        timestamp / 1000
+    } else {
+        timestamp
+    }
+}
+
+/// Options for an [`ExchangeRequest`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExchangeOptions {
+    /// Post the code to the server even if this client already exchanged it
+    ///
+    /// By default a client remembers the codes it exchanged for a few minutes and
+    /// returns the cached tokens when the same flow (same code, PKCE verifier and
+    /// redirect URI) exchanges again, since the server rejects a used code with an
+    /// opaque HTTP 400. (default: `false`)
+    pub allow_reuse: bool,
+}
+
+/// Everything needed to exchange an authorization response for tokens
+///
+/// Passed to `OAuthClient::exchange` and `AsyncOAuthClient::exchange`. Built from
+/// the [`OAuthFlow`] with [`new`](Self::new), so the state and verifier always
+/// come from the same flow, or from separately stored parts with
+/// [`from_parts`](Self::from_parts).
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{ExchangeOptions, ExchangeRequest, OAuthConfig, OAuthFlow, OAuthMode};
+///
+/// # fn example(flow: &OAuthFlow) {
+/// let request = ExchangeRequest::new("code123#state456", flow)
+///     .options(ExchangeOptions { allow_reuse: true });
+/// assert_eq!(request.expected_state, &flow.state);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExchangeRequest<'a> {
+    /// The authorization response pasted by the user (format: `code#state`), or
+    /// just the code if already separated
+    pub response: &'a str,
+    /// The state token of the flow (for CSRF validation)
+    pub expected_state: &'a CsrfState,
+    /// The PKCE verifier of the flow
+    pub verifier: &'a PkceVerifier,
+    /// The mode of the flow, whose token endpoint is used if known
+    pub mode: Option<&'a OAuthMode>,
+    /// The redirect URI of the flow, sent in the token exchange; the client's
+    /// configured one if unknown
+    pub redirect_uri: Option<&'a str>,
+    /// Exchange options
+    pub options: ExchangeOptions,
+}
+
+impl<'a> ExchangeRequest<'a> {
+    /// Exchange `response` for the given flow
+    pub fn new(response: &'a str, flow: &'a OAuthFlow) -> Self {
+        Self {
+            response,
+            expected_state: &flow.state,
+            verifier: &flow.verifier,
+            mode: Some(&flow.mode),
+            redirect_uri: Some(&flow.redirect_uri),
+            options: ExchangeOptions::default(),
+        }
+    }
+
+    /// Exchange `response` with a state and verifier restored separately, e.g. from
+    /// a database
+    ///
+    /// State and verifier are distinct types, so swapping them is a compile error.
+    /// Restore them from strings with `.parse()` (see [`CsrfState`] and [`PkceVerifier`]).
+    pub fn from_parts(
+        response: &'a str,
+        expected_state: &'a CsrfState,
+        verifier: &'a PkceVerifier,
+    ) -> Self {
+        Self {
+            response,
+            expected_state,
+            verifier,
+            mode: None,
+            redirect_uri: None,
+            options: ExchangeOptions::default(),
+        }
+    }
+
+    /// Set the exchange options
+    pub fn options(mut self, options: ExchangeOptions) -> Self {
+        self.options = options;
+        self
+    }
+}
+
+/// Coarse-grained authentication status, e.g. for a "connection status" indicator
+///
+/// Computed from the current tokens with [`AuthState::of`], from a
+/// [`TokenStore`](crate::TokenStore) with [`AuthState::from_store`], or from a
+/// token manager's `state()`, so UIs don't need to duplicate the expiry logic.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{AuthState, TokenSet};
+///
+/// # fn show(tokens: Option<&TokenSet>) {
+/// let label = match AuthState::of(tokens) {
+///     AuthState::NoCredentials => "Not signed in".to_string(),
+///     AuthState::NeedsRefresh => "Reconnecting...".to_string(),
+///     AuthState::Valid { expires_in } => format!("Connected ({}m left)", expires_in.as_secs() / 60),
+///     AuthState::ReauthRequired => "Please sign in again".to_string(),
+/// };
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "state")]
+pub enum AuthState {
+    /// No tokens are stored; the user has never logged in or logged out
+    NoCredentials,
+    /// The access token is expired or about to expire, but can be refreshed
+    NeedsRefresh,
+    /// The access token is usable
+    Valid {
+        /// Time until the access token expires
+        expires_in: Duration,
+    },
+    /// The tokens can't be refreshed (missing or rejected refresh token); the user
+    /// must log in again
+    ReauthRequired,
+}
+
+impl AuthState {
+    /// Compute the state of the given tokens
+    ///
+    /// Uses the same 5 minute buffer as [`TokenSet::is_expired`]; use
+    /// [`of_with`](Self::of_with) to honor a configured clock and expiry buffer.
+    pub fn of(tokens: Option<&TokenSet>) -> Self {
+        Self::of_with(tokens, &OAuthConfig::default())
+    }
+
+    /// Compute the state of the given tokens, judging expiry by
+    /// [`OAuthConfig::is_expired`]
+    pub fn of_with(tokens: Option<&TokenSet>, config: &OAuthConfig) -> Self {
+        match tokens {
+            None => Self::NoCredentials,
+            Some(tokens) if !config.is_expired(tokens) => Self::Valid {
+                expires_in: tokens.expires_in_with(config.clock(), config.clock_skew),
+            },
+            Some(tokens) if tokens.refresh_token.is_empty() => Self::ReauthRequired,
+            Some(_) => Self::NeedsRefresh,
+        }
+    }
+
+    /// Compute the state of the tokens in a store
+    ///
+    /// # Errors
+    ///
+    /// Returns the store's error if the tokens cannot be loaded
+    pub fn from_store(store: &dyn crate::TokenStore) -> crate::Result<Self> {
+        Ok(Self::of(store.load()?.as_ref()))
+    }
+
+    /// Whether an access token is, or can be made, available without user interaction
+    pub fn is_authenticated(self) -> bool {
+        matches!(self, Self::Valid { .. } | Self::NeedsRefresh)
+    }
 }
 
 /// OAuth authorization flow information
 ///
 /// Contains the authorization URL, PKCE verifier, and state token needed to complete
-/// the OAuth flow.
-#[derive(Debug, Clone)]
+/// the OAuth flow. To persist a flow with an expiry, use [`PendingFlow`](crate::PendingFlow).
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OAuthFlow {
     /// The URL the user should visit to authorize the application
     pub authorization_url: String,
     /// The PKCE verifier used to exchange the authorization code for tokens
-    pub verifier: String,
+    pub verifier: PkceVerifier,
     /// The state token for CSRF protection
-    pub state: String,
+    pub state: CsrfState,
     /// The OAuth mode (Max or Console)
     pub mode: OAuthMode,
+    /// The redirect URI sent in the authorization URL
+    pub redirect_uri: String,
+}
+
+impl OAuthFlow {
+    /// Get the host of the authorization page, e.g. `claude.ai`
+    ///
+    /// The accessors on this type parse [`authorization_url`](Self::authorization_url)
+    /// back into its components, so tests and security reviews can assert on exactly
+    /// what is sent to the browser. They return `None` if the URL or the parameter
+    /// is missing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::{OAuthClient, OAuthConfig, OAuthMode};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = OAuthClient::new(OAuthConfig::default())?;
+    /// let flow = client.start_flow(OAuthMode::Max)?;
+    ///
+    /// assert_eq!(flow.base_domain().as_deref(), Some("claude.ai"));
+    /// assert_eq!(flow.code_challenge_method().as_deref(), Some("S256"));
+    /// assert_eq!(flow.code_challenge(), Some(flow.verifier.challenge()));
+    /// assert!(flow.scopes().iter().any(|scope| scope == "user:inference"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn base_domain(&self) -> Option<String> {
+        url::Url::parse(&self.authorization_url)
+            .ok()?
+            .host_str()
+            .map(str::to_string)
+    }
+
+    /// Get the `client_id` sent in the authorization URL
+    pub fn client_id(&self) -> Option<String> {
+        self.query_param("client_id")
+    }
+
+    /// Get the scopes requested in the authorization URL
+    pub fn scopes(&self) -> Vec<String> {
+        self.query_param("scope")
+            .map(|scope| scope.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the PKCE `code_challenge` sent in the authorization URL
+    pub fn code_challenge(&self) -> Option<String> {
+        self.query_param("code_challenge")
+    }
+
+    /// Get the PKCE `code_challenge_method` sent in the authorization URL (`S256`)
+    pub fn code_challenge_method(&self) -> Option<String> {
+        self.query_param("code_challenge_method")
+    }
+
+    /// Get any query parameter of the authorization URL (e.g. `response_type`)
+    pub fn query_param(&self, name: &str) -> Option<String> {
+        url::Url::parse(&self.authorization_url)
+            .ok()?
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    }
+}
+
+/// Authorization code and state received on the OAuth callback
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallbackData {
+    /// The authorization code
+    pub code: String,
+    /// The state token, already validated against the expected state
+    pub state: String,
+}
+
+/// Authorization captured by a login that did not complete
+///
+/// Returned inside [`AnthropicAuthError::LoginIncomplete`](crate::AnthropicAuthError::LoginIncomplete)
+/// when the user already authorized but the code exchange failed transiently.
+/// Pass it to `OAuthClient::resume_login` to retry the exchange without a new
+/// authorization. Authorization codes are short-lived, so resume promptly.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncompleteLogin {
+    /// The captured authorization code and state
+    pub callback: CallbackData,
+    /// The PKCE verifier of the flow
+    pub verifier: PkceVerifier,
+    /// The OAuth mode of the flow
+    pub mode: OAuthMode,
+    /// The redirect URI of the flow, which the exchange must repeat; the resuming
+    /// client's configured one if unknown (e.g. persisted by an older version)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirect_uri: Option<String>,
+    /// Why the login did not complete
+    pub reason: String,
 }
 
 /// Configuration for the Anthropic OAuth client
@@ -93,15 +786,328 @@ pub struct OAuthFlow {
 pub struct OAuthConfig {
     /// OAuth client ID (default: "9d1c250a-e61b-44d9-88ed-5944d1962f5e")
     pub client_id: String,
-    /// Redirect URI for OAuth callback (default: "http://localhost:1455/callback")
-    pub redirect_uri: String,
+    /// Where the browser is sent after authorizing
+    /// (default: [`RedirectStrategy::ManualPaste`])
+    pub redirect: RedirectStrategy,
+    /// Extra headers sent with every token, refresh, and API key request (default: none)
+    pub default_headers: Vec<(String, String)>,
+    /// Space-separated scopes requested during authorization
+    /// (default: "org:create_api_key user:profile user:inference")
+    pub scope: String,
+    /// Endpoint overrides, e.g. for staging environments (default: Anthropic production endpoints)
+    pub endpoints: Option<Endpoints>,
+    /// Random source for PKCE verifiers and state tokens (default: `rand::thread_rng`)
+    pub rng: Option<FlowRng>,
+    /// Time limit for establishing a connection (default: the HTTP backend's default;
+    /// ignored on `wasm32`)
+    pub connect_timeout: Option<Duration>,
+    /// Time limit for a whole token, refresh, or API key request
+    /// (default: the HTTP backend's default; ignored on `wasm32`)
+    pub request_timeout: Option<Duration>,
+    /// Minimum estimated entropy, in bits, of the expected state token passed to
+    /// `exchange`; `0` disables the check (default: [`DEFAULT_MIN_STATE_ENTROPY`](crate::DEFAULT_MIN_STATE_ENTROPY))
+    pub min_state_entropy: u32,
+    /// OAuth protocol details spoken with the server (default: [`ProtocolProfile::AnthropicV1`])
+    pub protocol: ProtocolProfile,
+    /// Proxy URL for token, refresh, and API key requests, e.g. "http://proxy:3128"
+    /// (default: none; ignored on `wasm32`)
+    pub proxy: Option<String>,
+    /// How far the local clock may drift from the server's in either direction
+    /// (default: zero)
+    ///
+    /// Applied to expiry checks and token sanity checks, so a drifting clock
+    /// neither treats fresh tokens as expired nor rejects them as too far in the future.
+    pub clock_skew: Duration,
+    /// Source of the current time for expiry checks (default: [`SystemClock`])
+    ///
+    /// Lets tests control time, or machines with an unreliable clock plug in
+    /// corrected time.
+    pub clock: Option<Arc<dyn Clock>>,
+    /// How long before `expires_at` tokens already count as expired and get
+    /// refreshed (default: [`DEFAULT_EXPIRY_BUFFER`], 5 minutes)
+    pub expiry_buffer: Duration,
+    /// Whether to sleep and retry when rate limited (HTTP 429), up to 3 times
+    /// (default: `false`)
+    ///
+    /// Waits as long as the `Retry-After` header asks, capped at a minute, then
+    /// gives up with
+    /// [`AnthropicAuthError::RateLimited`](crate::AnthropicAuthError::RateLimited).
+    ///
+    /// **Note:** The async client needs `tokio` to sleep, which features such as
+    /// `token-manager` and `callback-server` enable; without it, or on `wasm32`,
+    /// it always returns the error.
+    pub respect_rate_limits: bool,
+    /// Webhook reporting logins, refreshes, created API keys and required
+    /// re-authentication (default: none)
+    pub notifier: Option<crate::WebhookNotifier>,
+    /// Called with the new tokens after every successful exchange or refresh
+    /// (default: none)
+    pub on_tokens_updated: Option<TokensUpdatedHook>,
+    /// Called with the progress of logins run by the high-level helpers
+    /// (default: none)
+    pub on_auth_event: Option<crate::AuthEventHook>,
+    /// Receives an audit trail of logins, refreshes, refresh failures and
+    /// created API keys (default: none)
+    pub audit_sink: Option<Arc<dyn crate::AuditSink>>,
+    /// Whether clients keep sanitized transcripts of their most recent HTTP
+    /// exchanges, returned by `last_exchanges()` (default: `false`)
+    ///
+    /// Codes, verifiers, state tokens, access and refresh tokens and API keys
+    /// are scrubbed from the transcripts.
+    pub debug_http: bool,
+    /// Whether clients answer OAuth requests with synthetic tokens and API keys
+    /// instead of contacting Anthropic (default: `false`)
+    ///
+    /// See [`OAuthConfigBuilder::dry_run`].
+    pub dry_run: bool,
+}
+
+/// Random number generator used to create PKCE verifiers and state tokens
+///
+/// Lets tests produce reproducible flows with [`FlowRng::from_seed`], or lets
+/// security-sensitive applications supply a vetted CSPRNG with [`FlowRng::new`].
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{FlowRng, OAuthConfig};
+///
+/// // Same seed, same verifier and state
+/// let config = OAuthConfig::builder().rng(FlowRng::from_seed(42)).build();
+/// ```
+#[derive(Clone)]
+pub struct FlowRng(Arc<Mutex<dyn RngCore + Send>>);
+
+impl FlowRng {
+    /// Use the given random number generator
+    pub fn new(rng: impl RngCore + Send + 'static) -> Self {
+        Self(Arc::new(Mutex::new(rng)))
+    }
+
+    /// Use a deterministic generator seeded with `seed`
+    ///
+    /// **Warning:** Only for tests - flows become predictable.
+    pub fn from_seed(seed: u64) -> Self {
+        Self::new(StdRng::seed_from_u64(seed))
+    }
+
+    /// Fill `dest` with random bytes
+    pub fn fill_bytes(&self, dest: &mut [u8]) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .fill_bytes(dest);
+    }
+}
+
+impl std::fmt::Debug for FlowRng {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FlowRng(..)")
+    }
+}
+
+/// Callback run with the new tokens after every successful exchange or refresh
+///
+/// Lets applications persist tokens, and especially rotated refresh tokens, as
+/// soon as they are issued instead of wrapping every call site. Token managers
+/// refresh through their client, so their refreshes run the hook too. Keep it
+/// quick: it runs before the exchange or refresh returns.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{MemoryTokenStore, OAuthConfig, TokenStore};
+///
+/// let store = MemoryTokenStore::new();
+/// let config = OAuthConfig::builder()
+///     .on_tokens_updated(move |tokens| {
+///         if let Err(e) = store.save(tokens) {
+///             eprintln!("Failed to persist tokens: {}", e);
+///         }
+///     })
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct TokensUpdatedHook(Arc<dyn Fn(&TokenSet) + Send + Sync>);
+
+impl TokensUpdatedHook {
+    /// Wrap a callback
+    pub fn new(hook: impl Fn(&TokenSet) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Run the callback
+    pub fn call(&self, tokens: &TokenSet) {
+        (self.0)(tokens)
+    }
+}
+
+impl std::fmt::Debug for TokensUpdatedHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TokensUpdatedHook(..)")
+    }
+}
+
+/// Version of the OAuth protocol details spoken with the server
+///
+/// Anthropic's OAuth endpoints currently differ from plain OAuth 2.0 in a few
+/// ways. The profile selects which behavior the clients use, so the crate can talk
+/// to current servers and to servers that follow the standard at the same time.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{OAuthConfig, ProtocolProfile};
+///
+/// // E.g. a standards-compliant staging server
+/// let config = OAuthConfig::builder()
+///     .protocol(ProtocolProfile::Standard)
+///     .build();
+/// assert!(!config.protocol.echoes_state_in_token_request());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum ProtocolProfile {
+    /// Anthropic's current behavior: `code=true` in the authorization URL, the
+    /// authorization page shows a `code#state` string, and the state is sent in
+    /// the token request
+    #[default]
+    AnthropicV1,
+    /// Plain OAuth 2.0 (RFC 6749 with PKCE): none of the above
+    Standard,
+}
+
+impl ProtocolProfile {
+    /// Whether `code=true` is added to the authorization URL
+    pub fn sends_code_param(self) -> bool {
+        matches!(self, Self::AnthropicV1)
+    }
+
+    /// Whether pasted authorization responses use the `code#state` format
+    ///
+    /// Otherwise the pasted response is used verbatim as the code.
+    pub fn uses_code_with_state(self) -> bool {
+        matches!(self, Self::AnthropicV1)
+    }
+
+    /// Whether the state is sent in the token exchange request
+    pub fn echoes_state_in_token_request(self) -> bool {
+        matches!(self, Self::AnthropicV1)
+    }
+}
+
+/// Where the browser is sent after the user authorized
+///
+/// Selects the `redirect_uri` of the authorization URL and token exchange. Set it
+/// with [`OAuthConfigBuilder::redirect`], or with
+/// [`redirect_port`](OAuthConfigBuilder::redirect_port) and
+/// [`redirect_uri`](OAuthConfigBuilder::redirect_uri). The redirect URI must be
+/// registered for the client ID.
+///
+/// # Example
+///
+/// ```
+/// use anthropic_auth::{OAuthConfig, RedirectStrategy};
+///
+/// // Capture the callback with a local callback server on port 8080
+/// let config = OAuthConfig::builder()
+///     .redirect(RedirectStrategy::Localhost(8080))
+///     .build();
+/// assert_eq!(config.redirect_uri(), "http://localhost:8080/callback");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum RedirectStrategy {
+    /// Anthropic's console page shows the authorization response (`code#state`)
+    /// for the user to paste into the app
+    #[default]
+    ManualPaste,
+    /// `http://localhost:<port>/callback`, served by a local callback server
+    ///
+    /// The one-call logins serve this fixed path, without
+    /// `CallbackServerConfig::secret_path`: the authorization server only
+    /// redirects to URIs registered for the client ID, and a per-flow path would
+    /// not be. The callback route is still protected: it only answers loopback
+    /// `Host` headers, and only a request carrying the flow's own state ends the
+    /// wait, so other local programs can neither complete nor cancel the login.
+    /// For a secret path, register a redirect URI prefix for your own client ID
+    /// and run a `BlockingCallbackServer` yourself.
+    Localhost(u16),
+    /// Any other redirect URI, e.g. a web app's callback route
+    Custom(String),
+}
+
+impl RedirectStrategy {
+    /// Pick the strategy matching a redirect URI
+    ///
+    /// Anthropic's console callback is [`ManualPaste`](Self::ManualPaste) and
+    /// `http://localhost:<port>/callback` is [`Localhost`](Self::Localhost);
+    /// anything else is [`Custom`](Self::Custom).
+    pub fn from_uri(uri: impl Into<String>) -> Self {
+        let uri = uri.into();
+        if uri == MANUAL_REDIRECT_URI {
+            return Self::ManualPaste;
+        }
+        let port = uri
+            .strip_prefix("http://localhost:")
+            .and_then(|rest| rest.strip_suffix("/callback"))
+            .and_then(|port| port.parse().ok());
+        match port {
+            Some(port) => Self::Localhost(port),
+            None => Self::Custom(uri),
+        }
+    }
+
+    /// Get the redirect URI sent to the server
+    pub fn redirect_uri(&self) -> String {
+        match self {
+            Self::ManualPaste => MANUAL_REDIRECT_URI.to_string(),
+            Self::Localhost(port) => format!("http://localhost:{}/callback", port),
+            Self::Custom(uri) => uri.clone(),
+        }
+    }
+}
+
+/// OAuth endpoint URLs
+///
+/// Overrides the production endpoints, e.g. to authenticate against a staging
+/// environment. When set, `authorize_url` is used for every OAuth mode but [`OAuthMode::Custom`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Endpoints {
+    /// Authorization page the user is sent to
+    pub authorize_url: String,
+    /// Token exchange and refresh endpoint
+    pub token_url: String,
+    /// API key creation endpoint
+    pub api_key_url: String,
 }
 
 impl Default for OAuthConfig {
     fn default() -> Self {
         Self {
             client_id: "9d1c250a-e61b-44d9-88ed-5944d1962f5e".to_string(),
-            redirect_uri: "http://localhost:1455/callback".to_string(),
+            redirect: RedirectStrategy::default(),
+            default_headers: Vec::new(),
+            scope: "org:create_api_key user:profile user:inference".to_string(),
+            endpoints: None,
+            rng: None,
+            connect_timeout: None,
+            request_timeout: None,
+            min_state_entropy: crate::DEFAULT_MIN_STATE_ENTROPY,
+            protocol: ProtocolProfile::default(),
+            proxy: None,
+            clock_skew: Duration::ZERO,
+            clock: None,
+            expiry_buffer: DEFAULT_EXPIRY_BUFFER,
+            respect_rate_limits: false,
+            notifier: None,
+            on_tokens_updated: None,
+            on_auth_event: None,
+            audit_sink: None,
+            debug_http: false,
+            dry_run: false,
         }
     }
 }
@@ -111,13 +1117,71 @@ impl OAuthConfig {
     pub fn builder() -> OAuthConfigBuilder {
         OAuthConfigBuilder::default()
     }
+
+    /// Report login progress to the `on_auth_event` hook, if any
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq"))]
+    pub(crate) fn emit(&self, event: crate::AuthEvent) {
+        if let Some(hook) = &self.on_auth_event {
+            hook.call(&event);
+        }
+    }
+
+    /// Record an event with the audit sink, if any
+    #[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
+    pub(crate) fn audit(&self, event: crate::AuditEvent, account: Option<&Organization>) {
+        if let Some(sink) = &self.audit_sink {
+            sink.record(&crate::AuditRecord {
+                timestamp: self.clock().unix_secs(),
+                event,
+                account: account.map(|organization| organization.uuid.clone()),
+            });
+        }
+    }
+
+    /// Get the redirect URI sent in the authorization URL and token exchange
+    pub fn redirect_uri(&self) -> String {
+        self.redirect.redirect_uri()
+    }
+
+    /// Get the configured clock, or the [`SystemClock`]
+    pub fn clock(&self) -> &dyn Clock {
+        match &self.clock {
+            Some(clock) => clock.as_ref(),
+            None => &SystemClock,
+        }
+    }
+
+    /// Check if tokens are expired or expire within the expiry buffer, by the
+    /// configured clock and allowing for the configured clock skew
+    pub fn is_expired(&self, tokens: &TokenSet) -> bool {
+        tokens.is_expired_with(self.clock(), self.clock_skew, self.expiry_buffer)
+    }
 }
 
 /// Builder for OAuthConfig
 #[derive(Debug, Clone, Default)]
 pub struct OAuthConfigBuilder {
     client_id: Option<String>,
-    redirect_uri: Option<String>,
+    redirect: Option<RedirectStrategy>,
+    default_headers: Vec<(String, String)>,
+    scope: Option<String>,
+    endpoints: Option<Endpoints>,
+    rng: Option<FlowRng>,
+    connect_timeout: Option<Duration>,
+    request_timeout: Option<Duration>,
+    min_state_entropy: Option<u32>,
+    protocol: Option<ProtocolProfile>,
+    proxy: Option<String>,
+    clock_skew: Option<Duration>,
+    clock: Option<Arc<dyn Clock>>,
+    expiry_buffer: Option<Duration>,
+    respect_rate_limits: Option<bool>,
+    notifier: Option<crate::WebhookNotifier>,
+    on_tokens_updated: Option<TokensUpdatedHook>,
+    on_auth_event: Option<crate::AuthEventHook>,
+    audit_sink: Option<Arc<dyn crate::AuditSink>>,
+    debug_http: Option<bool>,
+    dry_run: Option<bool>,
 }
 
 impl OAuthConfigBuilder {
@@ -127,24 +1191,233 @@ impl OAuthConfigBuilder {
         self
     }
 
-    /// Set the redirect URI
-    pub fn redirect_uri(mut self, redirect_uri: impl Into<String>) -> Self {
-        self.redirect_uri = Some(redirect_uri.into());
+    /// Set where the browser is sent after authorizing
+    pub fn redirect(mut self, redirect: RedirectStrategy) -> Self {
+        self.redirect = Some(redirect);
+        self
+    }
+
+    /// Set the redirect URI (see [`RedirectStrategy::from_uri`])
+    pub fn redirect_uri(self, redirect_uri: impl Into<String>) -> Self {
+        self.redirect(RedirectStrategy::from_uri(redirect_uri))
+    }
+
+    /// Redirect to a local callback server at `http://localhost:<port>/callback`
+    pub fn redirect_port(self, port: u16) -> Self {
+        self.redirect(RedirectStrategy::Localhost(port))
+    }
+
+    /// Add a header sent with every OAuth request
+    ///
+    /// Useful for corporate gateways or proxies that require their own
+    /// authentication or tracing headers. Can be called multiple times.
+    /// Header names and values are validated when the client is created.
+    pub fn default_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.default_headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Set the space-separated scopes requested during authorization
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scope = Some(scope.into());
         self
     }
 
-    /// Set the redirect URI with a custom port
-    pub fn redirect_port(mut self, port: u16) -> Self {
-        self.redirect_uri = Some(format!("http://localhost:{}/callback", port));
+    /// Override the OAuth endpoints
+    pub fn endpoints(mut self, endpoints: Endpoints) -> Self {
+        self.endpoints = Some(endpoints);
+        self
+    }
+
+    /// Set the random source for PKCE verifiers and state tokens
+    pub fn rng(mut self, rng: FlowRng) -> Self {
+        self.rng = Some(rng);
+        self
+    }
+
+    /// Set the time limit for establishing a connection
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the time limit for a whole token, refresh, or API key request
+    ///
+    /// Bounds calls against a hung endpoint; a timed-out request fails with a
+    /// network error.
+    pub fn request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the minimum estimated entropy (in bits) of state tokens; `0` disables the check
+    ///
+    /// Guards against applications passing weak, guessable state values such as
+    /// `"state"` to `exchange`.
+    pub fn min_state_entropy(mut self, bits: u32) -> Self {
+        self.min_state_entropy = Some(bits);
+        self
+    }
+
+    /// Set the OAuth protocol details spoken with the server
+    pub fn protocol(mut self, protocol: ProtocolProfile) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
+    /// Send token, refresh, and API key requests through a proxy
+    ///
+    /// The URL is validated when the client is created.
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Set how far the local clock may drift from the server's
+    pub fn clock_skew(mut self, skew: Duration) -> Self {
+        self.clock_skew = Some(skew);
+        self
+    }
+
+    /// Set the source of the current time for expiry checks
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Set how long before expiry tokens already count as expired
+    ///
+    /// Larger buffers refresh earlier, which helps with long-running requests;
+    /// smaller ones suit short-lived tokens.
+    pub fn expiry_buffer(mut self, buffer: Duration) -> Self {
+        self.expiry_buffer = Some(buffer);
+        self
+    }
+
+    /// Set whether to sleep and retry when rate limited
+    pub fn respect_rate_limits(mut self, respect: bool) -> Self {
+        self.respect_rate_limits = Some(respect);
+        self
+    }
+
+    /// Set a webhook to report token lifecycle events to
+    pub fn notifier(mut self, notifier: crate::WebhookNotifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Set a callback run with the new tokens after every successful exchange or refresh
+    pub fn on_tokens_updated(mut self, hook: impl Fn(&TokenSet) + Send + Sync + 'static) -> Self {
+        self.on_tokens_updated = Some(TokensUpdatedHook::new(hook));
+        self
+    }
+
+    /// Set a callback run with the progress of logins run by the high-level helpers
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::{AuthEvent, OAuthConfig};
+    ///
+    /// let config = OAuthConfig::builder()
+    ///     .on_auth_event(|event| match event {
+    ///         AuthEvent::FlowStarted { url } => println!("Sign in at {}", url),
+    ///         AuthEvent::WaitingForCallback => println!("Waiting for you to sign in..."),
+    ///         AuthEvent::TokensReceived => println!("Signed in"),
+    ///         AuthEvent::Error(error) => eprintln!("Sign-in failed: {}", error),
+    ///         _ => {}
+    ///     })
+    ///     .build();
+    /// ```
+    pub fn on_auth_event(
+        mut self,
+        hook: impl Fn(&crate::AuthEvent) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_auth_event = Some(crate::AuthEventHook::new(hook));
+        self
+    }
+
+    /// Set a sink receiving an audit trail of authentication events
+    ///
+    /// See [`AuditSink`](crate::AuditSink) for what is recorded.
+    pub fn audit_sink(mut self, sink: impl crate::AuditSink + 'static) -> Self {
+        self.audit_sink = Some(Arc::new(sink));
+        self
+    }
+
+    /// Set whether clients record sanitized transcripts of their HTTP exchanges
+    ///
+    /// Meant for diagnosing failed flows; see `last_exchanges()` on the clients.
+    pub fn debug_http(mut self, enabled: bool) -> Self {
+        self.debug_http = Some(enabled);
+        self
+    }
+
+    /// Set whether clients simulate the OAuth server instead of contacting it
+    ///
+    /// In dry-run mode, token exchanges and refreshes return well-formed but
+    /// synthetic tokens, and API key creation returns a synthetic key, so examples,
+    /// tutorials and tests run without real Anthropic credentials. Authorization
+    /// responses are still parsed and their state checked. Webhook events are not
+    /// sent. The synthetic tokens are not accepted by any Anthropic API.
+    ///
+    /// Only settable in code, never from a config file, so a leftover setting
+    /// can't make a production binary hand out synthetic tokens.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use anthropic_auth::{ExchangeRequest, OAuthClient, OAuthConfig, OAuthMode};
+    ///
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = OAuthClient::new(OAuthConfig::builder().dry_run(true).build())?;
+    /// let flow = client.start_flow(OAuthMode::Max)?;
+    ///
+    /// // Stands in for the `code#state` the user would paste
+    /// let response = format!("dry-run-authorization-code#{}", flow.state);
+    /// let tokens = client.exchange(&ExchangeRequest::new(&response, &flow))?;
+    /// assert!(!tokens.is_expired());
+    ///
+    /// let refreshed = client.refresh_token(&tokens.refresh_token)?;
+    /// assert_ne!(refreshed.access_token, tokens.access_token);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn dry_run(mut self, enabled: bool) -> Self {
+        self.dry_run = Some(enabled);
         self
     }
 
     /// Build the OAuthConfig
+    ///
+    /// Doesn't check the values; use [`try_build`](Self::try_build) to catch an
+    /// invalid client ID, redirect URI or endpoint early.
     pub fn build(self) -> OAuthConfig {
         let defaults = OAuthConfig::default();
         OAuthConfig {
             client_id: self.client_id.unwrap_or(defaults.client_id),
-            redirect_uri: self.redirect_uri.unwrap_or(defaults.redirect_uri),
+            redirect: self.redirect.unwrap_or(defaults.redirect),
+            default_headers: self.default_headers,
+            scope: self.scope.unwrap_or(defaults.scope),
+            endpoints: self.endpoints,
+            rng: self.rng,
+            connect_timeout: self.connect_timeout,
+            request_timeout: self.request_timeout,
+            min_state_entropy: self.min_state_entropy.unwrap_or(defaults.min_state_entropy),
+            protocol: self.protocol.unwrap_or(defaults.protocol),
+            proxy: self.proxy,
+            clock_skew: self.clock_skew.unwrap_or(defaults.clock_skew),
+            clock: self.clock,
+            expiry_buffer: self.expiry_buffer.unwrap_or(defaults.expiry_buffer),
+            respect_rate_limits: self
+                .respect_rate_limits
+                .unwrap_or(defaults.respect_rate_limits),
+            notifier: self.notifier,
+            on_tokens_updated: self.on_tokens_updated,
+            on_auth_event: self.on_auth_event,
+            audit_sink: self.audit_sink,
+            debug_http: self.debug_http.unwrap_or(defaults.debug_http),
+            dry_run: self.dry_run.unwrap_or(defaults.dry_run),
         }
     }
 }
@@ -155,25 +1428,58 @@ pub(crate) struct TokenResponse {
     pub access_token: String,
     pub refresh_token: Option<String>,
     pub expires_in: Option<u64>,
+    pub scope: Option<String>,
+    pub token_type: Option<String>,
+    // Kept as raw JSON so an unexpected shape doesn't fail the whole token response
+    pub organization: Option<serde_json::Value>,
+    pub account: Option<serde_json::Value>,
 }
 
-impl From<TokenResponse> for TokenSet {
-    fn from(response: TokenResponse) -> Self {
-        let expires_at = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-            + response.expires_in.unwrap_or(3600);
-
+impl TokenResponse {
+    /// Convert into a token set, computing `expires_at` by the time of `clock`
+    pub(crate) fn into_token_set(self, clock: &dyn Clock) -> TokenSet {
+        let now = clock.unix_secs();
         TokenSet {
-            access_token: response.access_token,
-            refresh_token: response.refresh_token.unwrap_or_default(),
-            expires_at,
+            access_token: self.access_token,
+            refresh_token: self.refresh_token.unwrap_or_default(),
+            expires_at: now + self.expires_in.unwrap_or(3600),
+            scope: self.scope,
+            token_type: self.token_type,
+            organization: self
+                .organization
+                .and_then(|value| serde_json::from_value(value).ok()),
+            obtained_at: Some(now),
+            account_id: self.account.and_then(|account| {
+                account
+                    .get("uuid")
+                    .and_then(|uuid| uuid.as_str())
+                    .map(str::to_string)
+            }),
         }
     }
 }
 
+impl From<TokenResponse> for TokenSet {
+    fn from(response: TokenResponse) -> Self {
+        response.into_token_set(&SystemClock)
+    }
+}
+
+/// Options for API key creation
+///
+/// Unset fields are omitted from the request and left to the server's defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ApiKeyOptions {
+    /// Display name for the key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Workspace the key should be created in
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<String>,
+}
+
 /// API key creation response
+#[cfg(any(feature = "blocking", feature = "blocking-ureq", feature = "async"))]
 #[derive(Debug, Deserialize)]
 pub(crate) struct ApiKeyResponse {
     pub raw_key: String,
@@ -0,0 +1,158 @@
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+use crate::{AsyncOAuthClient, TokenSet};
+
+/// How long before expiry tokens are refreshed by default (5 minutes, matching
+/// [`TokenSet::is_expired`] and the default [`OAuthConfig::expiry_buffer`](crate::OAuthConfig::expiry_buffer))
+pub const DEFAULT_REFRESH_AHEAD: Duration = Duration::from_secs(300);
+
+/// Delay between retries after a failed refresh
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Shortest time between two refreshes, in case the server issues tokens that
+/// don't outlive `refresh_ahead`
+const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Keeps an access token fresh in the background for long-lived connections
+///
+/// Designed for SSE/WebSocket clients that must rotate their `Authorization`
+/// header mid-stream: the watcher refreshes the tokens ahead of expiry and
+/// publishes every new `TokenSet` over a tokio `watch` channel. When a refresh
+/// fails and expiry gets close, [`TokenWatcher::expiring`] flips to `true` so the
+/// stream can be wound down gracefully instead of failing with a 401. When the
+/// server rejects the refresh token, [`TokenWatcher::reauth_required`] flips to
+/// `true` and the watcher stops; the user must log in again.
+///
+/// The background task is aborted when the watcher is dropped.
+///
+/// # Example
+///
+/// ```no_run
+/// use anthropic_auth::{AsyncOAuthClient, OAuthConfig, TokenWatcher};
+///
+/// # async fn example(tokens: anthropic_auth::TokenSet) -> Result<(), Box<dyn std::error::Error>> {
+/// let client = AsyncOAuthClient::new(OAuthConfig::default())?;
+/// let watcher = TokenWatcher::spawn(client, tokens);
+///
+/// let mut updates = watcher.subscribe();
+/// loop {
+///     updates.changed().await?;
+///     let token = updates.borrow_and_update().access_token.clone();
+///     println!("Rotating Authorization header ({} chars)", token.len());
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct TokenWatcher {
+    tokens: watch::Receiver<TokenSet>,
+    expiring: watch::Receiver<bool>,
+    reauth_required: watch::Receiver<bool>,
+    task: JoinHandle<()>,
+}
+
+impl TokenWatcher {
+    /// Start watching `tokens`, refreshing the client's configured
+    /// [`expiry_buffer`](crate::OAuthConfig::expiry_buffer) before expiry
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn spawn(client: AsyncOAuthClient, tokens: TokenSet) -> Self {
+        let refresh_ahead = client.config().expiry_buffer;
+        Self::spawn_with(client, tokens, refresh_ahead)
+    }
+
+    /// Start watching `tokens`, refreshing `refresh_ahead` before expiry
+    ///
+    /// Expiry is judged by the client's configured clock and clock skew.
+    ///
+    /// Must be called from within a tokio runtime.
+    pub fn spawn_with(client: AsyncOAuthClient, tokens: TokenSet, refresh_ahead: Duration) -> Self {
+        let (tokens_tx, tokens_rx) = watch::channel(tokens);
+        let (expiring_tx, expiring_rx) = watch::channel(false);
+        let (reauth_tx, reauth_rx) = watch::channel(false);
+
+        let task = tokio::spawn(async move {
+            loop {
+                let current = tokens_tx.borrow().clone();
+                let config = client.config();
+                let expires_in = current.expires_in_with(config.clock(), config.clock_skew);
+                tokio::time::sleep(expires_in.saturating_sub(refresh_ahead)).await;
+
+                match client.refresh_token_with_fallback(&current).await {
+                    Ok(fresh) => {
+                        expiring_tx.send_replace(false);
+                        if tokens_tx.send(fresh).is_err() {
+                            break;
+                        }
+                        tokio::time::sleep(MIN_REFRESH_INTERVAL).await;
+                    }
+                    Err(e) if e.requires_reauth() => {
+                        // Retrying a rejected refresh token can't succeed
+                        expiring_tx.send_replace(true);
+                        reauth_tx.send_replace(true);
+                        break;
+                    }
+                    Err(_) => {
+                        // We're inside the refresh window, so expiry is imminent
+                        expiring_tx.send_replace(true);
+                        tokio::time::sleep(RETRY_DELAY).await;
+                    }
+                }
+            }
+        });
+
+        Self {
+            tokens: tokens_rx,
+            expiring: expiring_rx,
+            reauth_required: reauth_rx,
+            task,
+        }
+    }
+
+    /// Get the freshest token set
+    pub fn tokens(&self) -> TokenSet {
+        self.tokens.borrow().clone()
+    }
+
+    /// Get the freshest access token
+    pub fn access_token(&self) -> String {
+        self.tokens.borrow().access_token.clone()
+    }
+
+    /// Subscribe to token updates
+    ///
+    /// The receiver is notified every time the tokens are refreshed.
+    pub fn subscribe(&self) -> watch::Receiver<TokenSet> {
+        self.tokens.clone()
+    }
+
+    /// Subscribe to the impending-expiry signal
+    ///
+    /// The value is `true` while the current token is inside the refresh window
+    /// and refreshing it has failed; it goes back to `false` after the next
+    /// successful refresh.
+    pub fn expiring(&self) -> watch::Receiver<bool> {
+        self.expiring.clone()
+    }
+
+    /// Subscribe to the reauthentication signal
+    ///
+    /// The value becomes `true`, and the watcher stops refreshing, once the server
+    /// rejects the refresh token (see [`AnthropicAuthError::requires_reauth`](crate::AnthropicAuthError::requires_reauth)).
+    /// The current tokens stay available until they expire.
+    pub fn reauth_required(&self) -> watch::Receiver<bool> {
+        self.reauth_required.clone()
+    }
+
+    /// Stop the background refresh task
+    pub fn stop(self) {
+        // Dropping aborts the task
+    }
+}
+
+impl Drop for TokenWatcher {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
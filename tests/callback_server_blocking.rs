@@ -0,0 +1,111 @@
+//! Request parsing of the `std::net` callback server, exercised over raw TCP
+
+#![cfg(feature = "callback-server-blocking")]
+
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+use anthropic_auth::{BlockingCallbackServer, CallbackData, CallbackServerConfig, Result};
+
+const STATE: &str = "expected-state-0123456789";
+
+/// Send raw bytes and return the response's status line
+fn send(addr: SocketAddr, request: &[u8]) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    stream.write_all(request).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response.lines().next().unwrap_or_default().to_string()
+}
+
+fn get(addr: SocketAddr, target: &str) -> String {
+    send(
+        addr,
+        format!("GET {} HTTP/1.1\r\nHost: {}\r\n\r\n", target, addr).as_bytes(),
+    )
+}
+
+/// Run `requests` against a waiting server, then finish the flow with a valid callback
+fn with_server(requests: impl FnOnce(SocketAddr)) -> Result<CallbackData> {
+    let server = BlockingCallbackServer::bind(&CallbackServerConfig::with_port(0)).unwrap();
+    let addr = server.local_addr().unwrap();
+    std::thread::scope(|scope| {
+        let waiting = scope.spawn(|| server.wait_timeout(STATE, Duration::from_secs(10)));
+        requests(addr);
+        get(
+            addr,
+            &format!("/callback?code=valid-code-0123456789&state={}", STATE),
+        );
+        waiting.join().unwrap()
+    })
+}
+
+#[test]
+fn malformed_request_line_is_rejected() {
+    let callback = with_server(|addr| {
+        assert_eq!(send(addr, b"GARBAGE\r\n\r\n"), "HTTP/1.1 400 Bad Request");
+        assert_eq!(
+            send(addr, b"GET /callback SPDY/3\r\n\r\n"),
+            "HTTP/1.1 400 Bad Request"
+        );
+    })
+    .unwrap();
+    assert_eq!(callback.code, "valid-code-0123456789");
+}
+
+#[test]
+fn oversize_head_is_rejected() {
+    let callback = with_server(|addr| {
+        // Exactly the 16 KiB limit without the terminating blank line, so the head never ends
+        let mut request = b"GET /callback HTTP/1.1\r\nX-Padding: ".to_vec();
+        request.resize(16 * 1024, b'a');
+        assert_eq!(send(addr, &request), "HTTP/1.1 400 Bad Request");
+    })
+    .unwrap();
+    assert_eq!(callback.code, "valid-code-0123456789");
+}
+
+#[test]
+fn non_get_is_rejected() {
+    let callback = with_server(|addr| {
+        let request = format!(
+            "POST /callback?code=posted-code-0123456789&state={} HTTP/1.1\r\nContent-Length: 0\r\n\r\n",
+            STATE
+        );
+        assert_eq!(
+            send(addr, request.as_bytes()),
+            "HTTP/1.1 405 Method Not Allowed"
+        );
+    })
+    .unwrap();
+    assert_eq!(callback.code, "valid-code-0123456789");
+}
+
+#[test]
+fn duplicate_params_keep_the_first() {
+    let server = BlockingCallbackServer::bind(&CallbackServerConfig::with_port(0)).unwrap();
+    let addr = server.local_addr().unwrap();
+    let callback = std::thread::scope(|scope| {
+        let waiting = scope.spawn(|| server.wait_timeout(STATE, Duration::from_secs(10)));
+        // A later state can't stand in for a wrong first one
+        get(
+            addr,
+            &format!("/callback?code=forged-code-0123456789&state=wrong&state={}", STATE),
+        );
+        get(
+            addr,
+            &format!(
+                "/callback?code=first-code-0123456789&state={}&code=second-code-0123456789&state=wrong",
+                STATE
+            ),
+        );
+        waiting.join().unwrap()
+    })
+    .unwrap();
+    assert_eq!(callback.code, "first-code-0123456789");
+    assert_eq!(callback.state, STATE);
+}